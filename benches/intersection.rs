@@ -0,0 +1,286 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mirror_verse::{
+    bezier_mirror::BezierMirror,
+    circle_mirror::CircleMirror,
+    mirror::{Mirror, PlaneMirror, Reflections, SphereMirror},
+    ray::{ray_from, Ray},
+    scene::Scene,
+    simulation::Simulation,
+    DIM,
+};
+use nalgebra::Point;
+
+/// Hides the bounding sphere of a mirror, so the scene can't skip it.
+struct Unbounded(SphereMirror);
+
+impl Mirror for Unbounded {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.0.reflect(ray)
+    }
+    fn get_type(&self) -> String {
+        self.0.get_type()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.0.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.0.sample_surface(n)
+    }
+}
+
+/// Moves the reflections of a mirror through a `Vec`, allocating for every hit like
+/// [`Mirror::reflect`] did before it returned [`Reflections`].
+struct VecReflections<M>(M);
+
+impl<M: Mirror> Mirror for VecReflections<M> {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        let reflections: Vec<_> = self.0.reflect(ray).into_vec();
+        Reflections::from_vec(reflections)
+    }
+    fn get_type(&self) -> String {
+        self.0.get_type()
+    }
+    fn is_analytic(&self) -> bool {
+        self.0.is_analytic()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.0.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.0.sample_surface(n)
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.0.bounding_sphere()
+    }
+}
+
+fn rays() -> Vec<Ray> {
+    (0..256)
+        .map(|i| {
+            let angle = i as f32 * 0.37;
+            ray_from([-5.0, (i as f32 * 0.1).sin()], [angle.cos(), angle.sin()])
+        })
+        .collect()
+}
+
+fn circle_vs_sphere(c: &mut Criterion) {
+    let rays = rays();
+    let circle = CircleMirror::new([0.0, 0.0].into(), 2.0);
+    let sphere = SphereMirror::new([0.0, 0.0].into(), 2.0);
+
+    let mut group = c.benchmark_group("circle intersection");
+    group.bench_function("CircleMirror", |b| {
+        b.iter(|| {
+            rays.iter()
+                .map(|ray| circle.reflect(black_box(*ray)).len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("SphereMirror", |b| {
+        b.iter(|| {
+            rays.iter()
+                .map(|ray| sphere.reflect(black_box(*ray)).len())
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+// small spheres scattered far apart, which most rays miss
+fn sparse_spheres() -> Vec<SphereMirror> {
+    (0..100)
+        .map(|i| SphereMirror::new([(i % 10) as f32 * 10.0, (i / 10) as f32 * 10.0].into(), 0.5))
+        .collect()
+}
+
+fn bounding_sphere_reject(c: &mut Criterion) {
+    let rays = rays();
+    let bounded = Scene::new(
+        sparse_spheres()
+            .into_iter()
+            .map(|sphere| Box::new(sphere) as Box<dyn Mirror>)
+            .collect(),
+    );
+    let unbounded = Scene::new(
+        sparse_spheres()
+            .into_iter()
+            .map(|sphere| Box::new(Unbounded(sphere)) as Box<dyn Mirror>)
+            .collect(),
+    );
+
+    let mut group = c.benchmark_group("sparse scene nearest hit");
+    group.bench_function("with bounding spheres", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter(|ray| bounded.nearest_hit(black_box(ray)).is_some())
+                .count()
+        })
+    });
+    group.bench_function("without bounding spheres", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter(|ray| unbounded.nearest_hit(black_box(ray)).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn nearest_hit_allocations(c: &mut Criterion) {
+    let rays = rays();
+    let inline = Scene::new(vec![
+        Box::new(SphereMirror::new([0.0, 0.0].into(), 2.0)),
+        Box::new(PlaneMirror::new([[3.0, 0.0].into(), [3.0, 1.0].into()])),
+    ]);
+    let heap = Scene::new(vec![
+        Box::new(VecReflections(SphereMirror::new([0.0, 0.0].into(), 2.0))),
+        Box::new(VecReflections(PlaneMirror::new([
+            [3.0, 0.0].into(),
+            [3.0, 1.0].into(),
+        ]))),
+    ]);
+
+    let mut group = c.benchmark_group("nearest hit allocations");
+    group.bench_function("inline reflections", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter(|ray| inline.nearest_hit(black_box(ray)).is_some())
+                .count()
+        })
+    });
+    group.bench_function("heap reflections", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter(|ray| heap.nearest_hit(black_box(ray)).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+// run with `--features simd` to compare the vectorized evaluation with the scalar one
+fn bezier_evaluation(c: &mut Criterion) {
+    let mirror = BezierMirror::new(
+        (0..12)
+            .map(|i| [i as f32, (i as f32 * 1.3).sin()].into())
+            .collect(),
+    );
+    let ts = (0..1024).map(|i| i as f32 / 1023.0).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("bezier evaluation");
+    group.bench_function("calculate_points", |b| {
+        b.iter(|| mirror.calculate_points(black_box(&ts)))
+    });
+    group.bench_function("calculate_point", |b| {
+        b.iter(|| {
+            black_box(&ts)
+                .iter()
+                .map(|t| mirror.calculate_point(*t))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+// run with `--features parallel` to compare with the evaluation on every thread
+fn dense_bezier_evaluation(c: &mut Criterion) {
+    let mirror = BezierMirror::new(
+        (0..12)
+            .map(|i| [i as f32, (i as f32 * 1.3).sin()].into())
+            .collect(),
+    );
+    let ts = (0..10_000).map(|i| i as f32 / 9999.0).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("dense bezier evaluation");
+    group.bench_function("calculate_point", |b| {
+        b.iter(|| {
+            black_box(&ts)
+                .iter()
+                .map(|t| mirror.calculate_point(*t))
+                .collect::<Vec<_>>()
+        })
+    });
+    #[cfg(feature = "parallel")]
+    group.bench_function("calculate_points_parallel", |b| {
+        b.iter(|| mirror.calculate_points_parallel(black_box(&ts)))
+    });
+    group.finish();
+}
+
+// run with `--features coherent` to bucket the batch by direction
+fn batch_tracing(c: &mut Criterion) {
+    let simulation = Simulation::new(Scene::new(
+        sparse_spheres()
+            .into_iter()
+            .map(|sphere| Box::new(sphere) as Box<dyn Mirror>)
+            .collect(),
+    ));
+    // a shuffled mix of rays aimed at different spheres
+    let rays = (0..1024)
+        .map(|i| {
+            let target = (i * 37) % 100;
+            let angle = (target % 10) as f32 * 0.02 + (target / 10) as f32 * 0.15;
+            ray_from([-5.0, -5.0], [angle.cos(), angle.sin()])
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("batch tracing");
+    group.bench_function("trace_many", |b| {
+        b.iter(|| simulation.trace_many(black_box(&rays), 8))
+    });
+    group.bench_function("one by one", |b| {
+        b.iter(|| {
+            black_box(&rays)
+                .iter()
+                .map(|ray| simulation.trace(*ray, 8))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+fn bundle_tracing(c: &mut Criterion) {
+    let simulation = Simulation::new(Scene::new(
+        sparse_spheres()
+            .into_iter()
+            .map(|sphere| Box::new(sphere) as Box<dyn Mirror>)
+            .collect(),
+    ));
+    // a tightly collimated beam, aimed at the middle of the scene
+    let rays = (0..4096)
+        .map(|i| {
+            let offset = i as f32 / 4096.0 - 0.5;
+            let angle = 0.78 + offset * 0.01;
+            ray_from([-5.0 + offset, -5.0 - offset], [angle.cos(), angle.sin()])
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("bundle tracing");
+    group.bench_function("trace_bundle", |b| {
+        b.iter(|| simulation.trace_bundle(black_box(&rays), 8))
+    });
+    group.bench_function("one by one", |b| {
+        b.iter(|| {
+            black_box(&rays)
+                .iter()
+                .map(|ray| simulation.trace(*ray, 8))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    circle_vs_sphere,
+    bounding_sphere_reject,
+    nearest_hit_allocations,
+    bezier_evaluation,
+    dense_bezier_evaluation,
+    batch_tracing,
+    bundle_tracing
+);
+criterion_main!(benches);
@@ -0,0 +1,669 @@
+use nalgebra::{Point, SMatrix, SVector, Unit};
+
+use crate::{
+    intersection::Intersection,
+    mirror::Mirror,
+    ray::{Ray, DEFAULT_WAVELENGTH},
+    simulation::{absorbs, Simulation, Termination, TraceResult},
+    DIM,
+};
+
+/// Below this smallest eigenvalue (per ray), the rays are considered parallel.
+const PARALLEL_THRESHOLD: f32 = 1e-5;
+/// Reflections after which a ray of a beam is considered trapped, and left where it is.
+const MAX_BEAM_BOUNCES: usize = 64;
+
+/// Centroid of a set of points, `None` if there are none.
+pub fn centroid(points: &[Point<f32, DIM>]) -> Option<Point<f32, DIM>> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let sum = points
+        .iter()
+        .fold(Point::origin(), |sum, point| sum + point.coords);
+    Some(sum / points.len() as f32)
+}
+
+/// Root mean square distance of the points to their centroid, the usual measure of a spot's size.
+pub fn rms_spot_size(points: &[Point<f32, DIM>]) -> f32 {
+    let Some(centroid) = centroid(points) else {
+        return 0.0;
+    };
+
+    let mean_squared = points
+        .iter()
+        .map(|point| (point - centroid).norm_squared())
+        .sum::<f32>()
+        / points.len() as f32;
+
+    mean_squared.sqrt()
+}
+
+/// Centroid of a set of points weighted by their `intensities`, like the rays of a
+/// polychromatic bundle dimmed by their reflections. `None` if there isn't one intensity per
+/// point, if one of them is negative or not finite, or if their total is zero.
+pub fn weighted_centroid(
+    points: &[Point<f32, DIM>],
+    intensities: &[f32],
+) -> Option<Point<f32, DIM>> {
+    if points.len() != intensities.len()
+        || !intensities
+            .iter()
+            .all(|intensity| *intensity >= 0.0 && intensity.is_finite())
+    {
+        return None;
+    }
+    let total = intensities.iter().sum::<f32>();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let sum = points
+        .iter()
+        .zip(intensities)
+        .fold(SVector::zeros(), |sum, (point, intensity)| {
+            sum + *intensity * point.coords
+        });
+    Some(Point::from(sum / total))
+}
+
+/// Like [`rms_spot_size`], every point counting in proportion to its intensity. `None` when
+/// the intensities have no [`weighted_centroid`].
+pub fn weighted_rms_spot_size(points: &[Point<f32, DIM>], intensities: &[f32]) -> Option<f32> {
+    let centroid = weighted_centroid(points, intensities)?;
+
+    let (sum, total) =
+        points
+            .iter()
+            .zip(intensities)
+            .fold((0.0, 0.0), |(sum, total), (point, intensity)| {
+                (
+                    sum + intensity * (point - centroid).norm_squared(),
+                    total + intensity,
+                )
+            });
+
+    Some((sum / total).sqrt())
+}
+
+/// The point minimizing the sum of squared distances to the lines supporting `rays`,
+/// or `None` if the rays are (nearly) parallel and don't converge anywhere.
+pub fn least_squares_focus(rays: &[Ray]) -> Option<Point<f32, DIM>> {
+    let mut matrix = SMatrix::<f32, DIM, DIM>::zeros();
+    let mut vector = SVector::<f32, DIM>::zeros();
+
+    for ray in rays {
+        let direction = ray.direction.into_inner();
+        let projection = SMatrix::identity() - direction * direction.transpose();
+        matrix += projection;
+        vector += projection * ray.origin.coords;
+    }
+
+    // parallel rays leave their common direction unconstrained
+    if matrix.symmetric_eigen().eigenvalues.min() < PARALLEL_THRESHOLD * rays.len() as f32 {
+        return None;
+    }
+
+    matrix
+        .try_inverse()
+        .map(|inverse| Point::from(inverse * vector))
+}
+
+/// How `mirror` redirects light coming from `incoming`: parallel rays are aimed at `samples`
+/// points spread over its surface, and every one that hits the mirror gives its hit point and
+/// outgoing direction. A ray can hit another part of the mirror than the one it aimed at,
+/// when that part shadows it. Rays the mirror absorbs, like a [`Simulation`] would, are left
+/// out.
+pub fn reflection_map(
+    mirror: &dyn Mirror,
+    incoming: Unit<SVector<f32, DIM>>,
+    samples: usize,
+) -> Vec<(Point<f32, DIM>, Unit<SVector<f32, DIM>>)> {
+    let targets = mirror.sample_surface(samples);
+    let Some(center) = centroid(&targets) else {
+        return vec![];
+    };
+    // start every ray outside of the sampled region
+    let back = 2.0
+        * targets
+            .iter()
+            .map(|target| (target - center).norm())
+            .fold(f32::EPSILON, f32::max);
+
+    targets
+        .iter()
+        .filter_map(|target| {
+            let ray = Ray {
+                origin: target - back * incoming.into_inner(),
+                direction: incoming,
+                wavelength: DEFAULT_WAVELENGTH,
+                time: 0.0,
+            };
+            let (distance, matrix) = *mirror.reflect(ray).first()?;
+            let intersection = Intersection::new(&ray, distance, matrix);
+            if absorbs(mirror, &intersection, &ray) {
+                return None;
+            }
+
+            let outgoing = intersection.reflected_ray(&ray);
+            Some((outgoing.origin, outgoing.direction))
+        })
+        .collect()
+}
+
+/// Estimates the étendue of a bundle crossing a section of size `area` (a length in 2D), from
+/// the largest angle θ between one of the rays and their mean direction. A 2D bundle spreading
+/// by ±θ has an étendue of `2 * area * sin θ`, which is conserved through an ideal system.
+pub fn etendue(rays: &[Ray], area: f32) -> f32 {
+    let sum = rays.iter().fold(SVector::<f32, DIM>::zeros(), |sum, ray| {
+        sum + ray.direction.into_inner()
+    });
+    // rays going every which way cancel out, and fill the whole half-space
+    let Some(mean) = sum.try_normalize(f32::EPSILON) else {
+        return if rays.is_empty() { 0.0 } else { 2.0 * area };
+    };
+
+    let sin = rays
+        .iter()
+        .map(|ray| {
+            let direction = ray.direction.into_inner();
+            if direction.dot(&mean) < 0.0 {
+                1.0
+            } else {
+                (direction - direction.dot(&mean) * mean).norm()
+            }
+        })
+        .fold(0.0, f32::max);
+
+    2.0 * area * sin
+}
+
+/// Angular spread of `input_beam` once traced through `simulation`, in radians: the rays are
+/// followed until they stop hitting mirrors, and the angle between the two most diverging
+/// output directions is returned. For a collimated input beam, this is the divergence added
+/// by the scene, e.g. zero for flat mirrors. Absorbed rays have no output direction, and are
+/// left out. Only meaningful in 2D.
+pub fn output_divergence(simulation: &Simulation, input_beam: &[Ray]) -> f32 {
+    let directions = simulation
+        .trace_many(input_beam, MAX_BEAM_BOUNCES)
+        .into_iter()
+        .filter(|result| result.termination != Termination::Absorbed)
+        .map(|result| result.last_ray.direction)
+        .collect::<Vec<_>>();
+    let Some(reference) = directions.first() else {
+        return 0.0;
+    };
+
+    // angles relative to one of the rays, so the spread doesn't wrap around at ±π
+    let angles = directions.iter().map(|direction| {
+        let cross = reference[0] * direction[1] - reference[1] * direction[0];
+        cross.atan2(reference.dot(direction))
+    });
+    let (min, max) = angles.fold((0.0f32, 0.0f32), |(min, max), angle| {
+        (min.min(angle), max.max(angle))
+    });
+
+    max - min
+}
+
+/// The envelope of the rays of `input_beam` once reflected in `simulation`, which is where the
+/// reflected light concentrates (the caustic curve). Every pair of neighbouring rays in the
+/// beam gives the point where their reflections cross, so the rays should be ordered across
+/// the beam, and dense enough for neighbours to be close.
+///
+/// Only the first reflection of each ray is considered. Pairs where one of the rays misses
+/// the scene or is absorbed, or where the reflections are parallel, are skipped. Only
+/// meaningful in 2D.
+pub fn caustic_envelope(simulation: &Simulation, input_beam: &[Ray]) -> Vec<Point<f32, DIM>> {
+    let reflected = simulation
+        .trace_many(input_beam, 1)
+        .into_iter()
+        .map(|result| (!result.intersections.is_empty()).then_some(result.last_ray))
+        .collect::<Vec<_>>();
+
+    reflected
+        .windows(2)
+        .filter_map(|pair| {
+            let (a, b) = (pair[0]?, pair[1]?);
+            // solve a.origin + s * a.direction = b.origin + t * b.direction for s
+            let cross = |u: &SVector<f32, DIM>, v: &SVector<f32, DIM>| u[0] * v[1] - u[1] * v[0];
+            let denominator = cross(&a.direction, &b.direction);
+            if denominator.abs() < f32::EPSILON {
+                return None;
+            }
+
+            let s = cross(&(b.origin - a.origin), &b.direction) / denominator;
+            Some(a.origin + s * a.direction.into_inner())
+        })
+        .collect()
+}
+
+/// Estimates the tangential and sagittal focal distances of a bundle reflected off a mirror,
+/// measured from the reflection point of the middle (chief) ray. They differ when the bundle
+/// hits a curved mirror off-axis, which is astigmatism.
+///
+/// Only the first reflection of each path is considered, unreflected paths are ignored.
+/// The tangential focus is where the reflected rays converge, and since a 2D bundle has no
+/// sagittal rays, the sagittal focus is derived from it with coddington's equations, using
+/// the chief ray's angle of incidence. Non-converging bundles have an infinite focal distance.
+pub fn tangential_sagittal_focus(paths: &[TraceResult]) -> (f32, f32) {
+    let reflections = paths
+        .iter()
+        .filter(|result| result.path.len() >= 2)
+        .map(|result| {
+            let outgoing = match result.path.get(2) {
+                Some(next) => next - result.path[1],
+                None => result.last_ray.direction.into_inner(),
+            };
+
+            (
+                Ray::new(result.path[0], result.path[1] - result.path[0]),
+                Ray::new(result.path[1], outgoing),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if reflections.len() < 2 {
+        return (f32::INFINITY, f32::INFINITY);
+    }
+
+    let (chief_in, chief_out) = reflections[reflections.len() / 2];
+    let (incoming, outgoing): (Vec<_>, Vec<_>) = reflections.into_iter().unzip();
+
+    // the angle between the reversed incoming ray and the outgoing one is twice the incidence
+    let cos_incidence = ((-chief_in.direction.dot(&chief_out.direction))
+        .clamp(-1.0, 1.0)
+        .acos()
+        / 2.0)
+        .cos();
+
+    let object_distance = least_squares_focus(&incoming).map_or(f32::INFINITY, |focus| {
+        (chief_out.origin - focus).dot(&chief_in.direction)
+    });
+    let tangential = least_squares_focus(&outgoing).map_or(f32::INFINITY, |focus| {
+        (focus - chief_out.origin).dot(&chief_out.direction)
+    });
+
+    // 1/object + 1/image = power, with the sagittal power being cos^2 of the tangential one
+    let tangential_power = 1.0 / object_distance + 1.0 / tangential;
+    let sagittal_power = tangential_power * cos_incidence * cos_incidence;
+    let sagittal = 1.0 / (sagittal_power - 1.0 / object_distance);
+
+    (tangential, sagittal)
+}
+
+/// Curvature of a wavefront right after reflecting off a mirror, in the plane of incidence,
+/// from coddington's tangential equation `1/u + 1/v = 2 / (R cos θ)`.
+///
+/// Curvatures are the inverse of the distance to the wavefront's center, positive for an
+/// incoming wavefront diverging from a point in front of the mirror, for a mirror concave
+/// towards the light, and for a reflected wavefront converging to a point in front of it.
+/// A negative result is a virtual image behind the mirror, and 0 a collimated beam.
+pub fn reflected_curvature(
+    incoming_curvature: f32,
+    mirror_curvature: f32,
+    incidence_angle: f32,
+) -> f32 {
+    2.0 * mirror_curvature / incidence_angle.cos() - incoming_curvature
+}
+
+/// Paraxial transverse magnification of a mirror imaging `object` at `image`, `-v / u`, the
+/// distances `u` and `v` of the object and the image to `mirror_center` (the vertex of the
+/// mirror) being measured along the axis from the vertex to the object.
+///
+/// A real image, in front of the mirror like the object, is inverted and gets a negative
+/// magnification, and a virtual one behind the mirror a positive one.
+pub fn magnification(
+    object: &Point<f32, DIM>,
+    image: &Point<f32, DIM>,
+    mirror_center: &Point<f32, DIM>,
+) -> f32 {
+    let to_object = object - mirror_center;
+    -(image - mirror_center).dot(&to_object) / to_object.norm_squared()
+}
+
+/// Wavefront error of a converging bundle: for each path, how much longer its optical path
+/// is than the mean one, up to where its last ray crosses `reference_sphere`, given as
+/// `(center, radius)`. The center is the ideal image point, so a perfectly focusing bundle
+/// has no error, and the rays should all start on the same wavefront at the same
+/// [`Ray::time`], which accumulates their path length.
+///
+/// The last ray of each path is extended, forwards or backwards, to the side of the sphere
+/// it comes from. Paths whose last ray misses the sphere get `NaN`, and don't count in the
+/// mean.
+pub fn wavefront_error(
+    paths: &[TraceResult],
+    reference_sphere: (Point<f32, DIM>, f32),
+) -> Vec<f32> {
+    let (center, radius) = reference_sphere;
+    let lengths = paths
+        .iter()
+        .map(|result| {
+            let ray = &result.last_ray;
+            let to_center = center - ray.origin;
+            let along = to_center.dot(&ray.direction);
+            let squared_miss = to_center.norm_squared() - along * along;
+            // sqrt of a negative number is NaN, for rays missing the sphere
+            ray.time + along - (radius * radius - squared_miss).sqrt()
+        })
+        .collect::<Vec<_>>();
+
+    let (sum, count) = lengths
+        .iter()
+        .filter(|length| !length.is_nan())
+        .fold((0.0, 0), |(sum, count), length| (sum + length, count + 1));
+    let mean = sum / count.max(1) as f32;
+
+    lengths.into_iter().map(|length| length - mean).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circle_mirror::CircleMirror,
+        mirror::{PlaneMirror, SphereMirror},
+        opaque_mirror::OpaqueMirror,
+        ray::ray_from,
+        scene::Scene,
+        segment_mirror::SegmentMirror,
+    };
+
+    #[test]
+    fn test_rms_spot_size() {
+        let points = [
+            [1.0, 0.0].into(),
+            [-1.0, 0.0].into(),
+            [0.0, 1.0].into(),
+            [0.0, -1.0].into(),
+        ];
+
+        assert_eq!(centroid(&points), Some(Point::origin()));
+        assert!((rms_spot_size(&points) - 1.0).abs() < 1e-6);
+        assert_eq!(rms_spot_size(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_weighted_spot() {
+        let points = [[0.0, 0.0].into(), [4.0, 0.0].into()];
+
+        // the dimmer point pulls the centroid a quarter of the way
+        let weighted = weighted_centroid(&points, &[0.75, 0.25]).unwrap();
+        assert!((weighted - Point::from([1.0, 0.0])).norm() < 1e-6);
+        let size = weighted_rms_spot_size(&points, &[0.75, 0.25]).unwrap();
+        assert!((size - 3f32.sqrt()).abs() < 1e-6);
+
+        // equal intensities give the plain statistics
+        assert_eq!(weighted_centroid(&points, &[0.5, 0.5]), centroid(&points));
+        let size = weighted_rms_spot_size(&points, &[0.5, 0.5]).unwrap();
+        assert!((size - rms_spot_size(&points)).abs() < 1e-6);
+
+        assert_eq!(weighted_centroid(&points, &[0.0, 0.0]), None);
+        assert_eq!(weighted_rms_spot_size(&[], &[]), None);
+        // one intensity per point, none of them negative
+        assert_eq!(weighted_centroid(&points, &[0.5]), None);
+        assert_eq!(weighted_centroid(&points, &[0.5, 0.5, 0.5]), None);
+        assert_eq!(weighted_rms_spot_size(&points, &[1.0, -0.5]), None);
+        assert_eq!(weighted_centroid(&points, &[1.0, f32::NAN]), None);
+    }
+
+    #[test]
+    fn test_reflection_map() {
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        let incoming = Unit::new_normalize(SVector::from([1.0, -2.0]));
+
+        let map = reflection_map(&plane, incoming, 10);
+
+        assert_eq!(map.len(), 10);
+        let expected = SVector::<f32, DIM>::from([1.0, 2.0]).normalize();
+        for (point, outgoing) in &map {
+            assert!(plane.contains_point(point, 1e-5));
+            assert!((outgoing.into_inner() - expected).norm() < 1e-6);
+        }
+
+        let opaque = OpaqueMirror::new(Box::new(plane));
+        assert!(reflection_map(&opaque, incoming, 10).is_empty());
+    }
+
+    #[test]
+    fn test_etendue() {
+        let collimated = (0..5)
+            .map(|i| Ray::new([i as f32 * 0.1, 0.0].into(), [0.0, 1.0].into()))
+            .collect::<Vec<_>>();
+        let diverging = (0..5)
+            .map(|i| {
+                let angle = (i as f32 - 2.0) * 0.1;
+                Ray::new(
+                    [i as f32 * 0.1, 0.0].into(),
+                    [angle.sin(), angle.cos()].into(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(etendue(&collimated, 0.4) < 1e-6);
+        assert!((etendue(&diverging, 0.4) - 0.8 * 0.2f32.sin()).abs() < 1e-5);
+        assert_eq!(etendue(&[], 0.4), 0.0);
+    }
+
+    #[test]
+    fn test_least_squares_focus() {
+        let rays = [
+            Ray::new([0.0, 0.0].into(), [1.0, 1.0].into()),
+            Ray::new([2.0, 0.0].into(), [-1.0, 1.0].into()),
+            Ray::new([1.0, -1.0].into(), [0.0, 1.0].into()),
+        ];
+        let focus = least_squares_focus(&rays).unwrap();
+        assert!((focus - Point::from([1.0, 1.0])).norm() < 1e-5);
+
+        let parallel = [
+            Ray::new([0.0, 0.0].into(), [1.0, 0.0].into()),
+            Ray::new([0.0, 1.0].into(), [1.0, 0.0].into()),
+        ];
+        assert_eq!(least_squares_focus(&parallel), None);
+    }
+
+    // collimated bundle hitting the bottom of a concave circle of radius 2 at `incidence`
+    fn bundle_on_sphere(incidence: f32) -> Vec<TraceResult> {
+        let simulation = Simulation::new(Scene::new(vec![Box::new(SphereMirror::new(
+            Point::origin(),
+            2.0,
+        ))]));
+        let direction = SVector::from([incidence.sin(), -incidence.cos()]);
+        let side = SVector::from([direction[1], -direction[0]]);
+        let hit = Point::from([0.0, -2.0]);
+
+        (-2..=2)
+            .map(|i| {
+                let origin = hit - 1.5 * direction + i as f32 * 0.02 * side;
+                simulation.trace(Ray::new(origin, direction), 1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tangential_sagittal_focus_on_axis() {
+        let (tangential, sagittal) = tangential_sagittal_focus(&bundle_on_sphere(0.0));
+
+        // the focal length of a spherical mirror is half its radius
+        assert!((tangential - 1.0).abs() < 1e-2);
+        assert!((sagittal - tangential).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_tangential_sagittal_focus_off_axis() {
+        let incidence = 30.0_f32.to_radians();
+        let (tangential, sagittal) = tangential_sagittal_focus(&bundle_on_sphere(incidence));
+
+        // coddington: f_t = f * cos(incidence), f_s = f / cos(incidence)
+        assert!((tangential - incidence.cos()).abs() < 1e-2);
+        assert!((sagittal - 1.0 / incidence.cos()).abs() < 1e-2);
+        assert!(sagittal - tangential > 0.1);
+    }
+
+    #[test]
+    fn test_reflected_curvature() {
+        // 1/v + 1/u = 2/R with R = 2
+        for object in [1.5, 3.0, 10.0] {
+            let image = 1.0 / reflected_curvature(1.0 / object, 0.5, 0.0);
+            assert!((1.0 / image + 1.0 / object - 1.0).abs() < 1e-6);
+        }
+
+        // collimated light focuses at R/2, and a plane mirror makes a virtual image
+        assert!((reflected_curvature(0.0, 0.5, 0.0) - 1.0).abs() < 1e-6);
+        assert_eq!(reflected_curvature(0.25, 0.0, 0.3), -0.25);
+    }
+
+    #[test]
+    fn test_magnification() {
+        // a concave mirror of radius 2, so f = 1, with its vertex at the origin
+        let simulation = Simulation::new(Scene::new(vec![Box::new(SphereMirror::new(
+            [0.0, 2.0].into(),
+            2.0,
+        ))]));
+        let vertex = Point::origin();
+        // where paraxial rays leaving `object` towards the mirror meet again
+        let image_of = |object: Point<f32, DIM>| {
+            let rays = (-2..=2)
+                .map(|i| {
+                    let direction = vertex + SVector::from([i as f32 * 0.05, 0.0]) - object;
+                    simulation.trace(Ray::new(object, direction), 1).last_ray
+                })
+                .collect::<Vec<_>>();
+            least_squares_focus(&rays).unwrap()
+        };
+
+        // at 2f, the image is where the object is, upside down
+        let object = Point::from([0.0, 2.0]);
+        let image = image_of(object);
+        assert!((image - object).norm() < 1e-3);
+        assert!((magnification(&object, &image, &vertex) + 1.0).abs() < 1e-3);
+
+        // at 3f, the image is at 1.5f, half as big
+        let object = Point::from([0.0, 3.0]);
+        let image = image_of(object);
+        assert!((magnification(&object, &image, &vertex) + 0.5).abs() < 1e-3);
+
+        // a plane mirror makes a virtual image of the same size
+        let mirrored = Point::from([0.0, -3.0]);
+        assert_eq!(magnification(&object, &mirrored, &vertex), 1.0);
+    }
+
+    #[test]
+    fn test_output_divergence() {
+        // a collimated beam going down, from y = 2
+        let beam = (-4..=4)
+            .map(|i| ray_from([i as f32 / 10.0, 2.0], [0.0, -1.0]))
+            .collect::<Vec<_>>();
+
+        let flat = Simulation::new(Scene::new(vec![Box::new(PlaneMirror::new([
+            [-1.0, 0.0].into(),
+            [1.0, -1.0].into(),
+        ]))]));
+        assert!(output_divergence(&flat, &beam) < 1e-5);
+
+        // rays 0.4 off the axis of a bowl of radius 2 leave 2 * asin(0.2) away from it
+        let bowl = || {
+            Box::new(CircleMirror::arc(
+                [0.0, 2.0].into(),
+                2.0,
+                std::f32::consts::PI,
+                std::f32::consts::TAU,
+            ))
+        };
+        let curved = Simulation::new(Scene::new(vec![bowl()]));
+        let expected = 4.0 * (0.2f32).asin();
+        assert!((output_divergence(&curved, &beam) - expected).abs() < 1e-4);
+
+        assert_eq!(output_divergence(&curved, &[]), 0.0);
+
+        // with every ray but the axial one absorbed on the way down, nothing diverges
+        let stop = |from: f32, to: f32| {
+            Box::new(OpaqueMirror::new(Box::new(SegmentMirror::new(
+                [from, 1.5].into(),
+                [to, 1.5].into(),
+            ))))
+        };
+        let stopped = Simulation::new(Scene::new(vec![bowl(), stop(-1.0, -0.05), stop(0.05, 1.0)]));
+        assert!(output_divergence(&stopped, &beam) < 1e-5);
+    }
+
+    #[test]
+    fn test_caustic_envelope() {
+        // a collimated beam reflected inside a unit circle gives a nephroid: the ray at height
+        // sin θ, hit at angle θ, touches it at (1.5cos θ - cos^3 θ, sin^3 θ)
+        let bowl = Simulation::new(Scene::new(vec![Box::new(CircleMirror::arc(
+            Point::origin(),
+            1.0,
+            -std::f32::consts::FRAC_PI_2,
+            std::f32::consts::FRAC_PI_2,
+        ))]));
+        let heights = (-20..=20).map(|i| i as f32 * 0.045).collect::<Vec<_>>();
+        let beam = heights
+            .iter()
+            .map(|y| ray_from([-2.0, *y], [1.0, 0.0]))
+            .collect::<Vec<_>>();
+
+        let envelope = caustic_envelope(&bowl, &beam);
+
+        assert_eq!(envelope.len(), 40);
+        for (point, pair) in envelope.iter().zip(heights.windows(2)) {
+            let theta = ((pair[0] + pair[1]) / 2.0).asin();
+            let (sin, cos) = theta.sin_cos();
+            let expected = Point::from([1.5 * cos - cos.powi(3), sin.powi(3)]);
+            assert!((point - expected).norm() < 1e-2);
+        }
+        // the cusp is at the paraxial focus, halfway to the mirror
+        assert!((envelope[19] - Point::from([0.5, 0.0])).norm() < 1e-2);
+        assert!((envelope[20] - Point::from([0.5, 0.0])).norm() < 1e-2);
+
+        // a flat mirror keeps the beam parallel, so there is no caustic
+        let flat = Simulation::new(Scene::new(vec![Box::new(PlaneMirror::new([
+            [1.0, -1.0].into(),
+            [1.0, 1.0].into(),
+        ]))]));
+        assert!(caustic_envelope(&flat, &beam).is_empty());
+
+        // absorbed rays aren't reflected, so they don't cross their neighbours either
+        let opaque = Simulation::new(Scene::new(vec![Box::new(OpaqueMirror::new(Box::new(
+            CircleMirror::arc(
+                Point::origin(),
+                1.0,
+                -std::f32::consts::FRAC_PI_2,
+                std::f32::consts::FRAC_PI_2,
+            ),
+        )))]));
+        assert!(caustic_envelope(&opaque, &beam).is_empty());
+    }
+
+    #[test]
+    fn test_wavefront_error() {
+        // a collimated bundle coming straight down, its wavefront being the line y = 2.5
+        let bundle = |scene| {
+            let simulation = Simulation::new(scene);
+            (-6..=6)
+                .map(|i| simulation.trace(ray_from([i as f32 * 0.2, 2.5], [0.0, -1.0]), 1))
+                .collect::<Vec<_>>()
+        };
+
+        // a parabola focuses it perfectly
+        let parabola = bundle(crate::scenes::parabolic_collimator());
+        let errors = wavefront_error(&parabola, ([0.0, 1.0].into(), 0.5));
+        assert_eq!(errors.len(), 13);
+        assert!(errors.iter().all(|error| error.abs() < 1e-3));
+
+        // a circle of radius 2 doesn't, the marginal rays focusing closer to it
+        let circle = bundle(Scene::new(vec![Box::new(SphereMirror::new(
+            [0.0, 1.0].into(),
+            2.0,
+        ))]));
+        let errors = wavefront_error(&circle, ([0.0, 0.0].into(), 0.5));
+        assert!(errors[0].abs() > 1e-2);
+        assert!((errors[0] - errors[12]).abs() < 1e-4);
+
+        // a ray going away from the sphere never crosses it
+        let escaped =
+            Simulation::new(Scene::new(vec![])).trace(ray_from([0.0, 3.0], [1.0, 0.0]), 1);
+        assert!(wavefront_error(&[escaped], ([0.0, 0.0].into(), 0.5))[0].is_nan());
+    }
+}
@@ -0,0 +1,135 @@
+use std::f32::consts::FRAC_PI_2;
+
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    material::Polarization,
+    mirror::{degenerate, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Wraps a mirror whose coating only works up to some angle of incidence, like most
+/// dielectric coatings failing at grazing incidence. More oblique rays are absorbed, which
+/// ends their trace with [`crate::simulation::Termination::Absorbed`].
+pub struct AngleLimitedMirror {
+    mirror: Box<dyn Mirror>,
+    /// In radians, from the normal.
+    max_incidence_angle: f32,
+}
+
+impl Mirror for AngleLimitedMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.mirror.reflect(ray)
+    }
+    fn get_type(&self) -> String {
+        "angle_limited".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.mirror.is_analytic()
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        self.mirror.is_opaque()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.mirror.reflectivity()
+    }
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.mirror.reflectivity_at(point)
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.mirror
+            .polarized_reflectivity(polarization, sin2_incidence)
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mirror.retardance(sin2_incidence)
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn max_incidence_angle(&self) -> Option<f32> {
+        Some(self.max_incidence_angle)
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !(self.max_incidence_angle > 0.0 && self.max_incidence_angle <= FRAC_PI_2) {
+            degenerate(self, "its maximum incidence angle isn't in ]0, π/2]")
+        } else {
+            self.mirror.validate()
+        }
+    }
+}
+
+impl AngleLimitedMirror {
+    /// `max_incidence_angle` is in radians, measured from the normal.
+    pub fn new(mirror: Box<dyn Mirror>, max_incidence_angle: f32) -> Self {
+        Self {
+            mirror,
+            max_incidence_angle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mirror::PlaneMirror,
+        ray::ray_from,
+        scene::Scene,
+        simulation::{Simulation, Termination},
+    };
+
+    #[test]
+    fn test_absorbs_beyond_max_incidence() {
+        // reflects up to 60° from the normal, which is the y axis
+        let simulation = Simulation::new(Scene::new(vec![Box::new(AngleLimitedMirror::new(
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            60f32.to_radians(),
+        ))]));
+        let towards_origin = |angle: f32| {
+            let (sin, cos) = angle.to_radians().sin_cos();
+            ray_from([-sin, cos], [sin, -cos])
+        };
+
+        let reflected = simulation.trace(towards_origin(45.0), 2);
+        assert_eq!(reflected.termination, Termination::Escaped);
+        assert_eq!(reflected.len(), 1);
+
+        let absorbed = simulation.trace(towards_origin(75.0), 2);
+        assert_eq!(absorbed.termination, Termination::Absorbed);
+        assert!(absorbed.is_empty());
+        assert_eq!(absorbed.path.len(), 2);
+        assert!((absorbed.path[1] - Point::origin()).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_validate() {
+        let plane = || Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]));
+
+        assert!(AngleLimitedMirror::new(plane(), 1.0).validate().is_ok());
+        assert!(AngleLimitedMirror::new(plane(), 0.0).validate().is_err());
+        assert!(AngleLimitedMirror::new(plane(), 2.0).validate().is_err());
+    }
+}
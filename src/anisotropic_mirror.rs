@@ -0,0 +1,176 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    material::Polarization,
+    mirror::{degenerate, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Wraps a mirror with a coating that reflects s- and p-polarized light differently.
+///
+/// At normal incidence both polarizations are indistinguishable and get the mean of the two
+/// reflectivities. The gap opens with the squared sine of the angle of incidence, reaching
+/// `s` and `p` at grazing incidence. The retardance between both polarizations, if any,
+/// opens the same way.
+pub struct AnisotropicMirror {
+    mirror: Box<dyn Mirror>,
+    s: f32,
+    p: f32,
+    retardance: f32,
+}
+
+impl Mirror for AnisotropicMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.mirror.reflect(ray)
+    }
+    fn get_type(&self) -> String {
+        "anisotropic".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.mirror.is_analytic()
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        self.mirror.is_opaque()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror.sample_surface(n)
+    }
+    /// What unpolarized light keeps, whatever the angle of incidence.
+    fn reflectivity(&self) -> f32 {
+        (self.s + self.p) / 2.0
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        let target = match polarization {
+            Polarization::S => self.s,
+            Polarization::P => self.p,
+            Polarization::Unpolarized => return self.reflectivity(),
+        };
+
+        self.reflectivity() + (target - self.reflectivity()) * sin2_incidence
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.retardance * sin2_incidence
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn max_incidence_angle(&self) -> Option<f32> {
+        self.mirror.max_incidence_angle()
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !((0.0..=1.0).contains(&self.s) && (0.0..=1.0).contains(&self.p)) {
+            degenerate(self, "its reflectivities aren't between 0 and 1")
+        } else {
+            self.mirror.validate()
+        }
+    }
+}
+
+impl AnisotropicMirror {
+    /// `s` and `p` are the reflectivities of each polarization at grazing incidence.
+    pub fn new(mirror: Box<dyn Mirror>, s: f32, p: f32) -> Self {
+        Self {
+            mirror,
+            s,
+            p,
+            retardance: 0.0,
+        }
+    }
+
+    /// `retardance` is the phase, in radians, p-polarized light gains over s-polarized light
+    /// at grazing incidence (see [`Mirror::retardance`]).
+    pub fn with_retardance(self, retardance: f32) -> Self {
+        Self { retardance, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        angle_limited_mirror::AngleLimitedMirror, filter_mirror::FilterMirror,
+        lattice_mirror::LatticeMirror, masked_mirror::MaskedMirror, mirror::PlaneMirror,
+        opaque_mirror::OpaqueMirror, ray::ray_from, scene::Scene,
+    };
+
+    fn reflectivity_at(ray: Ray, polarization: Polarization) -> f32 {
+        let scene = Scene::new(vec![Box::new(AnisotropicMirror::new(
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            0.95,
+            0.75,
+        ))]);
+        let (index, intersection) = scene.nearest_hit(&ray).unwrap();
+
+        scene.mirrors()[index]
+            .polarized_reflectivity(polarization, intersection.sin2_incidence(&ray.direction))
+    }
+
+    #[test]
+    fn test_polarizations_differ() {
+        // 45 degrees of incidence, halfway to grazing
+        let ray = ray_from([-1.0, 1.0], [1.0, -1.0]);
+
+        let s = reflectivity_at(ray, Polarization::S);
+        let p = reflectivity_at(ray, Polarization::P);
+
+        assert!((s - 0.9).abs() < 1e-5);
+        assert!((p - 0.8).abs() < 1e-5);
+        assert!((reflectivity_at(ray, Polarization::Unpolarized) - 0.85).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_normal_incidence() {
+        let ray = ray_from([0.0, 1.0], [0.0, -1.0]);
+
+        let s = reflectivity_at(ray, Polarization::S);
+        let p = reflectivity_at(ray, Polarization::P);
+
+        assert!((s - p).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_wrappers_keep_the_coating() {
+        let coated = || {
+            Box::new(
+                AnisotropicMirror::new(
+                    Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+                    0.95,
+                    0.75,
+                )
+                .with_retardance(0.5),
+            )
+        };
+        let wrappers: [Box<dyn Mirror>; 4] = [
+            Box::new(AngleLimitedMirror::new(coated(), 1.5)),
+            Box::new(FilterMirror::new(coated(), |_| true)),
+            Box::new(MaskedMirror::new(coated(), |_| true)),
+            Box::new(LatticeMirror::new(coated(), [0.0, 1.0].into(), [1, 3])),
+        ];
+
+        for wrapper in &wrappers {
+            assert_eq!(wrapper.retardance(0.5), 0.25);
+            assert_eq!(wrapper.polarized_reflectivity(Polarization::S, 0.5), 0.9);
+        }
+        assert_eq!(OpaqueMirror::new(coated()).retardance(0.5), 0.25);
+    }
+}
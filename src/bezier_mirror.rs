@@ -1,33 +1,325 @@
+use std::sync::RwLock;
+
 use nalgebra::{Point, SMatrix, SVector, Unit};
-use std::io::Write;
 
-use crate::{mirror::Mirror, ray::Ray, DIM};
+use crate::{
+    analysis,
+    error::MirrorError,
+    json, material,
+    math::{gauss_legendre, magnitude},
+    mirror::{
+        degenerate, enclosing_sphere, householder_matrix, is_valid_distance, sample_parameters,
+        sort_by_distance, Mirror, Reflections,
+    },
+    ray::Ray,
+    DIM,
+};
+
+/// Number of points of the gauss-legendre rule integrating each piece of the arc length.
+const ARC_LENGTH_NODES: usize = 5;
+/// Number of intervals per control point used to bracket the intersections with a ray.
+const SAMPLES_PER_CONTROL_POINT: usize = 16;
+/// Maximum number of root-finder iterations spent refining a single bracket.
+const MAX_ITERATIONS: usize = 50;
+/// A root is accepted once the perpendicular distance to the ray falls below this, relative
+/// to the magnitude of the coordinates.
+const TOLERANCE: f32 = 1e-6;
+/// Maximum number of times a segment is halved when tessellating.
+const MAX_TESSELLATION_DEPTH: usize = 16;
+/// Parameter step of the secant used when the derivative of the curve vanishes.
+const SECANT_STEP: f32 = 1e-3;
+/// Brackets narrower than this can't be split any further in f32.
+const MIN_BRACKET_WIDTH: f32 = 1e-7;
+/// A ray whose perpendicular distance to the curve has a local minimum below this (relative
+/// to the magnitude of the coordinates) is considered tangent to it. Looser than
+/// [`TOLERANCE`], since near a double root the distance is flat and the extremum is located
+/// less precisely.
+const GRAZING_TOLERANCE: f32 = 1e-5;
+/// Curves of a higher degree compute their binomial coefficients on the fly instead of
+/// sharing them in [`PASCAL_TRIANGLE`], so a single huge curve doesn't keep every row below
+/// its degree alive.
+const MAX_CACHED_DEGREE: usize = 64;
+/// Curves of a higher degree are evaluated with de casteljau's algorithm instead of their
+/// bernstein polynomials, whose huge binomial coefficients times tiny powers of t lose
+/// precision, and eventually overflow.
+pub(crate) const MAX_BERNSTEIN_DEGREE: usize = 40;
+/// Number of points of a closed curve used to measure its winding.
+const WINDING_SAMPLES: usize = 64;
+/// Self-intersections whose parameters are both closer than this are the same crossing.
+const SAME_CROSSING: f32 = 1e-4;
+
+/// Rows of pascal's triangle shared by every curve, filled up to the highest degree used so far.
+static PASCAL_TRIANGLE: RwLock<Vec<Vec<usize>>> = RwLock::new(Vec::new());
 
 #[derive(PartialEq, Debug)]
 pub struct BezierMirror {
     control_points: Vec<Point<f32, DIM>>,
+    reflectivity: f32,
+    /// The intervals of `t` which reflect, the rest of the curve letting rays through.
+    /// `None` when the whole curve is coated.
+    coating: Option<Vec<(f32, f32)>>,
+}
+
+/// The refinement of one bracketed intersection, as recorded by
+/// [`BezierMirror::intersections_debug`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct RootSearch {
+    /// The parameter interval in which the perpendicular distance changes sign, or reaches
+    /// zero without changing sign when the ray is tangent to the curve.
+    pub bracket: (f32, f32),
+    /// Every `(t, residual)` candidate tried, in order.
+    pub steps: Vec<(f32, f32)>,
+    pub converged: bool,
+}
+
+/// A polyline approximation of a curve, returned by [`BezierMirror::tessellate`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct Tessellation {
+    /// Points on the curve, starting and ending exactly at its endpoints.
+    pub points: Vec<Point<f32, DIM>>,
+    /// Largest distance measured between the curve and the polyline.
+    pub max_deviation: f32,
 }
 
 impl Mirror for BezierMirror {
-    fn reflect(&self, ray: Ray) -> Vec<(f32, Unit<SMatrix<f32, DIM, DIM>>)> {
-        // use the other mirror to reflect the ray
-        vec![]
+    fn reflect(&self, ray: Ray) -> Reflections {
+        sort_by_distance(
+            self.intersections(&ray)
+                .into_iter()
+                .filter(|t| self.is_coated(*t))
+                .filter_map(|t| {
+                    let distance = (self.calculate_point(t) - ray.origin).dot(&ray.direction);
+
+                    is_valid_distance(distance)
+                        .then(|| (distance, householder_matrix(&self.calculate_normal(t))))
+                })
+                .collect(),
+        )
+    }
+    /// Minimized over the curve, so a ray crossing it gets ~0 even where it isn't coated.
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        let distance = |t: f32| ray.distance_to(&self.calculate_point(t));
+        distance(self.minimize(distance))
     }
     fn get_type(&self) -> String {
         "bezier".to_string()
     }
+    /// Intersections are found with a numerical root-finder.
+    fn is_analytic(&self) -> bool {
+        false
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        let (_, closest) = self.closest_point(point);
+        (closest - point).norm() <= tolerance
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.calculate_points(&sample_parameters(n))
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        Some((0.0, 1.0))
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        let (Some(first), Some(last)) = (self.control_points.first(), self.control_points.last())
+        else {
+            return false;
+        };
+
+        !self.is_closed()
+            && [first, last]
+                .iter()
+                .any(|endpoint| (point - *endpoint).norm() <= tolerance)
+    }
+    /// The curve stays within the convex hull of its control points.
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        let points = self.control_points.iter().map(|point| (*point, 0.0));
+        enclosing_sphere(&points.collect::<Vec<_>>())
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.control_points.len() < 2 {
+            degenerate(self, "it has less than 2 control points")
+        } else if self.control_points.iter().all(|point| {
+            (point - self.control_points[0]).norm()
+                <= f32::EPSILON * magnitude(&self.control_points)
+        }) {
+            degenerate(self, "all its control points are the same")
+        } else if self
+            .coating
+            .iter()
+            .flatten()
+            .any(|(start, end)| !(0.0 <= *start && start <= end && *end <= 1.0))
+        {
+            degenerate(self, "its coated intervals aren't within [0, 1]")
+        } else {
+            Ok(())
+        }
+    }
+    /// Curves with less than 2 control points have no tangent to reflect along, so they are
+    /// rejected rather than written out.
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        if self.control_points.len() < 2 {
+            return Err(too_few_control_points());
+        }
+
+        let mut json = serde_json::json!({
+            "type": self.get_type(),
+            "control_points": json::from_points(&self.control_points),
+            "reflectivity": self.reflectivity,
+        });
+        if let Some(coating) = &self.coating {
+            json["coating"] = serde_json::json!(coating
+                .iter()
+                .map(|(start, end)| [*start, *end])
+                .collect::<Vec<_>>());
+        }
+
+        Ok(json)
+    }
 }
 
 impl BezierMirror {
+    pub fn new(control_points: Vec<Point<f32, DIM>>) -> Self {
+        Self {
+            control_points,
+            reflectivity: 1.0,
+            coating: None,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    /// Only coats the parts of the curve whose `t` is in one of the `(start, end)` intervals,
+    /// so rays hitting the rest of it go through, like a partially coated mirror.
+    pub fn with_coating(self, intervals: Vec<(f32, f32)>) -> Self {
+        Self {
+            coating: Some(intervals),
+            ..self
+        }
+    }
+
+    /// The curve with every control point moved by `f`, keeping the rest of its properties.
+    pub(crate) fn map_control_points(
+        &self,
+        f: impl Fn(&Point<f32, DIM>) -> Point<f32, DIM>,
+    ) -> Self {
+        Self {
+            control_points: self.control_points.iter().map(f).collect(),
+            coating: self.coating.clone(),
+            ..*self
+        }
+    }
+
+    /// Whether the curve reflects at `t`, see [`BezierMirror::with_coating`].
+    pub fn is_coated(&self, t: f32) -> bool {
+        self.coating.as_ref().is_none_or(|intervals| {
+            intervals
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&t))
+        })
+    }
+
+    /// Parses the `d` attribute of an svg path into one mirror per segment, only in 2D.
+    ///
+    /// The `M` (move), `L` (line), `Q` (quadratic) and `C` (cubic) commands are supported,
+    /// in both their absolute and relative (lowercase) forms.
+    pub fn from_svg_path(d: &str) -> Result<Vec<BezierMirror>, MirrorError> {
+        let tokens = svg_tokens(d)?;
+        let mut mirrors = vec![];
+        let mut current: Option<[f32; 2]> = None;
+        let mut command = None;
+        let mut i = 0;
+
+        while i < tokens.len() {
+            if let SvgToken::Command(c) = tokens[i] {
+                command = Some(c);
+                i += 1;
+            }
+
+            let c = command.ok_or_else(|| {
+                MirrorError::InvalidSvgPath("path must start with a command".into())
+            })?;
+            let relative = c.is_ascii_lowercase();
+            let count = match c.to_ascii_uppercase() {
+                'M' | 'L' => 1,
+                'Q' => 2,
+                'C' => 3,
+                other => {
+                    return Err(MirrorError::InvalidSvgPath(format!(
+                        "unsupported command {other}"
+                    )))
+                }
+            };
+
+            let origin = current.unwrap_or([0.0, 0.0]);
+            let mut points = Vec::with_capacity(count);
+            for _ in 0..count {
+                let (x, y) = match (tokens.get(i), tokens.get(i + 1)) {
+                    (Some(SvgToken::Number(x)), Some(SvgToken::Number(y))) => (*x, *y),
+                    _ => {
+                        return Err(MirrorError::InvalidSvgPath(format!(
+                            "command {c} expects {} coordinate pairs",
+                            count
+                        )))
+                    }
+                };
+                i += 2;
+
+                points.push(if relative {
+                    [origin[0] + x, origin[1] + y]
+                } else {
+                    [x, y]
+                });
+            }
+
+            let end = *points.last().unwrap();
+            if c.eq_ignore_ascii_case(&'M') {
+                // coordinates following a move are implicit lines
+                command = Some(if relative { 'l' } else { 'L' });
+            } else {
+                let start = current.ok_or_else(|| {
+                    MirrorError::InvalidSvgPath("path must start with a move".into())
+                })?;
+
+                let control_points = std::iter::once(start)
+                    .chain(points)
+                    .map(|[x, y]| Point::from_slice(&[x, y]))
+                    .collect();
+                mirrors.push(BezierMirror::new(control_points));
+            }
+            current = Some(end);
+        }
+
+        Ok(mirrors)
+    }
+
+    pub fn control_points(&self) -> &[Point<f32, DIM>] {
+        &self.control_points
+    }
+
     // Method to calculate a point on the Bezier curve
-    fn calculate_point(&self, t: f32) -> Point<f32, DIM> {
-        let mut point: Point<f32, DIM> = Point::origin();
+    pub fn calculate_point(&self, t: f32) -> Point<f32, DIM> {
         let n = self.control_points.len() - 1; // degree of the curve
+        if n > MAX_BERNSTEIN_DEGREE {
+            return de_casteljau(&self.control_points, t);
+        }
+        with_binomials(n, |binomials| self.point_from_binomials(binomials, t))
+    }
+
+    // the point at t, given the binomial coefficients of the degree of the curve
+    fn point_from_binomials(&self, binomials: &[usize], t: f32) -> Point<f32, DIM> {
+        let mut point: Point<f32, DIM> = Point::origin();
+        let n = self.control_points.len() - 1;
 
         for (i, control_point) in self.control_points.iter().enumerate() {
-            let bernstein_polynomial = binomial_coefficient(n, i) as f32
-                * t.powi(i as i32)
-                * (1.0 - t).powi((n - i) as i32);
+            let bernstein_polynomial =
+                binomials[i] as f32 * t.powi(i as i32) * (1.0 - t).powi((n - i) as i32);
 
             for (j, coordinate) in point.iter_mut().enumerate() {
                 *coordinate += bernstein_polynomial * control_point[j];
@@ -37,24 +329,624 @@ impl BezierMirror {
         point
     }
 
-    fn calculate_tangent(&self, t: f32) -> SVector<f32, DIM> {
+    /// [`BezierMirror::calculate_point`] for every parameter in `ts`, spread over the threads
+    /// of rayon's pool, giving exactly the same points.
+    #[cfg(feature = "parallel")]
+    pub fn calculate_points_parallel(&self, ts: &[f32]) -> Vec<Point<f32, DIM>> {
+        use rayon::prelude::*;
+
         let n = self.control_points.len() - 1; // degree of the curve
+        if n > MAX_BERNSTEIN_DEGREE {
+            return ts.par_iter().map(|t| self.calculate_point(*t)).collect();
+        }
+        with_binomials(n, |binomials| {
+            ts.par_iter()
+                .map(|t| self.point_from_binomials(binomials, *t))
+                .collect()
+        })
+    }
+
+    /// [`BezierMirror::calculate_point`] for every parameter in `ts`. With the `simd` feature,
+    /// several parameters are evaluated at once.
+    #[cfg(not(feature = "simd"))]
+    pub fn calculate_points(&self, ts: &[f32]) -> Vec<Point<f32, DIM>> {
+        ts.iter().map(|t| self.calculate_point(*t)).collect()
+    }
+
+    /// [`BezierMirror::calculate_point`] for every parameter in `ts`, evaluated by groups of
+    /// 8 parameters.
+    #[cfg(feature = "simd")]
+    pub fn calculate_points(&self, ts: &[f32]) -> Vec<Point<f32, DIM>> {
+        use wide::f32x8;
+
+        const LANES: usize = 8;
+        let n = self.control_points.len() - 1; // degree of the curve
+        if n > MAX_BERNSTEIN_DEGREE {
+            return ts.iter().map(|t| self.calculate_point(*t)).collect();
+        }
+        let mut points = Vec::with_capacity(ts.len());
+
+        with_binomials(n, |binomials| {
+            for chunk in ts.chunks(LANES) {
+                let mut t = [0.0; LANES];
+                t[..chunk.len()].copy_from_slice(chunk);
+                let t = f32x8::from(t);
+                let one_minus_t = f32x8::ONE - t;
+
+                // (1 - t)^k for every k up to the degree
+                let mut one_minus_t_powers = vec![f32x8::ONE; n + 1];
+                for k in 1..=n {
+                    one_minus_t_powers[k] = one_minus_t_powers[k - 1] * one_minus_t;
+                }
+
+                let mut coordinates = [f32x8::ZERO; DIM];
+                let mut t_power = f32x8::ONE;
+                for (i, control_point) in self.control_points.iter().enumerate() {
+                    let bernstein_polynomial =
+                        f32x8::splat(binomials[i] as f32) * t_power * one_minus_t_powers[n - i];
+
+                    for (j, coordinate) in coordinates.iter_mut().enumerate() {
+                        *coordinate += bernstein_polynomial * f32x8::splat(control_point[j]);
+                    }
+                    t_power *= t;
+                }
+
+                let coordinates = coordinates.map(|coordinate| coordinate.to_array());
+                points.extend(
+                    (0..chunk.len())
+                        .map(|lane| Point::from(coordinates.map(|coordinate| coordinate[lane]))),
+                );
+            }
+        });
+
+        points
+    }
+
+    pub fn calculate_tangent(&self, t: f32) -> SVector<f32, DIM> {
+        let derivative = self.calculate_derivative(t);
+        if derivative.norm() > f32::EPSILON * magnitude(&self.control_points) {
+            return derivative.normalize();
+        }
+
+        // the derivative vanishes where control points coincide (e.g. at the ends of a
+        // split curve), so fall back to the direction of a small secant around t
+        let (a, b) = if t < 0.5 {
+            (t, t + SECANT_STEP)
+        } else {
+            (t - SECANT_STEP, t)
+        };
+        (self.calculate_point(b) - self.calculate_point(a)).normalize()
+    }
+
+    /// The ends of a segment of the given `length` along the tangent at `t`, centered on the
+    /// curve, to draw the tangent the reflections use.
+    pub fn tangent_segment(&self, t: f32, length: f32) -> (Point<f32, DIM>, Point<f32, DIM>) {
+        let point = self.calculate_point(t);
+        let half = length / 2.0 * self.calculate_tangent(t);
+
+        (point - half, point + half)
+    }
+
+    /// The normal of the curve, only meaningful in 2D where it is the tangent rotated by 90°
+    pub fn calculate_normal(&self, t: f32) -> Unit<SVector<f32, DIM>> {
+        let tangent = self.calculate_tangent(t);
+        let mut normal = SVector::<f32, DIM>::zeros();
+        normal[0] = -tangent[1];
+        normal[1] = tangent[0];
+
+        Unit::new_normalize(normal)
+    }
+
+    /// Whether the curve is a loop, its last point being its first one. Only meaningful in 2D.
+    pub fn is_closed(&self) -> bool {
+        let [first, .., last] = self.control_points.as_slice() else {
+            return false;
+        };
+
+        self.control_points.len() > 2
+            && (last - first).norm() <= TOLERANCE * magnitude(&self.control_points)
+    }
+
+    /// The normal of a closed curve pointing inside the loop, or `None` if it isn't closed.
+    ///
+    /// [`BezierMirror::calculate_normal`] points left of the direction of travel, which is
+    /// inside for a counter-clockwise loop, so it is flipped for clockwise ones. Reflections
+    /// don't depend on it, rays inside a loop always stay inside.
+    pub fn calculate_inward_normal(&self, t: f32) -> Option<Unit<SVector<f32, DIM>>> {
+        if !self.is_closed() {
+            return None;
+        }
+
+        // shoelace formula over points of the curve, positive when counter-clockwise
+        let points = self.sample_surface(WINDING_SAMPLES);
+        let doubled_area = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+            .sum::<f32>();
+
+        let normal = self.calculate_normal(t);
+        Some(if doubled_area < 0.0 { -normal } else { normal })
+    }
+
+    // derivative of the curve with respect to t, not normalized
+    fn calculate_derivative(&self, t: f32) -> SVector<f32, DIM> {
+        let n = self.control_points.len().saturating_sub(1); // degree of the curve
+        if n == 0 {
+            // a single point (or none) doesn't go anywhere
+            return SVector::zeros();
+        }
+        if n > MAX_BERNSTEIN_DEGREE + 1 {
+            // the derivative is the curve of the differences between control points, times n
+            let differences = self
+                .control_points
+                .windows(2)
+                .map(|pair| Point::from(pair[1] - pair[0]))
+                .collect::<Vec<_>>();
+            return n as f32 * de_casteljau(&differences, t).coords;
+        }
         let mut tangent: SVector<f32, DIM> = SVector::zeros();
 
-        for i in 0..n {
-            let bernstein_derivative = (n as f32)
-                * binomial_coefficient(n - 1, i) as f32
-                * t.powi(i as i32)
-                * (1.0 - t).powi((n - 1 - i) as i32);
+        with_binomials(n - 1, |binomials| {
+            for (i, binomial) in binomials.iter().enumerate() {
+                let bernstein_derivative = (n as f32)
+                    * *binomial as f32
+                    * t.powi(i as i32)
+                    * (1.0 - t).powi((n - 1 - i) as i32);
+
+                let difference = self.control_points[i + 1] - self.control_points[i];
+                tangent += bernstein_derivative * difference;
+            }
+        });
+
+        tangent
+    }
+
+    /// Splits the curve at `t` using de casteljau's algorithm, returning the parts covering
+    /// `[0, t]` and `[t, 1]`.
+    ///
+    /// Only affine combinations of the control points are involved, so coincident control
+    /// points don't produce NaNs and both halves evaluate exactly like the original curve.
+    pub fn split(&self, t: f32) -> (BezierMirror, BezierMirror) {
+        let mut points = self.control_points.clone();
+        let mut left = Vec::with_capacity(points.len());
+        let mut right = Vec::with_capacity(points.len());
 
-            let difference = self.control_points[i + 1] - self.control_points[i];
-            tangent += bernstein_derivative * difference;
+        while let Some(last) = points.last() {
+            left.push(points[0]);
+            right.push(*last);
+
+            points = points
+                .windows(2)
+                .map(|pair| pair[0] + t * (pair[1] - pair[0]))
+                .collect();
         }
 
-        tangent.normalize()
+        right.reverse();
+        (BezierMirror::new(left), BezierMirror::new(right))
     }
 
-    fn from_json(json: &serde_json::Value) -> Self {
+    /// Approximates the curve with one of degree one lower, or `None` if the approximation
+    /// strays further than `tolerance` from it (or the curve has less than two control points).
+    /// Curves that were degree elevated are reduced back exactly.
+    ///
+    /// Undoing the elevation `P_i = i/n Q_{i-1} + (1 - i/n) Q_i` from the start of the curve
+    /// and from its end both give the new control points, but errors pile up along the way,
+    /// so each half of them is taken from the end it is closest to.
+    pub fn reduce_degree(&self, tolerance: f32) -> Option<BezierMirror> {
+        let points = &self.control_points;
+        let n = points.len().checked_sub(1).filter(|n| *n > 0)?;
+
+        let mut forward = vec![points[0]];
+        for i in 1..n {
+            let previous = forward[i - 1].coords;
+            forward.push(Point::from(
+                (n as f32 * points[i].coords - i as f32 * previous) / (n - i) as f32,
+            ));
+        }
+        let mut backward = vec![points[n]; n];
+        for i in (1..n).rev() {
+            let next = backward[i].coords;
+            backward[i - 1] =
+                Point::from((n as f32 * points[i].coords - (n - i) as f32 * next) / i as f32);
+        }
+
+        let reduced = BezierMirror::new(
+            forward[..n / 2]
+                .iter()
+                .chain(&backward[n / 2..])
+                .copied()
+                .collect(),
+        )
+        .with_reflectivity(self.reflectivity);
+        let reduced = Self {
+            coating: self.coating.clone(),
+            ..reduced
+        };
+
+        let ts = sample_parameters(points.len() * SAMPLES_PER_CONTROL_POINT);
+        let error = self
+            .calculate_points(&ts)
+            .iter()
+            .zip(reduced.calculate_points(&ts))
+            .map(|(a, b)| (a - b).norm())
+            .fold(0.0, f32::max);
+
+        (error <= tolerance).then_some(reduced)
+    }
+
+    /// Approximates the curve with a polyline that deviates from it by at most `tolerance`,
+    /// subdividing more where the curve bends the most.
+    pub fn tessellate(&self, tolerance: f32) -> Tessellation {
+        let last = self.control_points.len() - 1;
+        let mut tessellation = Tessellation {
+            points: vec![self.control_points[0]],
+            max_deviation: 0.0,
+        };
+
+        // start with one segment per degree so that no S-shape can hide between the samples
+        let segments = last.max(1);
+        for i in 0..segments {
+            let t0 = i as f32 / segments as f32;
+            let t1 = (i + 1) as f32 / segments as f32;
+            let end = if i + 1 == segments {
+                self.control_points[last]
+            } else {
+                self.calculate_point(t1)
+            };
+
+            let start = *tessellation.points.last().unwrap();
+            self.tessellate_segment((t0, start), (t1, end), tolerance, 0, &mut tessellation);
+        }
+
+        tessellation
+    }
+
+    fn tessellate_segment(
+        &self,
+        (t0, p0): (f32, Point<f32, DIM>),
+        (t1, p1): (f32, Point<f32, DIM>),
+        tolerance: f32,
+        depth: usize,
+        tessellation: &mut Tessellation,
+    ) {
+        let deviation = [0.25, 0.5, 0.75]
+            .into_iter()
+            .map(|f| distance_to_segment(&self.calculate_point(t0 + f * (t1 - t0)), &p0, &p1))
+            .fold(0.0, f32::max);
+
+        if deviation > tolerance && depth < MAX_TESSELLATION_DEPTH {
+            let tm = (t0 + t1) / 2.0;
+            let pm = self.calculate_point(tm);
+            self.tessellate_segment((t0, p0), (tm, pm), tolerance, depth + 1, tessellation);
+            self.tessellate_segment((tm, pm), (t1, p1), tolerance, depth + 1, tessellation);
+        } else {
+            tessellation.max_deviation = tessellation.max_deviation.max(deviation);
+            tessellation.points.push(p1);
+        }
+    }
+
+    /// The length of the curve, integrating its speed with a gauss-legendre rule on each of
+    /// a few pieces of the parameter range, one per control point.
+    pub fn arc_length(&self) -> f32 {
+        let pieces = self.control_points.len().max(1);
+
+        (0..pieces)
+            .map(|i| {
+                gauss_legendre(
+                    |t| self.calculate_derivative(t).norm(),
+                    i as f32 / pieces as f32,
+                    (i + 1) as f32 / pieces as f32,
+                    ARC_LENGTH_NODES,
+                )
+            })
+            .sum()
+    }
+
+    /// The centroid of the curve itself, every part of it weighing as much as its length, as
+    /// opposed to the centroid of the control points. A curve reduced to a point is its
+    /// centroid.
+    pub fn centroid(&self) -> Point<f32, DIM> {
+        let samples = self.control_points.len() * SAMPLES_PER_CONTROL_POINT;
+        let points = self.calculate_points(&sample_parameters(samples + 1));
+
+        let (sum, length) = points.windows(2).fold(
+            (SVector::<f32, DIM>::zeros(), 0.0),
+            |(sum, length), pair| {
+                let segment = (pair[1] - pair[0]).norm();
+                (
+                    sum + segment * (pair[0].coords + pair[1].coords) / 2.0,
+                    length + segment,
+                )
+            },
+        );
+
+        if length > 0.0 {
+            Point::from(sum / length)
+        } else {
+            self.control_points[0]
+        }
+    }
+
+    /// The direction the control points spread the most along (their first principal
+    /// component), oriented from the first control point towards the last one. It is the
+    /// direction of the chord for a curve symmetric about its middle.
+    pub fn principal_axis(&self) -> Unit<SVector<f32, DIM>> {
+        let center = analysis::centroid(&self.control_points).unwrap_or_else(Point::origin);
+        let covariance = self.control_points.iter().fold(
+            SMatrix::<f32, DIM, DIM>::zeros(),
+            |covariance, point| {
+                let offset = point - center;
+                covariance + offset * offset.transpose()
+            },
+        );
+
+        let eigen = covariance.symmetric_eigen();
+        let axis = eigen
+            .eigenvectors
+            .column(eigen.eigenvalues.imax())
+            .into_owned();
+        let chord = self.control_points[self.control_points.len() - 1] - self.control_points[0];
+
+        Unit::new_normalize(if axis.dot(&chord) < 0.0 { -axis } else { axis })
+    }
+
+    /// The point of the curve closest to `point`, along with its parameter `t`.
+    pub fn closest_point(&self, point: &Point<f32, DIM>) -> (f32, Point<f32, DIM>) {
+        let t = self.minimize(|t| (self.calculate_point(t) - point).norm_squared());
+        (t, self.calculate_point(t))
+    }
+
+    /// The depth of the curve: the largest distance between a point of the curve and the
+    /// chord joining its endpoints, or its first point for a closed curve.
+    pub fn sag(&self) -> f32 {
+        let (Some(first), Some(last)) = (self.control_points.first(), self.control_points.last())
+        else {
+            return 0.0;
+        };
+        let chord = (last - first).try_normalize(f32::EPSILON);
+        let deviation = |t: f32| {
+            let offset = self.calculate_point(t) - first;
+            match chord {
+                Some(chord) => (offset - offset.dot(&chord) * chord).norm(),
+                None => offset.norm(),
+            }
+        };
+
+        deviation(self.minimize(|t| -deviation(t)))
+    }
+
+    /// The parameter `t` in `[0, 1]` where `f` is the smallest.
+    fn minimize(&self, f: impl Fn(f32) -> f32) -> f32 {
+        // coarse sampling to find the right neighbourhood...
+        let samples = self.control_points.len() * SAMPLES_PER_CONTROL_POINT;
+        let step = 1.0 / samples as f32;
+        let best_sample = (0..=samples)
+            .map(|i| i as f32 * step)
+            .min_by(|a, b| f(*a).total_cmp(&f(*b)))
+            .unwrap();
+
+        // ...then a golden-section search around it
+        let ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+        let mut a = (best_sample - step).max(0.0);
+        let mut b = (best_sample + step).min(1.0);
+        for _ in 0..MAX_ITERATIONS {
+            if b - a < MIN_BRACKET_WIDTH {
+                break;
+            }
+
+            let c = b - ratio * (b - a);
+            let d = a + ratio * (b - a);
+            if f(c) < f(d) {
+                b = d;
+            } else {
+                a = c;
+            }
+        }
+
+        (a + b) / 2.0
+    }
+
+    /// The pairs of parameters `(s, t)`, with `s < t`, where the curve crosses itself, sorted
+    /// by `s`. The endpoints of a closed curve meeting don't count. Only meaningful in 2D.
+    ///
+    /// Crossings are first found between the segments of a polyline sampling the curve, then
+    /// refined with newton's method on `B(s) - B(t) = 0`. Loops smaller than the sampling step
+    /// may be missed.
+    pub fn self_intersections(&self) -> Vec<(f32, f32)> {
+        let ts = sample_parameters(self.control_points.len() * SAMPLES_PER_CONTROL_POINT + 1);
+        let points = self.calculate_points(&ts);
+        let segments = points.len().saturating_sub(1);
+        let tolerance = TOLERANCE * magnitude(&self.control_points);
+        let closed = self.is_closed();
+
+        let mut crossings: Vec<(f32, f32)> = vec![];
+        for i in 0..segments {
+            // neighbouring segments share an endpoint, as do the ends of a closed curve
+            for j in i + 2..segments {
+                if closed && i == 0 && j == segments - 1 {
+                    continue;
+                }
+                let Some((u, v)) =
+                    segment_crossing(&points[i], &points[i + 1], &points[j], &points[j + 1])
+                else {
+                    continue;
+                };
+
+                let s = ts[i] + u * (ts[i + 1] - ts[i]);
+                let t = ts[j] + v * (ts[j + 1] - ts[j]);
+                let Some((s, t)) = self.refine_self_intersection(s, t, tolerance) else {
+                    continue;
+                };
+                // a crossing right on a sample is found by the segments on both sides of it
+                if !crossings
+                    .iter()
+                    .any(|(a, b)| (a - s).abs() < SAME_CROSSING && (b - t).abs() < SAME_CROSSING)
+                {
+                    crossings.push((s, t));
+                }
+            }
+        }
+
+        crossings.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        crossings
+    }
+
+    // newton's method on B(s) - B(t) = 0, starting from an approximate crossing
+    fn refine_self_intersection(
+        &self,
+        mut s: f32,
+        mut t: f32,
+        tolerance: f32,
+    ) -> Option<(f32, f32)> {
+        for _ in 0..MAX_ITERATIONS {
+            let difference = self.calculate_point(s) - self.calculate_point(t);
+            if difference.norm() <= tolerance {
+                break;
+            }
+
+            let (ds, dt) = (self.calculate_derivative(s), -self.calculate_derivative(t));
+            let determinant = ds[0] * dt[1] - ds[1] * dt[0];
+            if determinant.abs() < f32::EPSILON {
+                return None;
+            }
+            s -= (difference[0] * dt[1] - difference[1] * dt[0]) / determinant;
+            t -= (ds[0] * difference[1] - ds[1] * difference[0]) / determinant;
+        }
+
+        let converged = (self.calculate_point(s) - self.calculate_point(t)).norm() <= tolerance;
+        let valid =
+            (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&t) && t - s > MIN_BRACKET_WIDTH;
+        (converged && valid).then_some((s, t))
+    }
+
+    /// Parameters `t` in `[0, 1]` where the curve crosses the line supporting `ray`
+    /// (in front of or behind its origin).
+    pub fn intersections(&self, ray: &Ray) -> Vec<f32> {
+        self.roots(ray, f32::INFINITY, None)
+    }
+
+    /// Same as [`BezierMirror::intersections`], but also returns every iteration of the
+    /// root-finder, which helps understanding why a ray misses the curve.
+    pub fn intersections_debug(&self, ray: &Ray) -> (Vec<f32>, Vec<RootSearch>) {
+        let mut trace = vec![];
+        let roots = self.roots(ray, f32::INFINITY, Some(&mut trace));
+        (roots, trace)
+    }
+
+    /// Same as [`BezierMirror::intersections`], with the interval around each `t` in which the
+    /// root-finder saw the curve cross the line, telling how precisely `t` was found. The
+    /// root-finder keeps narrowing it until it is at most `width` wide, or can't be split any
+    /// further. The interval is `None` when the curve isn't seen crossing the line, e.g. when a
+    /// grazing ray only touches the curve.
+    pub fn intersections_bracketed(&self, ray: &Ray, width: f32) -> Vec<(f32, Option<(f32, f32)>)> {
+        self.find_intersections(ray, width, None)
+    }
+
+    fn roots(&self, ray: &Ray, width: f32, trace: Option<&mut Vec<RootSearch>>) -> Vec<f32> {
+        self.find_intersections(ray, width, trace)
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect()
+    }
+
+    /// Every `t` where the curve meets the line of `ray`, with the bracket the root-finder
+    /// narrowed down to `width` around it, if any.
+    fn find_intersections(
+        &self,
+        ray: &Ray,
+        width: f32,
+        mut trace: Option<&mut Vec<RootSearch>>,
+    ) -> Vec<(f32, Option<(f32, f32)>)> {
+        // a single point has no tangent to reflect along, even when the ray goes through it
+        if self.control_points.len() < 2 {
+            return vec![];
+        }
+
+        // signed distance from the curve point to the ray's line (2D cross product)
+        let cross = |v: SVector<f32, DIM>| ray.direction[0] * v[1] - ray.direction[1] * v[0];
+        let residual = |t: f32| cross(self.calculate_point(t) - ray.origin);
+        let derivative = |t: f32| cross(self.calculate_derivative(t));
+        let scale = magnitude(self.control_points.iter().chain([&ray.origin]));
+
+        // a tangent ray touches the curve at a double root, where the distance doesn't change
+        // sign but has an extremum reaching zero, found by bisecting on the derivative
+        let grazing = |lo: f32, hi: f32, f_lo: f32| {
+            let (mut a, mut b, d_a) = (lo, hi, derivative(lo));
+            // the distance must be heading towards zero and come back within the interval
+            if d_a * f_lo >= 0.0 || d_a * derivative(hi) >= 0.0 {
+                return None;
+            }
+
+            let mut steps = vec![];
+            for _ in 0..MAX_ITERATIONS {
+                let t = (a + b) / 2.0;
+                steps.push((t, residual(t)));
+
+                if b - a < MIN_BRACKET_WIDTH {
+                    break;
+                }
+                if derivative(t).signum() == d_a.signum() {
+                    a = t;
+                } else {
+                    b = t;
+                }
+            }
+
+            let converged = steps.last().unwrap().1.abs() < GRAZING_TOLERANCE * scale;
+            converged.then_some(RootSearch {
+                bracket: (lo, hi),
+                steps,
+                converged,
+            })
+        };
+
+        let samples = self.control_points.len() * SAMPLES_PER_CONTROL_POINT;
+        let mut roots = vec![];
+
+        let mut lo = 0.0;
+        let mut f_lo = residual(lo);
+        for i in 1..=samples {
+            let hi = i as f32 / samples as f32;
+            let f_hi = residual(hi);
+
+            if f_lo == 0.0 {
+                roots.push((lo, Some((lo, lo))));
+            } else if f_lo * f_hi < 0.0 {
+                let (search, bracket) = refine_root(
+                    residual,
+                    derivative,
+                    (lo, hi),
+                    TOLERANCE * scale,
+                    width,
+                    MAX_ITERATIONS,
+                );
+
+                // a search which ran out of iterations found no root, only a candidate
+                if search.converged {
+                    roots.push((search.steps.last().unwrap().0, Some(bracket)));
+                }
+                if let Some(trace) = trace.as_mut() {
+                    trace.push(search);
+                }
+            } else if let Some(search) = grazing(lo, hi, f_lo) {
+                roots.push((search.steps.last().unwrap().0, None));
+                if let Some(trace) = trace.as_mut() {
+                    trace.push(search);
+                }
+            }
+
+            (lo, f_lo) = (hi, f_hi);
+        }
+
+        if f_lo == 0.0 {
+            roots.push((lo, Some((lo, lo))));
+        }
+
+        roots
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
         /* example json
         {
             "control_points": [
@@ -62,49 +954,254 @@ impl BezierMirror {
                 [4.0, 5.0, 6.0, ...],
                 [7.0, 8.0, 9.0, ...],
                 ...
-            ]
+            ],
+            "material": "silver", // or "reflectivity": 0.97, optional
+            "coating": [[0.0, 0.25], [0.5, 1.0]] // coated intervals of t, optional
         }
          */
-        let control_points = json["control_points"]
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|point| {
-                let point = point
-                    .as_array()
-                    .unwrap()
-                    .iter()
-                    .map(|value| value.as_f64().unwrap() as f32)
-                    .collect::<Vec<_>>();
+        let control_points = json::points(json, "control_points")?;
+        if control_points.len() < 2 {
+            return Err(too_few_control_points());
+        }
+        let mirror =
+            Self::new(control_points).with_reflectivity(material::reflectivity_from_json(json)?);
 
-                Point::from_slice(&point)
+        if json["coating"].is_null() {
+            return Ok(mirror);
+        }
+        let coating = json::array(json, "coating")?
+            .iter()
+            .map(|interval| match interval.as_array().map(Vec::as_slice) {
+                Some([start, end]) => Some((start.as_f64()? as f32, end.as_f64()? as f32)),
+                _ => None,
             })
-            .collect::<Vec<_>>();
+            .collect::<Option<Vec<_>>>()
+            .ok_or(MirrorError::InvalidField {
+                field: "coating".to_string(),
+                expected: "an array of [start, end] intervals",
+            })?;
+
+        Ok(mirror.with_coating(coating))
+    }
+}
+
+fn too_few_control_points() -> MirrorError {
+    MirrorError::InvalidField {
+        field: "control_points".to_string(),
+        expected: "at least two points",
+    }
+}
+
+enum SvgToken {
+    Command(char),
+    Number(f32),
+}
+
+fn svg_tokens(d: &str) -> Result<Vec<SvgToken>, MirrorError> {
+    let mut tokens = vec![];
+    let mut chars = d.char_indices().peekable();
 
-        Self { control_points }
+    while let Some((start, c)) = chars.next() {
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(SvgToken::Command(c));
+        } else if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' {
+            // a number ends at the next separator, command or sign not part of an exponent
+            let mut end = start + c.len_utf8();
+            let mut previous = c;
+            let mut seen_dot = c == '.';
+            while let Some(&(index, next)) = chars.peek() {
+                let is_exponent_sign =
+                    (next == '-' || next == '+') && matches!(previous, 'e' | 'E');
+                let continues = next.is_ascii_digit()
+                    || next == 'e'
+                    || next == 'E'
+                    || is_exponent_sign
+                    || (next == '.' && !seen_dot);
+                if !continues {
+                    break;
+                }
+
+                seen_dot |= next == '.';
+                previous = next;
+                end = index + next.len_utf8();
+                chars.next();
+            }
+
+            let number = d[start..end].parse::<f32>().map_err(|_| {
+                MirrorError::InvalidSvgPath(format!("invalid number {}", &d[start..end]))
+            })?;
+            tokens.push(SvgToken::Number(number));
+        } else if !(c.is_whitespace() || c == ',') {
+            return Err(MirrorError::InvalidSvgPath(format!(
+                "unexpected character {c}"
+            )));
+        }
     }
+
+    Ok(tokens)
 }
 
-// Function to calculate binomial coefficients
+fn distance_to_segment(point: &Point<f32, DIM>, a: &Point<f32, DIM>, b: &Point<f32, DIM>) -> f32 {
+    let segment = b - a;
+    let length_squared = segment.norm_squared();
+    if length_squared == 0.0 {
+        return (point - a).norm();
+    }
+
+    let t = ((point - a).dot(&segment) / length_squared).clamp(0.0, 1.0);
+    (point - (a + t * segment)).norm()
+}
+
+/// Where the segments `a0 a1` and `b0 b1` cross, as the fractions of each of them, if they do.
+fn segment_crossing(
+    a0: &Point<f32, DIM>,
+    a1: &Point<f32, DIM>,
+    b0: &Point<f32, DIM>,
+    b1: &Point<f32, DIM>,
+) -> Option<(f32, f32)> {
+    let cross = |u: &SVector<f32, DIM>, v: &SVector<f32, DIM>| u[0] * v[1] - u[1] * v[0];
+    let (a, b) = (a1 - a0, b1 - b0);
+    let denominator = cross(&a, &b);
+    if denominator == 0.0 {
+        return None;
+    }
+
+    let u = cross(&(b0 - a0), &b) / denominator;
+    let v = cross(&(b0 - a0), &a) / denominator;
+    ((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v)).then_some((u, v))
+}
+
+/// The point at `t` of the curve with the given control points, by repeated linear
+/// interpolation. Slower than summing bernstein polynomials, but stable at any degree.
+fn de_casteljau(control_points: &[Point<f32, DIM>], t: f32) -> Point<f32, DIM> {
+    let mut points = control_points.to_vec();
+    for len in (1..points.len()).rev() {
+        for i in 0..len {
+            points[i] = points[i] + t * (points[i + 1] - points[i]);
+        }
+    }
+
+    points[0]
+}
+
+/// Calls `f` with the binomial coefficients of degree `n`, taking them from the shared
+/// [`PASCAL_TRIANGLE`] and extending it if needed.
+pub(crate) fn with_binomials<R>(n: usize, f: impl FnOnce(&[usize]) -> R) -> R {
+    if n > MAX_CACHED_DEGREE {
+        return f(&(0..=n)
+            .map(|k| binomial_coefficient(n, k))
+            .collect::<Vec<_>>());
+    }
+
+    if let Some(row) = PASCAL_TRIANGLE.read().unwrap().get(n) {
+        return f(row);
+    }
+
+    let mut rows = PASCAL_TRIANGLE.write().unwrap();
+    extend_pascal_triangle(&mut rows, n);
+    f(&rows[n])
+}
+
+/// Adds the missing rows of pascal's triangle to `rows`, up to degree `n` and no further.
+fn extend_pascal_triangle(rows: &mut Vec<Vec<usize>>, n: usize) {
+    while rows.len() <= n {
+        let row = match rows.last() {
+            None => vec![1],
+            Some(previous) => std::iter::once(1)
+                .chain(previous.windows(2).map(|pair| pair[0] + pair[1]))
+                .chain(std::iter::once(1))
+                .collect(),
+        };
+        rows.push(row);
+    }
+}
+
+/// Refines the root of `residual` between `lo` and `hi`, where it changes sign, with
+/// newton's method falling back to bisection when it leaves the bracket. The search converges
+/// once the residual is below `tolerance` with the bracket narrowed down to `width` around it,
+/// or once the bracket can't be split anymore, and gives up after `max_iterations` candidates.
+/// Returns the search along with the final bracket.
+fn refine_root(
+    residual: impl Fn(f32) -> f32,
+    derivative: impl Fn(f32) -> f32,
+    (lo, hi): (f32, f32),
+    tolerance: f32,
+    width: f32,
+    max_iterations: usize,
+) -> (RootSearch, (f32, f32)) {
+    let mut search = RootSearch {
+        bracket: (lo, hi),
+        steps: vec![],
+        converged: false,
+    };
+
+    let (mut a, mut b, mut f_a) = (lo, hi, residual(lo));
+    let mut t = (a + b) / 2.0;
+    for _ in 0..max_iterations {
+        let f_t = residual(t);
+        search.steps.push((t, f_t));
+
+        if f_t == 0.0 {
+            (a, b) = (t, t);
+        } else if f_t.signum() == f_a.signum() {
+            (a, f_a) = (t, f_t);
+        } else {
+            b = t;
+        }
+
+        let found = f_t.abs() < tolerance;
+        if found && b - a <= width || b - a < MIN_BRACKET_WIDTH {
+            search.converged = true;
+            break;
+        }
+
+        let newton = t - f_t / derivative(t);
+        t = if found {
+            // t is one end of a bracket too wide, step just past the root
+            if t == a {
+                (t + width / 2.0).min((a + b) / 2.0)
+            } else {
+                (t - width / 2.0).max((a + b) / 2.0)
+            }
+        } else if newton > a && newton < b {
+            newton
+        } else {
+            (a + b) / 2.0
+        };
+    }
+
+    (search, (a, b))
+}
+
+// Function to calculate binomial coefficients, panicking when they don't fit in a usize
 fn binomial_coefficient(n: usize, k: usize) -> usize {
     if k > n {
         return 0;
     }
 
-    let mut result = 1;
+    // C(n, k) = C(n, n - k), and the fewer steps the smaller the intermediates. Each step
+    // multiplies before dividing so the division is exact, in u128 so the product can't
+    // overflow before the division brings it back down.
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
     for i in 0..k {
-        result *= n - i;
-        result /= i + 1;
+        result = result * (n - i) as u128 / (i + 1) as u128;
     }
 
-    result
+    usize::try_from(result).expect("binomial coefficient overflows usize")
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::io::Write;
 
     use super::*;
+    use crate::{
+        mirror::{mirror_from_json, test_helpers::in_front_of_plane, PlaneMirror},
+        ray::{ray_from, DEFAULT_WAVELENGTH},
+        scene::Scene,
+        simulation::{Simulation, Termination},
+    };
 
     fn complete_with_0(mut vec: Vec<f32>) -> Vec<f32> {
         vec.resize(DIM, 0.0);
@@ -130,6 +1227,137 @@ mod tests {
         assert_eq!(binomial_coefficient(4, 4), 1);
     }
 
+    #[test]
+    fn test_binomial_coefficient_mid_range() {
+        assert_eq!(binomial_coefficient(30, 15), 155_117_520);
+        assert_eq!(binomial_coefficient(40, 20), 137_846_528_820);
+        assert_eq!(binomial_coefficient(40, 37), 9_880);
+        assert_eq!(binomial_coefficient(60, 30), 118_264_581_564_861_424);
+        // the product of C(66, 32) and 34 doesn't fit in 64 bits
+        assert_eq!(binomial_coefficient(66, 33), 7_219_428_434_016_265_740);
+        assert_eq!(binomial_coefficient(5, 6), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows usize")]
+    fn test_binomial_coefficient_overflow() {
+        // about 1.1e20, more than 64 bits can hold
+        binomial_coefficient(70, 35);
+    }
+
+    #[test]
+    fn test_arc_length() {
+        // a straight curve is as long as its chord, however its control points are spread
+        let segment = BezierMirror::new(vec![
+            [0.0, 0.0].into(),
+            [0.3, 0.4].into(),
+            [3.0, 4.0].into(),
+        ]);
+        assert!((segment.arc_length() - 5.0).abs() < 1e-4);
+
+        // the usual cubic approximation of a quarter of the unit circle
+        let k = 0.552_284_8;
+        let quarter = BezierMirror::new(vec![
+            [1.0, 0.0].into(),
+            [1.0, k].into(),
+            [k, 1.0].into(),
+            [0.0, 1.0].into(),
+        ]);
+        assert!((quarter.arc_length() - std::f32::consts::FRAC_PI_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_centroid_and_principal_axis() {
+        // an arch symmetric about the y axis
+        let arch = BezierMirror::new(vec![
+            [-2.0, 0.0].into(),
+            [-1.0, 1.0].into(),
+            [1.0, 1.0].into(),
+            [2.0, 0.0].into(),
+        ]);
+
+        let centroid = arch.centroid();
+        assert!(centroid[0].abs() < 1e-5);
+        // between the chord and the top of the arch at y = 0.75
+        assert!(centroid[1] > 0.0 && centroid[1] < 0.75);
+        assert!((arch.principal_axis().into_inner() - SVector::from([1.0, 0.0])).norm() < 1e-5);
+
+        // the axis follows the curve from its first point to its last one
+        let reversed = BezierMirror::new(arch.control_points().iter().rev().copied().collect());
+        assert!((reversed.principal_axis().into_inner() + SVector::from([1.0, 0.0])).norm() < 1e-5);
+
+        // a segment is centered on its middle, however its control points are spread
+        let segment = BezierMirror::new(vec![
+            [0.0, 0.0].into(),
+            [0.2, 0.2].into(),
+            [2.0, 2.0].into(),
+        ]);
+        assert!((segment.centroid() - Point::from([1.0, 1.0])).norm() < 1e-3);
+        let diagonal = SVector::from([1.0, 1.0]).normalize();
+        assert!((segment.principal_axis().into_inner() - diagonal).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_high_degree_evaluation() {
+        // a segment with evenly spread control points is traversed at constant speed
+        for n in [45, 60, 100] {
+            let line = BezierMirror::new(
+                (0..=n)
+                    .map(|i| Point::from([i as f32 / n as f32, 2.0 * i as f32 / n as f32]))
+                    .collect(),
+            );
+
+            for t in [0.0, 0.1, 0.5, 0.77, 1.0] {
+                let point = line.calculate_point(t);
+                assert!((point - Point::from([t, 2.0 * t])).norm() < 1e-5);
+            }
+            let tangent = line.calculate_tangent(0.3);
+            assert!((tangent - SVector::from([1.0, 2.0]).normalize()).norm() < 1e-5);
+        }
+
+        // a wiggly degree 60 curve still goes through its ends, and never overflows
+        let wiggly = BezierMirror::new(
+            (0..=60)
+                .map(|i| Point::from([i as f32, 1e3 * (i as f32).sin()]))
+                .collect(),
+        );
+        assert_eq!(wiggly.calculate_point(0.0), Point::from([0.0, 0.0]));
+        assert!(
+            (wiggly.calculate_point(1.0) - Point::from([60.0, 1e3 * 60f32.sin()])).norm() < 1e-2
+        );
+        assert!(wiggly
+            .calculate_points(&sample_parameters(101))
+            .iter()
+            .all(|point| point.iter().all(|c| c.is_finite())));
+    }
+
+    #[test]
+    fn test_pascal_triangle() {
+        for n in [30, 4, 12] {
+            with_binomials(n, |row| {
+                let expected = (0..=n)
+                    .map(|k| binomial_coefficient(n, k))
+                    .collect::<Vec<_>>();
+                assert_eq!(row, expected);
+            });
+        }
+
+        // rows are only added up to the degree asked for
+        let mut rows = vec![];
+        extend_pascal_triangle(&mut rows, 12);
+        assert_eq!(rows.len(), 13);
+        extend_pascal_triangle(&mut rows, 4);
+        assert_eq!(rows.len(), 13);
+        assert_eq!(rows[4], [1, 4, 6, 4, 1]);
+
+        // evaluations match the closed form of the bernstein polynomials
+        let control_points = (0..31)
+            .map(|i| Point::<f32, DIM>::from_slice(&complete_with_0(vec![i as f32, 0.0])))
+            .collect::<Vec<_>>();
+        let bezier_mirror = BezierMirror::new(control_points);
+        assert!((bezier_mirror.calculate_point(0.3)[0] - 9.0).abs() < 1e-4);
+    }
+
     #[test]
     fn test_calculate_linear_point_2d() {
         let bezier_mirror = BezierMirror {
@@ -137,6 +1365,8 @@ mod tests {
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
             ],
+            reflectivity: 1.0,
+            coating: None,
         };
         assert_eq!(
             bezier_mirror.calculate_point(0.0),
@@ -160,6 +1390,8 @@ mod tests {
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
             ],
+            reflectivity: 1.0,
+            coating: None,
         };
         assert_eq!(
             bezier_mirror.calculate_point(0.0),
@@ -184,6 +1416,8 @@ mod tests {
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
             ],
+            reflectivity: 1.0,
+            coating: None,
         };
         assert_eq!(
             bezier_mirror.calculate_point(0.0),
@@ -210,6 +1444,8 @@ mod tests {
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 1.0])),
             ],
+            reflectivity: 1.0,
+            coating: None,
         };
 
         let mut file = std::fs::File::create("points.csv").unwrap();
@@ -229,6 +1465,8 @@ mod tests {
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
                 Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
             ],
+            reflectivity: 1.0,
+            coating: None,
         };
 
         let vector = bezier_mirror.calculate_tangent(1.0);
@@ -239,6 +1477,387 @@ mod tests {
         assert_eq!(bezier_mirror.calculate_tangent(0.0), reflected_vector);
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_calculate_points_parallel() {
+        let bezier_mirror = BezierMirror::new(
+            (0..12)
+                .map(|i| [i as f32, (i as f32 * 1.3).sin()].into())
+                .collect(),
+        );
+        let ts = (0..10_000).map(|i| i as f32 / 9999.0).collect::<Vec<_>>();
+
+        let serial = ts
+            .iter()
+            .map(|t| bezier_mirror.calculate_point(*t))
+            .collect::<Vec<_>>();
+
+        assert_eq!(bezier_mirror.calculate_points_parallel(&ts), serial);
+        assert!(bezier_mirror.calculate_points_parallel(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_tangent_segment() {
+        let bezier_mirror = BezierMirror::new(vec![
+            [0.0, 0.0].into(),
+            [0.5, 1.0].into(),
+            [2.0, -1.0].into(),
+            [3.0, 0.5].into(),
+        ]);
+
+        for t in [0.0, 0.3, 0.5, 1.0] {
+            let (start, end) = bezier_mirror.tangent_segment(t, 0.4);
+
+            let middle = start + (end - start) / 2.0;
+            assert!((middle - bezier_mirror.calculate_point(t)).norm() < 1e-6);
+            assert!((end - start - 0.4 * bezier_mirror.calculate_tangent(t)).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_reflect() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![-1.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 2.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 3.0])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![0.0, -1.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let reflections = bezier_mirror.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+
+        // the top of the curve is at (0, 1), where the tangent is horizontal
+        let (distance, matrix) = reflections[0];
+        assert!((distance - 2.0).abs() < 1e-5);
+        let reflected = matrix.into_inner() * ray.direction.into_inner();
+        assert!((reflected + ray.direction.into_inner()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_reflect_back_to_incoming_side() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![-1.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![-0.5, 2.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, -1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.5, 1.0])),
+        ]);
+
+        for i in 0..20 {
+            let t = (i as f32 + 0.5) / 20.0;
+            let point = bezier_mirror.calculate_point(t);
+            let normal = bezier_mirror.calculate_normal(t).into_inner();
+
+            for side in [1.0, -1.0] {
+                // come in obliquely from either side of the curve
+                let tangent = bezier_mirror.calculate_tangent(t).normalize();
+                let origin = point + side * 0.1 * normal + 0.05 * tangent;
+                let ray = Ray {
+                    origin,
+                    direction: Unit::new_normalize(point - origin),
+                    wavelength: DEFAULT_WAVELENGTH,
+                    time: 0.0,
+                };
+
+                let (distance, matrix) = bezier_mirror.reflect(ray)[0];
+                let reflected = matrix.into_inner() * ray.direction.into_inner();
+
+                assert!((distance - (point - origin).norm()).abs() < 1e-3);
+                assert!(side * reflected.dot(&normal) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_intersections_grazing() {
+        // y = 1 - 4t + 5t^2 has its minimum of 0.2 at t = 0.4, between two samples
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![-1.0, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, -1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 2.0])),
+        ]);
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![-5.0, 0.2])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![1.0, 0.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let roots = bezier_mirror.intersections(&ray);
+        assert_eq!(roots.len(), 1);
+        assert!((roots[0] - 0.4).abs() < 1e-3);
+
+        // a tangent ray goes on unchanged
+        let reflections = bezier_mirror.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+        let reflected = reflections[0].1.into_inner() * ray.direction.into_inner();
+        assert!((reflected - ray.direction.into_inner()).norm() < 1e-3);
+
+        // slightly below the lowest point, the ray misses the curve
+        let below = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![-5.0, 0.19])),
+            ..ray
+        };
+        assert!(bezier_mirror.intersections(&below).is_empty());
+    }
+
+    #[test]
+    fn test_intersections_bracketed() {
+        // y = 2t(1 - t) crosses y = 0.2 at t = (1 -+ sqrt(0.6)) / 2
+        let bezier_mirror = BezierMirror::new(vec![
+            [0.0, 0.0].into(),
+            [0.5, 1.0].into(),
+            [1.0, 0.0].into(),
+        ]);
+        let ray = ray_from([-1.0, 0.2], [1.0, 0.0]);
+        let exact = [(1.0 - 0.6f32.sqrt()) / 2.0, (1.0 + 0.6f32.sqrt()) / 2.0];
+
+        for width in [1e-3, 1e-4, 1e-6] {
+            let crossing = bezier_mirror.intersections_bracketed(&ray, width);
+            assert_eq!(crossing.len(), 2);
+
+            for ((t, bracket), exact) in crossing.into_iter().zip(exact) {
+                let (lo, hi) = bracket.unwrap();
+                assert!(lo <= t && t <= hi);
+                assert!(hi - lo <= width);
+                assert!(lo - 1e-6 <= exact && exact <= hi + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_intersections_debug_converges() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.3, -1.0])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![0.0, 1.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let (roots, trace) = bezier_mirror.intersections_debug(&ray);
+        assert_eq!(roots, bezier_mirror.intersections(&ray));
+        assert_eq!(roots.len(), 1);
+        assert_eq!(trace.len(), 1);
+
+        let search = &trace[0];
+        assert!(search.converged);
+        assert!(search.bracket.0 <= roots[0] && roots[0] <= search.bracket.1);
+        assert!(search
+            .steps
+            .windows(2)
+            .all(|pair| pair[1].1.abs() <= pair[0].1.abs()));
+        assert!((bezier_mirror.calculate_point(roots[0])[0] - 0.3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_refine_root_gives_up() {
+        // a root at 0.3 of a residual which newton's method approaches slowly
+        let residual = |t: f32| (t - 0.3).powi(3);
+        let derivative = |t: f32| 3.0 * (t - 0.3).powi(2);
+
+        let (search, (a, b)) = refine_root(residual, derivative, (0.0, 1.0), 1e-9, 1e-4, 3);
+        assert!(!search.converged);
+        assert_eq!(search.steps.len(), 3);
+        assert!(a <= 0.3 && 0.3 <= b);
+
+        let (search, (a, b)) =
+            refine_root(residual, derivative, (0.0, 1.0), 1e-9, 1e-4, MAX_ITERATIONS);
+        assert!(search.converged);
+        assert!(b - a <= 1e-4);
+        assert!((search.steps.last().unwrap().0 - 0.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+
+        for t in [0.0, 0.13, 0.5, 0.77, 1.0] {
+            assert!(bezier_mirror.contains_point(&bezier_mirror.calculate_point(t), 1e-4));
+        }
+
+        let off_curve = Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 0.8]));
+        assert!(!bezier_mirror.contains_point(&off_curve, 1e-2));
+
+        let (t, closest) = bezier_mirror.closest_point(&off_curve);
+        assert!((t - 0.5).abs() < 1e-3);
+        assert!((closest[1] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_tessellate() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 2.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, -1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+        ]);
+        let tolerance = 1e-3;
+
+        let tessellation = bezier_mirror.tessellate(tolerance);
+
+        assert_eq!(tessellation.points[0], bezier_mirror.control_points[0]);
+        assert_eq!(
+            tessellation.points.last(),
+            bezier_mirror.control_points.last()
+        );
+        assert!(tessellation.max_deviation < tolerance);
+
+        // every point of the curve is close to the polyline
+        for i in 0..=1000 {
+            let point = bezier_mirror.calculate_point(i as f32 / 1000.0);
+            let distance = tessellation
+                .points
+                .windows(2)
+                .map(|w| distance_to_segment(&point, &w[0], &w[1]))
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance < tolerance);
+        }
+
+        // a straight line doesn't need more than its endpoints
+        let line = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+        ]);
+        assert_eq!(line.tessellate(tolerance).points.len(), 2);
+    }
+
+    #[test]
+    fn test_sample_surface() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+
+        let samples = bezier_mirror.sample_surface(20);
+
+        assert_eq!(samples.len(), 20);
+        assert_eq!(samples[0], bezier_mirror.control_points[0]);
+        assert_eq!(samples[19], bezier_mirror.control_points[2]);
+        assert!(samples
+            .iter()
+            .all(|p| bezier_mirror.contains_point(p, 1e-5)));
+    }
+
+    #[test]
+    fn test_split() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.5, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+
+        let (left, right) = bezier_mirror.split(0.25);
+
+        assert_eq!(left.control_points.len(), 3);
+        assert_eq!(right.control_points.len(), 3);
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert!(
+                (left.calculate_point(t) - bezier_mirror.calculate_point(t * 0.25)).norm() < 1e-5
+            );
+            assert!(
+                (right.calculate_point(t) - bezier_mirror.calculate_point(0.25 + t * 0.75)).norm()
+                    < 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_coincident_control_points() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![2.0, 0.0])),
+        ]);
+
+        let (left, right) = bezier_mirror.split(0.5);
+
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert!(
+                (left.calculate_point(t) - bezier_mirror.calculate_point(t * 0.5)).norm() < 1e-5
+            );
+            assert!(
+                (right.calculate_point(t) - bezier_mirror.calculate_point(0.5 + t * 0.5)).norm()
+                    < 1e-5
+            );
+
+            for half in [&left, &right] {
+                assert!(half.calculate_point(t).iter().all(|c| c.is_finite()));
+                assert!(half.calculate_tangent(t).iter().all(|c| c.is_finite()));
+            }
+        }
+
+        // the repeated point makes the derivative vanish at the ends of the original curve too
+        let (_, degenerate) = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ])
+        .split(0.0);
+        let tangent = degenerate.calculate_tangent(0.0);
+        assert!((tangent - SVector::from_vec(complete_with_0(vec![1.0, 0.0]))).norm() < 1e-3);
+    }
+
+    #[test]
+    fn test_from_svg_path_cubic() {
+        let mirrors = BezierMirror::from_svg_path("M 10 20 C 30,40 50-60 70 80").unwrap();
+
+        assert_eq!(mirrors.len(), 1);
+        assert_eq!(
+            mirrors[0].control_points,
+            vec![
+                Point::<f32, DIM>::from_slice(&[10.0, 20.0]),
+                Point::<f32, DIM>::from_slice(&[30.0, 40.0]),
+                Point::<f32, DIM>::from_slice(&[50.0, -60.0]),
+                Point::<f32, DIM>::from_slice(&[70.0, 80.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_svg_path_segments() {
+        let mirrors = BezierMirror::from_svg_path("m1 1 2 0 q1 1 2 0 L 0 0 M 5 5 L 6 6").unwrap();
+
+        let degrees = mirrors
+            .iter()
+            .map(|m| m.control_points.len() - 1)
+            .collect::<Vec<_>>();
+        assert_eq!(degrees, vec![1, 2, 1, 1]);
+        assert_eq!(
+            mirrors[1].control_points,
+            vec![
+                Point::<f32, DIM>::from_slice(&[3.0, 1.0]),
+                Point::<f32, DIM>::from_slice(&[4.0, 2.0]),
+                Point::<f32, DIM>::from_slice(&[5.0, 1.0]),
+            ]
+        );
+        assert_eq!(mirrors[3].control_points[0], Point::from_slice(&[5.0, 5.0]));
+    }
+
+    #[test]
+    fn test_from_svg_path_invalid() {
+        assert!(BezierMirror::from_svg_path("M 0 0 A 1 1 0 0 1 2 2").is_err());
+        assert!(BezierMirror::from_svg_path("M 0 0 C 1 1 2").is_err());
+        assert!(BezierMirror::from_svg_path("L 1 1").is_err());
+        assert!(BezierMirror::from_svg_path("M 0 0 L 1 $").is_err());
+    }
+
     #[test]
     fn test_from_json() {
         let json = serde_json::json!({
@@ -249,14 +1868,327 @@ mod tests {
             ]
         });
         assert_eq!(
-            BezierMirror::from_json(&serde_json::to_value(json).unwrap()),
+            BezierMirror::from_json(&serde_json::to_value(json).unwrap()).unwrap(),
             BezierMirror {
                 control_points: vec![
                     Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 2.0, 3.0])),
                     Point::<f32, DIM>::from_slice(&complete_with_0(vec![4.0, 5.0, 6.0])),
                     Point::<f32, DIM>::from_slice(&complete_with_0(vec![7.0, 8.0, 9.0])),
                 ],
+                reflectivity: 1.0,
+                coating: None,
             }
         );
     }
+
+    #[test]
+    fn test_json_round_trip() {
+        let bezier_mirror = BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 2.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![3.0, 4.0])),
+        ])
+        .with_reflectivity(0.5);
+
+        let json = bezier_mirror.to_json().unwrap();
+
+        assert_eq!(BezierMirror::from_json(&json), Ok(bezier_mirror));
+    }
+
+    #[test]
+    fn test_json_round_trip_reflects_identically() {
+        let mut rng = oorandom::Rand32::new(7);
+        let mut coordinate = || rng.rand_float() * 10.0 - 5.0;
+
+        for _ in 0..100 {
+            let degree = 2 + (coordinate().abs() as usize) % 4;
+            let bezier_mirror = BezierMirror::new(
+                (0..=degree)
+                    .map(|_| [coordinate(), coordinate()].into())
+                    .collect(),
+            )
+            .with_reflectivity(coordinate().abs() / 5.0);
+            assert!(bezier_mirror.validate().is_ok());
+
+            // through the text form, where a lossy float formatting would show
+            let text = serde_json::to_string(&bezier_mirror.to_json().unwrap()).unwrap();
+            let loaded = BezierMirror::from_json(&serde_json::from_str(&text).unwrap()).unwrap();
+            assert_eq!(loaded, bezier_mirror);
+
+            // aimed at a point of the curve, so it is usually hit
+            let target = bezier_mirror.calculate_point(coordinate().abs() / 5.0);
+            let origin = Point::from([coordinate(), coordinate()]);
+            let ray = Ray::new(origin, target - origin);
+            assert_eq!(loaded.reflect(ray), bezier_mirror.reflect(ray));
+        }
+    }
+
+    #[test]
+    fn test_miss_distance() {
+        // y = 2t(1 - t) peaks at 0.5
+        let bezier_mirror = BezierMirror::new(vec![
+            [0.0, 0.0].into(),
+            [0.5, 1.0].into(),
+            [1.0, 0.0].into(),
+        ]);
+
+        let grazing = bezier_mirror.miss_distance(&ray_from([-1.0, 0.51], [1.0, 0.0]));
+        assert!((grazing - 0.01).abs() < 1e-4);
+
+        let hit = bezier_mirror.miss_distance(&ray_from([0.3, -1.0], [0.0, 1.0]));
+        assert!(hit < 1e-5);
+
+        // the curve is behind the ray, closest to its origin
+        let behind = bezier_mirror.miss_distance(&ray_from([0.5, 1.5], [0.0, 1.0]));
+        assert!((behind - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_partial_coating() {
+        // a curve along y = 1 only coated for x < 1, in front of a plain mirror at y = 0
+        let simulation = in_front_of_plane(Box::new(
+            BezierMirror::new(vec![
+                [0.0, 1.0].into(),
+                [1.0, 1.0].into(),
+                [2.0, 1.0].into(),
+            ])
+            .with_coating(vec![(0.0, 0.5)]),
+        ));
+
+        let coated = simulation.trace(ray_from([0.5, 2.0], [0.0, -1.0]), 1);
+        let uncoated = simulation.trace(ray_from([1.5, 2.0], [0.0, -1.0]), 1);
+
+        assert!((coated.path[1] - Point::from([0.5, 1.0])).norm() < 1e-5);
+        assert!((uncoated.path[1] - Point::from([1.5, 0.0])).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_coating_json() {
+        let bezier_mirror = BezierMirror::new(vec![[0.0, 0.0].into(), [1.0, 1.0].into()])
+            .with_coating(vec![(0.0, 0.25), (0.5, 1.0)]);
+
+        let json = bezier_mirror.to_json().unwrap();
+        assert_eq!(
+            json["coating"],
+            serde_json::json!([[0.0, 0.25], [0.5, 1.0]])
+        );
+        assert_eq!(BezierMirror::from_json(&json), Ok(bezier_mirror));
+
+        let invalid = serde_json::json!({ "control_points": [[0.0, 0.0]], "coating": [[0.5]] });
+        assert!(BezierMirror::from_json(&invalid).is_err());
+        assert!(
+            BezierMirror::new(vec![[0.0, 0.0].into(), [1.0, 1.0].into()])
+                .with_coating(vec![(0.5, 1.5)])
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_json_empty_control_points() {
+        let empty = BezierMirror::new(vec![]);
+        let error = MirrorError::InvalidField {
+            field: "control_points".to_string(),
+            expected: "at least two points",
+        };
+
+        assert_eq!(empty.to_json(), Err(error.clone()));
+        assert_eq!(
+            BezierMirror::from_json(&serde_json::json!({ "control_points": [] })),
+            Err(error)
+        );
+    }
+
+    #[test]
+    fn test_json_single_control_point() {
+        let json = serde_json::json!({ "type": "bezier", "control_points": [[0.0, 0.0]] });
+        assert!(BezierMirror::from_json(&json).is_err());
+        assert!(mirror_from_json(&json).is_err());
+
+        // built directly, it doesn't reflect anything, but doesn't panic either
+        let point = BezierMirror::new(vec![[0.0, 0.0].into()]);
+        assert!(point.to_json().is_err());
+        assert!(point.reflect(ray_from([0.0, 1.0], [0.0, -1.0])).is_empty());
+        assert_eq!(point.calculate_derivative(0.5).norm(), 0.0);
+        assert_eq!(
+            BezierMirror::new(vec![]).calculate_derivative(0.5).norm(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_closed_loop_traps_rays() {
+        // a counter-clockwise teardrop starting and ending at the origin
+        let control_points = vec![
+            Point::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::from_slice(&complete_with_0(vec![2.0, 3.0])),
+            Point::from_slice(&complete_with_0(vec![-2.0, 3.0])),
+            Point::from_slice(&complete_with_0(vec![0.0, 0.0])),
+        ];
+        let mirror = BezierMirror::new(control_points.clone());
+        assert!(mirror.is_closed());
+        assert!(!BezierMirror::new(control_points[..3].to_vec()).is_closed());
+
+        // the top of the loop, where the inward normal points down
+        let normal = mirror.calculate_inward_normal(0.5).unwrap();
+        assert!(
+            (normal.into_inner() - SVector::from_vec(complete_with_0(vec![0.0, -1.0]))).norm()
+                < 1e-5
+        );
+        let reversed = BezierMirror::new(control_points.into_iter().rev().collect());
+        assert_eq!(reversed.calculate_inward_normal(0.5), Some(normal));
+
+        let simulation = Simulation::new(Scene::new(vec![Box::new(mirror)]));
+        let ray = Ray {
+            origin: Point::from_slice(&complete_with_0(vec![0.0, 1.0])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![0.3, 1.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let result = simulation.trace(ray, 20);
+        assert_eq!(result.termination, Termination::MaxBounces);
+        for point in &result.path {
+            assert!(
+                point[0].abs() < 1.0 && point[1] > -1e-3 && point[1] < 2.3,
+                "{point}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_calculate_points_matches_calculate_point() {
+        let mirror = BezierMirror::new(
+            (0..12)
+                .map(|i| {
+                    let i = i as f32;
+                    Point::from_slice(&complete_with_0(vec![i, (i * 1.3).sin() * 4.0]))
+                })
+                .collect(),
+        );
+        // not a multiple of the number of lanes
+        let ts = sample_parameters(37);
+
+        let points = mirror.calculate_points(&ts);
+
+        assert_eq!(points.len(), ts.len());
+        for (t, point) in ts.iter().zip(&points) {
+            assert!((mirror.calculate_point(*t) - point).norm() < 1e-4);
+        }
+        assert!(mirror.calculate_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_param_domain() {
+        let mirror = BezierMirror::new(vec![
+            Point::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::from_slice(&complete_with_0(vec![1.0, 1.0])),
+        ]);
+
+        assert_eq!(mirror.param_domain(), Some((0.0, 1.0)));
+        let (start, end) = mirror.param_domain().unwrap();
+        assert_eq!(mirror.calculate_point(start), mirror.control_points()[0]);
+        assert_eq!(mirror.calculate_point(end), mirror.control_points()[1]);
+        assert_eq!(
+            PlaneMirror::new([
+                Point::from_slice(&complete_with_0(vec![0.0, 0.0])),
+                Point::from_slice(&complete_with_0(vec![1.0, 0.0])),
+            ])
+            .param_domain(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sag() {
+        let point = |x: f32, y: f32| Point::from_slice(&complete_with_0(vec![x, y]));
+        // symmetric, so the deepest point is the middle of the curve, at half the control height
+        let arc = BezierMirror::new(vec![point(0.0, 0.0), point(1.0, 3.0), point(2.0, 0.0)]);
+        assert!((arc.sag() - 1.5).abs() < 1e-5);
+        assert!((arc.sag() - arc.calculate_point(0.5)[1]).abs() < 1e-5);
+
+        // the depth is measured perpendicularly to the chord, wherever it points
+        let tilted = BezierMirror::new(vec![point(0.0, 0.0), point(-2.0, 4.0), point(2.0, 2.0)]);
+        let middle = tilted.calculate_point(0.5);
+        assert!((tilted.sag() - (middle[1] - middle[0]) / 2.0f32.sqrt()).abs() < 1e-5);
+
+        let straight = BezierMirror::new(vec![point(0.0, 0.0), point(1.0, 1.0)]);
+        assert!(straight.sag() < 1e-6);
+    }
+
+    #[test]
+    fn test_reduce_degree() {
+        let point = |x: f32, y: f32| Point::from_slice(&complete_with_0(vec![x, y]));
+        let cubic = BezierMirror::new(vec![
+            point(0.0, 0.0),
+            point(1.0, 2.0),
+            point(3.0, -1.0),
+            point(4.0, 1.0),
+        ]);
+        // raising the degree twice keeps the same curve, R_i = i/n P_{i-1} + (1 - i/n) P_i
+        let elevate = |curve: &BezierMirror| {
+            let points = curve.control_points();
+            let n = points.len();
+            BezierMirror::new(
+                (0..=n)
+                    .map(|i| {
+                        let ratio = i as f32 / n as f32;
+                        let previous = points[i.saturating_sub(1)].coords;
+                        let current = points[i.min(n - 1)].coords;
+                        Point::from(ratio * previous + (1.0 - ratio) * current)
+                    })
+                    .collect(),
+            )
+        };
+        let quintic = elevate(&elevate(&cubic));
+        assert_eq!(quintic.control_points().len(), 6);
+
+        let reduced = quintic
+            .reduce_degree(1e-4)
+            .and_then(|quartic| quartic.reduce_degree(1e-4))
+            .unwrap();
+        assert_eq!(reduced.control_points().len(), 4);
+        for (a, b) in reduced.control_points().iter().zip(cubic.control_points()) {
+            assert!((a - b).norm() < 1e-4);
+        }
+
+        // a genuine cubic isn't a quadratic
+        assert!(cubic.reduce_degree(1e-2).is_none());
+        assert!(cubic.reduce_degree(10.0).is_some());
+        assert!(BezierMirror::new(vec![point(1.0, 1.0)])
+            .reduce_degree(1.0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_self_intersections() {
+        let point = |x: f32, y: f32| Point::from_slice(&complete_with_0(vec![x, y]));
+        // symmetric around x = 0, so it crosses itself at t and 1 - t where
+        // x(t) = -3d + 20d^3 = 0, with d = t - 0.5
+        let looped = BezierMirror::new(vec![
+            point(-1.0, 0.0),
+            point(3.0, 2.0),
+            point(-3.0, 2.0),
+            point(1.0, 0.0),
+        ]);
+        let d = (0.15f32).sqrt();
+
+        let crossings = looped.self_intersections();
+
+        assert_eq!(crossings.len(), 1);
+        let (s, t) = crossings[0];
+        assert!((s - (0.5 - d)).abs() < 1e-4);
+        assert!((t - (0.5 + d)).abs() < 1e-4);
+        assert!((looped.calculate_point(s) - looped.calculate_point(t)).norm() < 1e-4);
+
+        // an arch doesn't cross itself, and neither does a closed loop at its ends
+        let arch = BezierMirror::new(vec![point(-1.0, 0.0), point(0.0, 2.0), point(1.0, 0.0)]);
+        assert!(arch.self_intersections().is_empty());
+        let closed = BezierMirror::new(vec![
+            point(0.0, 0.0),
+            point(2.0, 2.0),
+            point(-2.0, 2.0),
+            point(0.0, 0.0),
+        ]);
+        assert!(closed.self_intersections().is_empty());
+    }
 }
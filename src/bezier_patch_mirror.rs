@@ -0,0 +1,244 @@
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{
+    bezier_mirror::{with_binomials, MAX_BERNSTEIN_DEGREE},
+    DIM,
+};
+
+/// A tensor-product bezier surface, only meaningful in 3D where it has a normal.
+///
+/// This is the surface analogue of [`crate::bezier_mirror::BezierMirror`]. It can't reflect
+/// rays yet, as intersecting it requires a 3D scene.
+#[derive(PartialEq, Debug)]
+pub struct BezierPatchMirror {
+    /// Rows of control points along `u`, each row going along `v`. All rows should have the
+    /// same length, any extra point beyond the shortest row being ignored.
+    control_points: Vec<Vec<Point<f32, DIM>>>,
+}
+
+impl BezierPatchMirror {
+    pub fn new(control_points: Vec<Vec<Point<f32, DIM>>>) -> Self {
+        Self { control_points }
+    }
+
+    pub fn control_points(&self) -> &[Vec<Point<f32, DIM>>] {
+        &self.control_points
+    }
+
+    /// The point of the surface at `(u, v)`, the origin for a patch without control points.
+    pub fn calculate_point(&self, u: f32, v: f32) -> Point<f32, DIM> {
+        let (rows, columns) = self.shape();
+        if rows == 0 || columns == 0 {
+            return Point::origin();
+        }
+        let (bu, bv) = (bernstein(rows - 1, u), bernstein(columns - 1, v));
+
+        let mut point = Point::origin();
+        for (weight_u, row) in bu.iter().zip(&self.control_points) {
+            for (weight_v, control_point) in bv.iter().zip(row) {
+                point += weight_u * weight_v * control_point.coords;
+            }
+        }
+        point
+    }
+
+    /// Derivative of the surface with respect to `u`, not normalized.
+    pub fn partial_u(&self, u: f32, v: f32) -> SVector<f32, DIM> {
+        let (rows, columns) = self.shape();
+        if rows < 2 || columns == 0 {
+            return SVector::zeros();
+        }
+        let (bu, bv) = (bernstein(rows - 2, u), bernstein(columns - 1, v));
+
+        let mut partial = SVector::zeros();
+        for (weight_u, pair) in bu.iter().zip(self.control_points.windows(2)) {
+            for (weight_v, (start, end)) in bv.iter().zip(pair[0].iter().zip(&pair[1])) {
+                partial += (rows - 1) as f32 * weight_u * weight_v * (end - start);
+            }
+        }
+        partial
+    }
+
+    /// Derivative of the surface with respect to `v`, not normalized.
+    pub fn partial_v(&self, u: f32, v: f32) -> SVector<f32, DIM> {
+        let (rows, columns) = self.shape();
+        if rows == 0 || columns < 2 {
+            return SVector::zeros();
+        }
+        let (bu, bv) = (bernstein(rows - 1, u), bernstein(columns - 2, v));
+
+        let mut partial = SVector::zeros();
+        for (weight_u, row) in bu.iter().zip(&self.control_points) {
+            for (weight_v, pair) in bv.iter().zip(row.windows(2)) {
+                partial += (columns - 1) as f32 * weight_u * weight_v * (pair[1] - pair[0]);
+            }
+        }
+        partial
+    }
+
+    /// The normal of the surface, as the cross product of both partial derivatives.
+    ///
+    /// `None` when `DIM` isn't 3, the only dimension where a patch has a normal, or where both
+    /// partial derivatives are parallel and the surface is degenerate.
+    pub fn normal(&self, u: f32, v: f32) -> Option<Unit<SVector<f32, DIM>>> {
+        if DIM != 3 {
+            return None;
+        }
+        let (a, b) = (self.partial_u(u, v), self.partial_v(u, v));
+
+        // nalgebra's cross product only type-checks for 3D vectors, so it's spelled out
+        let mut normal = SVector::zeros();
+        for i in 0..DIM {
+            let (j, k) = ((i + 1) % DIM, (i + 2) % DIM);
+            normal[i] = a[j] * b[k] - a[k] * b[j];
+        }
+        Unit::try_new(normal, 0.0)
+    }
+
+    /// The number of rows and of points in the shortest one.
+    fn shape(&self) -> (usize, usize) {
+        let columns = self.control_points.iter().map(Vec::len).min();
+        (self.control_points.len(), columns.unwrap_or(0))
+    }
+}
+
+/// The bernstein polynomials of degree `n` at `t`.
+///
+/// Above [`MAX_BERNSTEIN_DEGREE`], they are built up from degree 0 with de casteljau's
+/// recurrence instead, as the binomial coefficients lose precision and eventually overflow.
+fn bernstein(n: usize, t: f32) -> Vec<f32> {
+    if n > MAX_BERNSTEIN_DEGREE {
+        let mut polynomials = vec![1.0];
+        for _ in 0..n {
+            let mut next = vec![0.0; polynomials.len() + 1];
+            for (i, polynomial) in polynomials.iter().enumerate() {
+                next[i] += (1.0 - t) * polynomial;
+                next[i + 1] += t * polynomial;
+            }
+            polynomials = next;
+        }
+        return polynomials;
+    }
+
+    with_binomials(n, |binomials| {
+        binomials
+            .iter()
+            .enumerate()
+            .map(|(i, binomial)| {
+                *binomial as f32 * t.powi(i as i32) * (1.0 - t).powi((n - i) as i32)
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete_with_0(mut vec: Vec<f32>) -> Vec<f32> {
+        vec.resize(DIM, 0.0);
+        vec
+    }
+
+    // the unit square of the z = 0 plane, with bilinear control points on a 3x2 grid
+    fn planar_patch() -> BezierPatchMirror {
+        BezierPatchMirror::new(
+            [0.0, 0.5, 1.0]
+                .into_iter()
+                .map(|x| {
+                    [0.0, 1.0]
+                        .into_iter()
+                        .map(|y| Point::from_slice(&complete_with_0(vec![x, y, 0.0])))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_calculate_point() {
+        let patch = planar_patch();
+
+        let point = patch.calculate_point(0.25, 0.75);
+
+        assert!((point - Point::from_slice(&complete_with_0(vec![0.25, 0.75, 0.0]))).norm() < 1e-6);
+        assert!(
+            (patch.partial_u(0.3, 0.6) - SVector::from_vec(complete_with_0(vec![1.0, 0.0, 0.0])))
+                .norm()
+                < 1e-6
+        );
+        assert!(
+            (patch.partial_v(0.3, 0.6) - SVector::from_vec(complete_with_0(vec![0.0, 1.0, 0.0])))
+                .norm()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_normal_planar_patch() {
+        let patch = planar_patch();
+
+        for (u, v) in [(0.0, 0.0), (0.3, 0.6), (1.0, 0.5)] {
+            if DIM == 3 {
+                let expected = SVector::from_vec(complete_with_0(vec![0.0, 0.0, 1.0]));
+                assert!((patch.normal(u, v).unwrap().into_inner() - expected).norm() < 1e-6);
+            } else {
+                assert_eq!(patch.normal(u, v), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_degenerate_patches() {
+        let empty = BezierPatchMirror::new(vec![]);
+        assert_eq!(empty.calculate_point(0.5, 0.5), Point::origin());
+        assert_eq!(empty.partial_u(0.5, 0.5).norm(), 0.0);
+        assert_eq!(empty.partial_v(0.5, 0.5).norm(), 0.0);
+        assert_eq!(empty.normal(0.5, 0.5), None);
+
+        // the extra point of the second row is ignored
+        let mut ragged = planar_patch();
+        ragged.control_points[1].push(Point::from_slice(&complete_with_0(vec![9.0, 9.0])));
+        assert_eq!(
+            ragged.calculate_point(0.25, 0.75),
+            planar_patch().calculate_point(0.25, 0.75)
+        );
+        assert_eq!(
+            ragged.partial_u(0.3, 0.6),
+            planar_patch().partial_u(0.3, 0.6)
+        );
+        assert_eq!(
+            BezierPatchMirror::new(vec![vec![]]).calculate_point(0.5, 0.5),
+            Point::origin()
+        );
+    }
+
+    #[test]
+    fn test_high_degree_patch() {
+        // 70 rows would overflow the binomial coefficients, the grid still spans the unit square
+        let patch = BezierPatchMirror::new(
+            (0..70)
+                .map(|i| {
+                    [0.0, 1.0]
+                        .into_iter()
+                        .map(|y| Point::from_slice(&complete_with_0(vec![i as f32 / 69.0, y, 0.0])))
+                        .collect()
+                })
+                .collect(),
+        );
+
+        let point = patch.calculate_point(0.25, 0.75);
+
+        assert!((point - Point::from_slice(&complete_with_0(vec![0.25, 0.75, 0.0]))).norm() < 1e-5);
+        assert!(
+            (patch.partial_u(0.3, 0.6) - SVector::from_vec(complete_with_0(vec![1.0, 0.0, 0.0])))
+                .norm()
+                < 1e-4
+        );
+        assert!(
+            (patch.partial_v(0.3, 0.6) - SVector::from_vec(complete_with_0(vec![0.0, 1.0, 0.0])))
+                .norm()
+                < 1e-5
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use std::f32::consts::TAU;
+
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{
+    error::MirrorError,
+    json, material,
+    mirror::{degenerate, householder_matrix, is_valid_distance, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// An arc of circle, only meaningful in 2D. Being that common, its intersection is
+/// specialized rather than going through the general sphere one.
+#[derive(Clone, Copy)]
+pub struct CircleMirror {
+    center: Point<f32, DIM>,
+    radius: f32,
+    /// Angle where the arc starts, in radians.
+    start_angle: f32,
+    /// Angle covered by the arc counterclockwise from its start, `TAU` for a full circle.
+    span: f32,
+    reflectivity: f32,
+}
+
+impl Mirror for CircleMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        let (ox, oy) = (
+            ray.origin[0] - self.center[0],
+            ray.origin[1] - self.center[1],
+        );
+        let (dx, dy) = (ray.direction[0], ray.direction[1]);
+
+        let b = dx * ox + dy * oy;
+        let discriminant = b * b - (ox * ox + oy * oy - self.radius * self.radius);
+        if discriminant < 0.0 {
+            return Reflections::new();
+        }
+
+        let root = discriminant.sqrt();
+        let mut reflections = Reflections::new();
+        // the roots are already sorted
+        for distance in [-b - root, -b + root] {
+            let (x, y) = (ox + distance * dx, oy + distance * dy);
+            if is_valid_distance(distance) && self.covers(x, y) {
+                let mut normal = SVector::<f32, DIM>::zeros();
+                normal[0] = x / self.radius;
+                normal[1] = y / self.radius;
+                reflections.push((distance, householder_matrix(&Unit::new_unchecked(normal))));
+            }
+        }
+
+        reflections
+    }
+    fn get_type(&self) -> String {
+        "circle".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        let offset = point - self.center;
+        (offset.norm() - self.radius).abs() <= tolerance && self.covers(offset[0], offset[1])
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        // a full circle would have its first and last samples at the same place
+        let steps = if self.span >= TAU {
+            n
+        } else {
+            n.saturating_sub(1).max(1)
+        };
+
+        (0..n)
+            .map(|i| self.point_at(self.start_angle + i as f32 / steps as f32 * self.span))
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    /// The angles covered by the arc, in radians, as taken by [`CircleMirror::point_at`].
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        Some((self.start_angle, self.start_angle + self.span))
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.span < TAU
+            && [self.start_angle, self.start_angle + self.span]
+                .iter()
+                .any(|angle| (point - self.point_at(*angle)).norm() <= tolerance)
+    }
+    /// On the unit circle, at the same angle.
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        Some(Point::from((point - self.center) / self.radius))
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        Some((self.center, self.radius))
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !self.center.iter().all(|x| x.is_finite()) {
+            degenerate(self, "its center isn't finite")
+        } else if !(self.radius.is_finite() && self.radius > 0.0) {
+            degenerate(self, "its radius isn't strictly positive")
+        } else if self.span <= 0.0 {
+            degenerate(self, "its arc is empty")
+        } else {
+            Ok(())
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "center": json::from_point(&self.center),
+            "radius": self.radius,
+            "start_angle": self.start_angle,
+            "end_angle": self.start_angle + self.span,
+            "reflectivity": self.reflectivity,
+        }))
+    }
+}
+
+impl CircleMirror {
+    /// A full circle.
+    pub fn new(center: Point<f32, DIM>, radius: f32) -> Self {
+        Self::arc(center, radius, 0.0, TAU)
+    }
+
+    /// The arc going counterclockwise from `start_angle` to `end_angle`, in radians.
+    pub fn arc(center: Point<f32, DIM>, radius: f32, start_angle: f32, end_angle: f32) -> Self {
+        let span = if end_angle - start_angle >= TAU {
+            TAU
+        } else {
+            (end_angle - start_angle).rem_euclid(TAU)
+        };
+
+        Self {
+            center,
+            radius,
+            start_angle,
+            span,
+            reflectivity: 1.0,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "center": [1.0, 2.0],
+            "radius": 4.0,
+            "start_angle": 0.0, // optional, in radians
+            "end_angle": 3.14, // optional, in radians
+            "material": "silver" // or "reflectivity": 0.97, optional
+        }
+         */
+        let center = json::point(json, "center")?;
+        let radius = json::number(json, "radius")?;
+        let start_angle = match &json["start_angle"] {
+            serde_json::Value::Null => 0.0,
+            _ => json::number(json, "start_angle")?,
+        };
+        let end_angle = match &json["end_angle"] {
+            serde_json::Value::Null => start_angle + TAU,
+            _ => json::number(json, "end_angle")?,
+        };
+
+        Ok(Self::arc(center, radius, start_angle, end_angle)
+            .with_reflectivity(material::reflectivity_from_json(json)?))
+    }
+
+    pub fn center(&self) -> Point<f32, DIM> {
+        self.center
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn point_at(&self, angle: f32) -> Point<f32, DIM> {
+        let mut offset = SVector::<f32, DIM>::zeros();
+        offset[0] = angle.cos();
+        offset[1] = angle.sin();
+        self.center + self.radius * offset
+    }
+
+    // whether the direction (x, y) from the center is within the arc
+    fn covers(&self, x: f32, y: f32) -> bool {
+        self.span >= TAU || (y.atan2(x) - self.start_angle).rem_euclid(TAU) <= self.span
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::{mirror::SphereMirror, ray::ray_from, scene::Scene};
+
+    #[test]
+    fn test_local_point() {
+        let scene = Scene::new(vec![Box::new(CircleMirror::new([1.0, -2.0].into(), 3.0))]);
+        let ray = ray_from([-5.0, -2.0], [1.0, 0.2]);
+
+        let (_, intersection) = scene.nearest_hit(&ray).unwrap();
+        let local = intersection.local_point.unwrap();
+
+        // on the unit circle, in the direction of the hit from the center
+        assert!((local.coords.norm() - 1.0).abs() < 1e-6);
+        let direction = (intersection.point - Point::from([1.0, -2.0])).normalize();
+        assert!((local.coords - direction).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_reflect_matches_sphere() {
+        let circle = CircleMirror::new([1.0, -2.0].into(), 3.0);
+        let sphere = SphereMirror::new([1.0, -2.0].into(), 3.0);
+
+        for i in 0..50 {
+            let angle = i as f32 * 0.37;
+            let origin = [(i as f32 * 0.7).sin() * 6.0, (i as f32 * 1.3).cos() * 6.0];
+            let ray = ray_from(origin, [angle.cos(), angle.sin()]);
+
+            let expected = sphere.reflect(ray);
+            let actual = circle.reflect(ray);
+
+            assert_eq!(expected.is_empty(), actual.is_empty());
+            if let (Some(expected), Some(actual)) = (expected.first(), actual.first()) {
+                assert!((expected.0 - actual.0).abs() < 1e-4);
+                assert!((expected.1.into_inner() - actual.1.into_inner()).norm() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reflect_arc() {
+        // upper half of the unit circle
+        let arc = CircleMirror::arc(Point::origin(), 1.0, 0.0, PI);
+
+        let reflections = arc.reflect(ray_from([0.0, 0.0], [0.0, 1.0]));
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 1.0).abs() < 1e-6);
+
+        assert!(arc.reflect(ray_from([0.0, 0.0], [0.0, -1.0])).is_empty());
+
+        // from below, the first root is off the arc but the second one isn't
+        let reflections = arc.reflect(ray_from([0.0, -2.0], [0.0, 1.0]));
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let arc = CircleMirror::arc(Point::origin(), 1.0, -PI / 2.0, PI / 2.0);
+
+        assert!(arc.contains_point(&[1.0, 0.0].into(), 1e-6));
+        assert!(!arc.contains_point(&[-1.0, 0.0].into(), 1e-6));
+        assert!(arc
+            .sample_surface(5)
+            .iter()
+            .all(|p| arc.contains_point(p, 1e-5)));
+    }
+
+    #[test]
+    fn test_param_domain() {
+        let arc = CircleMirror::arc(Point::origin(), 1.0, -PI / 2.0, PI / 2.0);
+        assert_eq!(arc.param_domain(), Some((-PI / 2.0, PI / 2.0)));
+        assert_eq!(
+            CircleMirror::new(Point::origin(), 1.0).param_domain(),
+            Some((0.0, TAU))
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = serde_json::json!({
+            "center": [1.0, 2.0],
+            "radius": 3.0,
+            "start_angle": 0.0,
+            "end_angle": 1.0
+        });
+
+        let arc = CircleMirror::from_json(&json).unwrap();
+
+        assert_eq!(arc.center(), Point::from([1.0, 2.0]));
+        assert_eq!(arc.radius(), 3.0);
+        assert!((arc.span - 1.0).abs() < 1e-6);
+
+        let full = CircleMirror::from_json(&serde_json::json!({
+            "center": [1.0, 2.0],
+            "radius": 3.0,
+        }))
+        .unwrap();
+        assert!((full.span - TAU).abs() < 1e-6);
+
+        let mut invalid = json.clone();
+        invalid["end_angle"] = serde_json::json!("1.0");
+        assert!(CircleMirror::from_json(&invalid).is_err());
+    }
+}
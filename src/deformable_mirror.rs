@@ -0,0 +1,170 @@
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{
+    error::MirrorError,
+    mirror::{degenerate, householder_matrix, Mirror, PlaneMirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// A flat mirror pushed by actuators, as used in adaptive optics.
+///
+/// Each actuator displaces the surface along its normal with a gaussian influence function
+/// centered on its position. Like for [`crate::figure_error_mirror::FigureErrorMirror`], the
+/// displacements are assumed small: only the local normal tilts, following the slope of the
+/// summed displacements, and the intersections stay those of the plane.
+#[derive(Clone)]
+pub struct DeformableMirror {
+    plane: PlaneMirror,
+    positions: Vec<Point<f32, DIM>>,
+    /// Displacement of each actuator, along the normal of the plane.
+    displacements: Vec<f32>,
+    /// Standard deviation of the influence functions.
+    width: f32,
+}
+
+impl Mirror for DeformableMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.plane
+            .reflect(ray)
+            .into_iter()
+            .map(|(distance, matrix)| {
+                if self
+                    .displacements
+                    .iter()
+                    .all(|displacement| *displacement == 0.0)
+                {
+                    return (distance, matrix);
+                }
+
+                let point = ray.origin + distance * ray.direction.into_inner();
+                let normal = self.plane.normal().into_inner();
+                let slope = self.gradient(&point);
+                let slope = slope - slope.dot(&normal) * normal;
+
+                (
+                    distance,
+                    householder_matrix(&Unit::new_normalize(normal - slope)),
+                )
+            })
+            .collect()
+    }
+    fn get_type(&self) -> String {
+        "deformable".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.plane.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.plane.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.plane.reflectivity()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !(self.width.is_finite() && self.width > 0.0) {
+            degenerate(self, "its influence width isn't strictly positive")
+        } else {
+            self.plane.validate()
+        }
+    }
+}
+
+impl DeformableMirror {
+    /// A mirror with an actuator at each of `positions`, all at rest.
+    pub fn new(plane: PlaneMirror, positions: Vec<Point<f32, DIM>>, width: f32) -> Self {
+        Self {
+            plane,
+            displacements: vec![0.0; positions.len()],
+            positions,
+            width,
+        }
+    }
+
+    pub fn positions(&self) -> &[Point<f32, DIM>] {
+        &self.positions
+    }
+
+    pub fn displacements(&self) -> &[f32] {
+        &self.displacements
+    }
+
+    /// Moves the actuator at `index`, panicking if there is no such actuator.
+    pub fn set_displacement(&mut self, index: usize, displacement: f32) {
+        self.displacements[index] = displacement;
+    }
+
+    /// Displacement of the surface at `point`, summed over every actuator.
+    pub fn displacement_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.positions
+            .iter()
+            .zip(&self.displacements)
+            .map(|(position, displacement)| displacement * self.influence(point, position))
+            .sum()
+    }
+
+    fn influence(&self, point: &Point<f32, DIM>, position: &Point<f32, DIM>) -> f32 {
+        (-(point - position).norm_squared() / (2.0 * self.width * self.width)).exp()
+    }
+
+    fn gradient(&self, point: &Point<f32, DIM>) -> SVector<f32, DIM> {
+        self.positions
+            .iter()
+            .zip(&self.displacements)
+            .map(|(position, displacement)| {
+                -displacement * self.influence(point, position) * (point - position)
+                    / (self.width * self.width)
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::ray_from;
+
+    fn mirror() -> DeformableMirror {
+        DeformableMirror::new(
+            PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]),
+            (-2..=2).map(|i| [i as f32, 0.0].into()).collect(),
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_flat_at_rest() {
+        let deformable = mirror();
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+
+        for x in [-1.7, -0.3, 0.0, 0.8] {
+            let ray = ray_from([x, 1.0], [0.3, -1.0]);
+            assert_eq!(deformable.reflect(ray), plane.reflect(ray));
+        }
+    }
+
+    #[test]
+    fn test_poked_actuator_tilts_reflection() {
+        let mut deformable = mirror();
+        deformable.set_displacement(2, 0.1);
+        assert!((deformable.displacement_at(&[0.0, 0.0].into()) - 0.1).abs() < 1e-6);
+
+        let reflected = |x: f32| {
+            let ray = ray_from([x, 1.0], [0.0, -1.0]);
+            let (distance, matrix) = deformable.reflect(ray)[0];
+            assert!((distance - 1.0).abs() < 1e-6);
+            matrix.into_inner() * ray.direction.into_inner()
+        };
+
+        // right on top of the actuator the surface is flat
+        assert!((reflected(0.0) - SVector::<f32, DIM>::from([0.0, 1.0])).norm() < 1e-6);
+        // on its sides the bump sends the rays away from it
+        assert!(reflected(0.4)[0] > 0.01);
+        assert!(reflected(-0.4)[0] < -0.01);
+        // far from any poked actuator nothing changes
+        assert!((reflected(3.0) - SVector::<f32, DIM>::from([0.0, 1.0])).norm() < 1e-4);
+    }
+}
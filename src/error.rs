@@ -0,0 +1,93 @@
+use thiserror::Error;
+
+/// Everything that can go wrong while building a mirror.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum MirrorError {
+    /// A field is missing or doesn't have the expected shape.
+    #[error("field `{field}` should be {expected}")]
+    InvalidField {
+        field: String,
+        expected: &'static str,
+    },
+    /// A point doesn't have exactly `DIM` coordinates. `index` is its position in `field` when
+    /// it holds an array of points, and `None` when it holds a single one.
+    #[error("{} has {found} coordinates, expected {expected}", point_name(.field, *.index))]
+    DimensionMismatch {
+        field: String,
+        index: Option<usize>,
+        found: usize,
+        expected: usize,
+    },
+    #[error("unknown mirror type: {0}")]
+    UnknownMirrorType(String),
+    #[error("unknown material: {0}")]
+    UnknownMaterial(String),
+    /// An svg path's `d` attribute couldn't be parsed.
+    #[error("invalid svg path: {0}")]
+    InvalidSvgPath(String),
+    /// A file couldn't be read or written.
+    #[error("couldn't access the file: {0}")]
+    Io(String),
+    /// A toml scene isn't valid toml.
+    #[error("invalid toml: {0}")]
+    InvalidToml(String),
+    /// A mirror can't be written to json, e.g. because it is built from closures.
+    #[error("{0} mirrors can't be written to json")]
+    Unserializable(String),
+    /// A mirror has no reflecting surface to speak of, e.g. a sphere with no radius.
+    #[error("degenerate {mirror_type} mirror: {reason}")]
+    DegenerateMirror {
+        mirror_type: String,
+        reason: &'static str,
+    },
+    /// Two mirrors of a scene share the same surface, so every ray hitting one hits both.
+    #[error("mirrors {first} and {second} coincide")]
+    CoincidentMirrors { first: usize, second: usize },
+}
+
+fn point_name(field: &str, index: Option<usize>) -> String {
+    match index {
+        Some(index) => format!("point {index} of `{field}`"),
+        None => format!("point `{field}`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = MirrorError::DimensionMismatch {
+            field: "points".to_string(),
+            index: Some(2),
+            found: 3,
+            expected: 2,
+        };
+        assert_eq!(
+            error.to_string(),
+            "point 2 of `points` has 3 coordinates, expected 2"
+        );
+
+        let error = MirrorError::DimensionMismatch {
+            field: "center".to_string(),
+            index: None,
+            found: 3,
+            expected: 2,
+        };
+        assert_eq!(
+            error.to_string(),
+            "point `center` has 3 coordinates, expected 2"
+        );
+
+        let error = MirrorError::InvalidField {
+            field: "radius".to_string(),
+            expected: "a number",
+        };
+        assert_eq!(error.to_string(), "field `radius` should be a number");
+
+        let boxed: Box<dyn std::error::Error> =
+            Box::new(MirrorError::UnknownMaterial("unobtainium".to_string()));
+        assert_eq!(boxed.to_string(), "unknown material: unobtainium");
+    }
+}
@@ -0,0 +1,489 @@
+//! Preparing traced paths for plots.
+
+use std::{fmt::Write, io};
+
+use nalgebra::{Point, SVector};
+
+use crate::{
+    math::{dedup_consecutive, normal_from_reflection},
+    mirror::Mirror,
+    ray::Ray,
+    scene::{tolerance, Scene},
+    simulation::TraceResult,
+    DIM,
+};
+
+/// Number of points drawn for each mirror.
+const MIRROR_SAMPLES: usize = 64;
+/// Consecutive points closer than this, relative to the size of the drawing, are merged.
+const DEDUP_TOLERANCE: f32 = 1e-6;
+
+/// Clips polylines to the box going from `min` to `max`, with the Liang–Barsky algorithm.
+///
+/// A path leaving the box and coming back is split in two, so every returned polyline lies
+/// entirely inside it. Paths which never enter the box are dropped.
+pub fn clip_paths(
+    paths: &[Vec<Point<f32, DIM>>],
+    min: Point<f32, DIM>,
+    max: Point<f32, DIM>,
+) -> Vec<Vec<Point<f32, DIM>>> {
+    let mut clipped = vec![];
+
+    for path in paths {
+        let mut current: Vec<Point<f32, DIM>> = vec![];
+
+        if let [point] = path.as_slice() {
+            if (0..DIM).all(|axis| min[axis] <= point[axis] && point[axis] <= max[axis]) {
+                clipped.push(vec![*point]);
+            }
+            continue;
+        }
+
+        for pair in path.windows(2) {
+            let Some((start, end)) = clip_segment(pair[0], pair[1], &min, &max) else {
+                if current.len() > 1 {
+                    clipped.push(std::mem::take(&mut current));
+                }
+                current.clear();
+                continue;
+            };
+
+            // the segment was cut at its start, so it doesn't continue the current polyline
+            if current.last() != Some(&start) {
+                if current.len() > 1 {
+                    clipped.push(std::mem::take(&mut current));
+                }
+                current = vec![start];
+            }
+            current.push(end);
+
+            if end != pair[1] {
+                clipped.push(std::mem::take(&mut current));
+            }
+        }
+
+        if current.len() > 1 {
+            clipped.push(current);
+        }
+    }
+
+    clipped
+}
+
+/// Draws the mirrors of `scene` in gray and the paths of `results` colored by their
+/// wavelength, as an svg document framing all of them. Only meaningful in 2D.
+pub fn to_svg(scene: &Scene, results: &[TraceResult]) -> String {
+    let mirrors = scene
+        .mirrors()
+        .iter()
+        .map(|mirror| mirror.sample_surface(MIRROR_SAMPLES))
+        .collect::<Vec<_>>();
+    let all_points = mirrors
+        .iter()
+        .chain(results.iter().map(|result| &result.path))
+        .flatten();
+
+    let (mut svg, size) = svg_header(all_points);
+    let tolerance = DEDUP_TOLERANCE * size;
+    for points in &mirrors {
+        svg += &polyline(&dedup_consecutive(points, tolerance), "gray");
+    }
+    for result in results {
+        let [r, g, b] = wavelength_to_rgb(result.last_ray.wavelength);
+        let path = dedup_consecutive(&result.path, tolerance);
+        svg += &polyline(&path, &format!("rgb({r},{g},{b})"));
+    }
+    svg += "</svg>";
+
+    svg
+}
+
+/// Draws `mirror` in gray with its normals in red at `samples` points along it, each `length`
+/// long, as an svg document written to `out`. Only meaningful in 2D.
+///
+/// The normals are the mirror's own, from the reflection of a ray sent at each sample, so
+/// they stay right across seams and corners. They are oriented left of the direction
+/// [`Mirror::sample_surface`] goes along the mirror, like
+/// [`crate::bezier_mirror::BezierMirror::calculate_normal`], so a mirror drawn the wrong way
+/// around shows them on the wrong side.
+pub fn normals_to_svg(
+    mirror: &dyn Mirror,
+    samples: usize,
+    length: f32,
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let points = mirror.sample_surface(samples);
+    let normals = points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, point)| {
+            // left of the direction along the mirror, from the neighbouring samples
+            let tangent = points[(i + 1).min(points.len() - 1)] - points[i.saturating_sub(1)];
+            let left = SVector::<f32, DIM>::from_fn(|axis, _| match axis {
+                0 => -tangent[1],
+                1 => tangent[0],
+                _ => 0.0,
+            })
+            .try_normalize(f32::EPSILON);
+
+            let normal = normal_at(mirror, point, left, length)?;
+            let normal = match left {
+                Some(left) if normal.dot(&left) < 0.0 => -normal,
+                _ => normal,
+            };
+            Some([*point, point + length * normal])
+        })
+        .collect::<Vec<_>>();
+
+    let (mut svg, _) = svg_header(points.iter().chain(normals.iter().flatten()));
+    svg += &polyline(&points, "gray");
+    for normal in &normals {
+        svg += &polyline(normal, "red");
+    }
+    svg += "</svg>";
+
+    out.write_all(svg.as_bytes())
+}
+
+/// The normal of `mirror` at `point`, on its surface, from the reflection of a ray sent
+/// straight at `point` from `distance` away: along `guess` first, then along each axis, until
+/// one of them is reflected there.
+fn normal_at(
+    mirror: &dyn Mirror,
+    point: &Point<f32, DIM>,
+    guess: Option<SVector<f32, DIM>>,
+    distance: f32,
+) -> Option<SVector<f32, DIM>> {
+    let tolerance = tolerance(point, mirror.bounding_sphere());
+
+    guess
+        .into_iter()
+        .chain((0..DIM).map(|axis| SVector::ith(axis, 1.0)))
+        .find_map(|direction| {
+            let ray = Ray::new(point + distance * direction, -direction);
+            mirror
+                .reflect(ray)
+                .into_iter()
+                .find(|(to_point, _)| (to_point - distance).abs() <= tolerance)
+                .map(|(_, matrix)| normal_from_reflection(&matrix).into_inner())
+        })
+}
+
+/// Writes every segment of `paths` as a row of a csv ray database, the plain columnar layout
+/// optics tools like zemax or comsol import rays from. The columns are:
+///
+/// - `ray` and `segment`, the indices of the path and of the segment along it,
+/// - `start_x`, `start_y`, `end_x` and `end_y`, the ends of the segment,
+/// - `cos_x` and `cos_y`, the direction cosines of the segment, which form a unit vector,
+/// - `intensity`, taken from `intensities` for every segment of the ray (1 if missing).
+///
+/// The first line names the columns. Segments of zero length have no direction and are
+/// skipped, without shifting the indices of the others.
+pub fn to_ray_database(
+    paths: &[Vec<Point<f32, DIM>>],
+    intensities: &[f32],
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let axes = ["x", "y", "z"];
+    let columns = |prefix: &str| {
+        axes[..DIM]
+            .iter()
+            .map(|axis| format!("{prefix}_{axis}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    writeln!(
+        out,
+        "ray,segment,{},{},{},intensity",
+        columns("start"),
+        columns("end"),
+        columns("cos")
+    )?;
+
+    let join = |values: &[f32]| {
+        values
+            .iter()
+            .map(f32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    for (ray, path) in paths.iter().enumerate() {
+        let intensity = intensities.get(ray).copied().unwrap_or(1.0);
+        for (segment, pair) in path.windows(2).enumerate() {
+            let Some(direction) = (pair[1] - pair[0]).try_normalize(f32::EPSILON) else {
+                continue;
+            };
+            writeln!(
+                out,
+                "{ray},{segment},{},{},{},{intensity}",
+                join(pair[0].coords.as_slice()),
+                join(pair[1].coords.as_slice()),
+                join(direction.as_slice()),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Approximate color of light of the given wavelength in nanometers, after dan bruton's
+/// piecewise linear mapping. Invisible wavelengths are black.
+pub fn wavelength_to_rgb(wavelength: f32) -> [u8; 3] {
+    let (r, g, b) = match wavelength {
+        w if (380.0..440.0).contains(&w) => (-(w - 440.0) / 60.0, 0.0, 1.0),
+        w if (440.0..490.0).contains(&w) => (0.0, (w - 440.0) / 50.0, 1.0),
+        w if (490.0..510.0).contains(&w) => (0.0, 1.0, -(w - 510.0) / 20.0),
+        w if (510.0..580.0).contains(&w) => ((w - 510.0) / 70.0, 1.0, 0.0),
+        w if (580.0..645.0).contains(&w) => (1.0, -(w - 645.0) / 65.0, 0.0),
+        w if (645.0..=780.0).contains(&w) => (1.0, 0.0, 0.0),
+        _ => (0.0, 0.0, 0.0),
+    };
+
+    // the eye is less sensitive at both ends of the spectrum
+    let intensity = match wavelength {
+        w if w < 420.0 => 0.3 + 0.7 * (w - 380.0) / 40.0,
+        w if w > 700.0 => 0.3 + 0.7 * (780.0 - w) / 80.0,
+        _ => 1.0,
+    };
+
+    [r, g, b].map(|channel: f32| (255.0 * (channel * intensity).clamp(0.0, 1.0)).round() as u8)
+}
+
+/// The opening tag of an svg document framing `points`, and the size of the frame.
+fn svg_header<'a>(points: impl Iterator<Item = &'a Point<f32, DIM>>) -> (String, f32) {
+    let (mut min, mut max) = ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]);
+    for point in points {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    if min[0] > max[0] {
+        (min, max) = ([0.0; 2], [1.0; 2]);
+    }
+
+    // svg's y axis points down
+    let header = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min[0],
+        -max[1],
+        (max[0] - min[0]).max(f32::EPSILON),
+        (max[1] - min[1]).max(f32::EPSILON)
+    );
+
+    (header, (max[0] - min[0]).max(max[1] - min[1]))
+}
+
+fn polyline(points: &[Point<f32, DIM>], color: &str) -> String {
+    let mut coordinates = String::new();
+    for point in points {
+        // subtracting from 0 rather than negating avoids printing -0
+        let _ = write!(coordinates, "{},{} ", point[0], 0.0 - point[1]);
+    }
+
+    format!(
+        r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+        coordinates.trim_end()
+    )
+}
+
+/// The part of the segment from `start` to `end` inside the box, if any.
+fn clip_segment(
+    start: Point<f32, DIM>,
+    end: Point<f32, DIM>,
+    min: &Point<f32, DIM>,
+    max: &Point<f32, DIM>,
+) -> Option<(Point<f32, DIM>, Point<f32, DIM>)> {
+    let delta = end - start;
+    let (mut enter, mut exit) = (0.0f32, 1.0f32);
+
+    for axis in 0..DIM {
+        // p * t <= q for both sides of the box along this axis
+        for (p, q) in [
+            (-delta[axis], start[axis] - min[axis]),
+            (delta[axis], max[axis] - start[axis]),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else if p < 0.0 {
+                enter = enter.max(q / p);
+            } else {
+                exit = exit.min(q / p);
+            }
+        }
+    }
+
+    if enter > exit {
+        return None;
+    }
+
+    let at = |t: f32| {
+        if t == 0.0 {
+            start
+        } else if t == 1.0 {
+            end
+        } else {
+            start + t * delta
+        }
+    };
+    Some((at(enter), at(exit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mirror::PlaneMirror, polygon_mirror::PolygonMirror, ray::ray_from, simulation::Simulation,
+    };
+
+    #[test]
+    fn test_clip_crossing_path() {
+        let paths = vec![vec![
+            [-1.0, 0.5].into(),
+            [0.5, 0.5].into(),
+            [0.5, 2.0].into(),
+        ]];
+
+        let clipped = clip_paths(&paths, [0.0, 0.0].into(), [1.0, 1.0].into());
+
+        assert_eq!(
+            clipped,
+            vec![vec![
+                [0.0, 0.5].into(),
+                [0.5, 0.5].into(),
+                [0.5, 1.0].into()
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_clip_splits_reentering_path() {
+        let paths = vec![vec![
+            [0.5, 0.5].into(),
+            [0.5, 2.0].into(),
+            [0.25, 0.5].into(),
+            [-1.0, 0.5].into(),
+        ]];
+
+        let clipped = clip_paths(&paths, [0.0, 0.0].into(), [1.0, 1.0].into());
+
+        assert_eq!(clipped.len(), 2);
+        assert_eq!(clipped[0], vec![[0.5, 0.5].into(), [0.5, 1.0].into()]);
+        assert_eq!(clipped[1].len(), 3);
+        assert!((clipped[1][0] - Point::from([1.0 / 3.0, 1.0])).norm() < 1e-6);
+        assert_eq!(clipped[1][2], [0.0, 0.5].into());
+    }
+
+    #[test]
+    fn test_clip_outside_path() {
+        let paths = vec![vec![[2.0, 2.0].into(), [3.0, 2.0].into()]];
+
+        assert!(clip_paths(&paths, [0.0, 0.0].into(), [1.0, 1.0].into()).is_empty());
+    }
+
+    #[test]
+    fn test_wavelength_to_rgb() {
+        let [r, g, b] = wavelength_to_rgb(650.0);
+        assert!(r > 200 && g < 50 && b < 50);
+
+        let [r, g, b] = wavelength_to_rgb(450.0);
+        assert!(b > 200 && r < 50 && g < 100);
+
+        assert_eq!(wavelength_to_rgb(1000.0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_to_svg_colors_rays() {
+        let simulation = Simulation::new(Scene::new(vec![Box::new(PlaneMirror::new([
+            [0.0, 0.0].into(),
+            [1.0, 0.0].into(),
+        ]))]));
+        let results = [650.0, 450.0].map(|wavelength| {
+            simulation.trace(
+                ray_from([0.0, 1.0], [1.0, -1.0]).with_wavelength(wavelength),
+                1,
+            )
+        });
+
+        let svg = to_svg(simulation.scene(), &results);
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 3);
+        assert!(svg.contains(r#"stroke="gray""#));
+        assert!(svg.contains(r#"stroke="rgb(255,0,0)""#));
+        assert!(svg.contains(r#"<polyline points="0,-1 "#));
+        assert!(svg.contains(r#"stroke="rgb(0,51,255)""#));
+    }
+
+    #[test]
+    fn test_normals_to_svg() {
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        let mut out = vec![];
+
+        normals_to_svg(&plane, 5, 0.5, &mut out).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert_eq!(svg.matches(r#"stroke="gray""#).count(), 1);
+        assert_eq!(svg.matches(r#"stroke="red""#).count(), 5);
+        // going right along the plane, the normals point up
+        assert!(svg.contains(r#"<polyline points="0,0 0,-0.5""#));
+    }
+
+    #[test]
+    fn test_normals_to_svg_on_corners() {
+        let square = PolygonMirror::new(
+            [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+                .map(Point::from)
+                .to_vec(),
+        );
+        let mut out = vec![];
+
+        // one sample on each vertex, where the neighbouring samples are along different edges
+        normals_to_svg(&square, 4, 0.5, &mut out).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+
+        // normal to one of the edges meeting there, pointing inside, rather than diagonal
+        assert_eq!(svg.matches(r#"stroke="red""#).count(), 4);
+        for normal in ["0,0 0,-0.5", "1,0 1,-0.5", "1,-1 0.5,-1", "0,-1 0,-0.5"] {
+            assert!(svg.contains(&format!(r#"<polyline points="{normal}""#)));
+        }
+    }
+
+    #[test]
+    fn test_to_ray_database() {
+        let paths = vec![
+            vec![
+                [0.0, 0.0].into(),
+                [3.0, 4.0].into(),
+                [3.0, 4.0].into(),
+                [3.0, 0.0].into(),
+            ],
+            vec![[1.0, 1.0].into(), [0.0, 1.0].into()],
+        ];
+        let mut out = vec![];
+
+        to_ray_database(&paths, &[0.5], &mut out).unwrap();
+        let database = String::from_utf8(out).unwrap();
+        let lines = database.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            [
+                "ray,segment,start_x,start_y,end_x,end_y,cos_x,cos_y,intensity",
+                "0,0,0,0,3,4,0.6,0.8,0.5",
+                "0,2,3,4,3,0,0,-1,0.5",
+                "1,0,1,1,0,1,-1,0,1",
+            ]
+        );
+        for line in &lines[1..] {
+            let values = line
+                .split(',')
+                .map(|value| value.parse::<f32>().unwrap())
+                .collect::<Vec<_>>();
+            assert!((values[6].hypot(values[7]) - 1.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{
+    error::MirrorError,
+    intersection::Intersection,
+    material::Polarization,
+    mirror::{householder_matrix, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Step used to differentiate the surface error numerically.
+const GRADIENT_STEP: f32 = 1e-3;
+
+/// Displacement of the surface along its normal at a given point.
+pub type SurfaceError = dyn Fn(&Point<f32, DIM>) -> f32;
+
+/// Wraps a mirror with a small manufacturing error of its surface, given as a displacement
+/// along the normal at each point, towards the side the ray comes from. Only the local normal
+/// is perturbed, the intersections stay those of the ideal surface.
+pub struct FigureErrorMirror {
+    mirror: Box<dyn Mirror>,
+    error: Box<SurfaceError>,
+}
+
+impl Mirror for FigureErrorMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.mirror
+            .reflect(ray)
+            .into_iter()
+            .map(|(distance, matrix)| {
+                let intersection = Intersection::new(&ray, distance, matrix);
+                let normal = intersection.facing_normal(&ray.direction);
+                let normal = self.perturbed_normal(&intersection.point, &normal);
+                (distance, householder_matrix(&normal))
+            })
+            .collect()
+    }
+    fn get_type(&self) -> String {
+        "figure_error".to_string()
+    }
+    /// The perturbed normals are differentiated numerically.
+    fn is_analytic(&self) -> bool {
+        false
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        self.mirror.is_opaque()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.mirror.reflectivity()
+    }
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.mirror.reflectivity_at(point)
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.mirror
+            .polarized_reflectivity(polarization, sin2_incidence)
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mirror.retardance(sin2_incidence)
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn max_incidence_angle(&self) -> Option<f32> {
+        self.mirror.max_incidence_angle()
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        self.mirror.validate()
+    }
+}
+
+impl FigureErrorMirror {
+    pub fn new(mirror: Box<dyn Mirror>, error: impl Fn(&Point<f32, DIM>) -> f32 + 'static) -> Self {
+        Self {
+            mirror,
+            error: Box::new(error),
+        }
+    }
+
+    /// The normal tilts against the slope of the error along the surface.
+    fn perturbed_normal(
+        &self,
+        point: &Point<f32, DIM>,
+        normal: &Unit<SVector<f32, DIM>>,
+    ) -> Unit<SVector<f32, DIM>> {
+        let gradient = SVector::<f32, DIM>::from_fn(|axis, _| {
+            let step = SVector::<f32, DIM>::ith(axis, GRADIENT_STEP);
+            ((self.error)(&(point + step)) - (self.error)(&(point - step))) / (2.0 * GRADIENT_STEP)
+        });
+        let slope = gradient - gradient.dot(normal) * normal.into_inner();
+
+        Unit::new_normalize(normal.into_inner() - slope)
+    }
+}
+
+/// A sinusoidal ripple of the given amplitude and period, travelling along `direction`.
+pub fn ripple(
+    amplitude: f32,
+    period: f32,
+    direction: SVector<f32, DIM>,
+) -> impl Fn(&Point<f32, DIM>) -> f32 {
+    let direction = direction.normalize();
+    move |point| amplitude * (std::f32::consts::TAU * point.coords.dot(&direction) / period).sin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::rms_spot_size, bezier_mirror::BezierMirror, mirror::PlaneMirror, ray::ray_from,
+        scene::Scene, simulation::Simulation,
+    };
+
+    // parabola y = x^2 / 4 focusing at (0, 1), with a detector there
+    fn spot_size(error: impl Fn(&Point<f32, DIM>) -> f32 + 'static) -> f32 {
+        let parabola = BezierMirror::new(vec![
+            [-1.0, 0.25].into(),
+            [0.0, -0.25].into(),
+            [1.0, 0.25].into(),
+        ]);
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(FigureErrorMirror::new(Box::new(parabola), error)),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ]));
+
+        let spot = (-8..=8)
+            .map(|i| {
+                let ray = ray_from([i as f32 / 10.0 + 0.01, 0.5], [0.0, -1.0]);
+                simulation.trace(ray, 2).path[2]
+            })
+            .collect::<Vec<_>>();
+
+        rms_spot_size(&spot)
+    }
+
+    #[test]
+    fn test_zero_error_is_ideal() {
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 1.0].into()]);
+        let mirror = FigureErrorMirror::new(Box::new(plane), |_| 0.0);
+        let ray = ray_from([2.0, 0.0], [-1.0, 0.0]);
+
+        let ideal = plane.reflect(ray);
+        let perturbed = mirror.reflect(ray);
+
+        assert_eq!(ideal.len(), perturbed.len());
+        assert!((ideal[0].0 - perturbed[0].0).abs() < 1e-6);
+        assert!((ideal[0].1.into_inner() - perturbed[0].1.into_inner()).norm() < 1e-6);
+
+        assert!(spot_size(|_| 0.0) < 1e-3);
+    }
+
+    #[test]
+    fn test_error_is_towards_the_ray() {
+        // raised by 0.1x towards either side, like the planes y = 0.1x and y = -0.1x
+        let mirror = FigureErrorMirror::new(
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            |point| 0.1 * point[0],
+        );
+
+        for (ray, tilted) in [
+            (ray_from([0.0, 1.0], [0.0, -1.0]), [1.0, 0.1]),
+            (ray_from([0.0, -1.0], [0.0, 1.0]), [1.0, -0.1]),
+        ] {
+            let tilted = PlaneMirror::new([[0.0, 0.0].into(), tilted.into()]);
+            let reflected = mirror.reflect_ray(&ray).unwrap();
+            let expected = tilted.reflect_ray(&ray).unwrap();
+
+            assert!(
+                (reflected.direction.into_inner() - expected.direction.into_inner()).norm() < 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn test_ripple_increases_spot_size() {
+        let ideal = spot_size(|_| 0.0);
+        let rippled = spot_size(ripple(1e-3, 0.1, [1.0, 0.0].into()));
+
+        assert!(rippled > ideal + 1e-2);
+    }
+}
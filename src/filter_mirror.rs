@@ -0,0 +1,116 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    material::Polarization,
+    mirror::{Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Decides whether a ray is reflected by a [`FilterMirror`].
+pub type RayPredicate = dyn Fn(&Ray) -> bool;
+
+/// Wraps a mirror so it only reflects the rays matching a predicate, like a dichroic mirror
+/// selecting wavelengths. The other rays go through it as if it wasn't there.
+pub struct FilterMirror {
+    mirror: Box<dyn Mirror>,
+    predicate: Box<RayPredicate>,
+}
+
+impl Mirror for FilterMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        if (self.predicate)(&ray) {
+            self.mirror.reflect(ray)
+        } else {
+            Reflections::new()
+        }
+    }
+    fn get_type(&self) -> String {
+        "filter".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.mirror.is_analytic()
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        self.mirror.is_opaque()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.mirror.reflectivity()
+    }
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.mirror.reflectivity_at(point)
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.mirror
+            .polarized_reflectivity(polarization, sin2_incidence)
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mirror.retardance(sin2_incidence)
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn max_incidence_angle(&self) -> Option<f32> {
+        self.mirror.max_incidence_angle()
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        self.mirror.validate()
+    }
+}
+
+impl FilterMirror {
+    pub fn new(mirror: Box<dyn Mirror>, predicate: impl Fn(&Ray) -> bool + 'static) -> Self {
+        Self {
+            mirror,
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Reflects the rays shorter than `wavelength` and lets the others through.
+    pub fn short_pass(mirror: Box<dyn Mirror>, wavelength: f32) -> Self {
+        Self::new(mirror, move |ray| ray.wavelength < wavelength)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::{test_helpers::in_front_of_plane, PlaneMirror};
+
+    #[test]
+    fn test_reflects_short_wavelengths() {
+        // a dichroic mirror at y = 1, in front of a plain mirror at y = 0
+        let simulation = in_front_of_plane(Box::new(FilterMirror::short_pass(
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+            500.0,
+        )));
+
+        let results =
+            simulation.trace_spectrum([0.0, 2.0].into(), [1.0, -1.0].into(), &[450.0, 600.0], 1);
+
+        assert!((results[0].path[1] - Point::from([1.0, 1.0])).norm() < 1e-5);
+        assert!((results[1].path[1] - Point::from([2.0, 0.0])).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,81 @@
+//! Conversions between the 2D mirrors and the `geo` ecosystem's geometries.
+
+use geo_types::{Coord, Line, LineString, Polygon};
+use nalgebra::Point;
+
+use crate::{polygon_mirror::PolygonMirror, segment_mirror::SegmentMirror, DIM};
+
+fn to_point(coord: Coord<f32>) -> Point<f32, DIM> {
+    Point::from_slice(&[coord.x, coord.y])
+}
+
+fn to_coord(point: &Point<f32, DIM>) -> Coord<f32> {
+    Coord {
+        x: point[0],
+        y: point[1],
+    }
+}
+
+impl From<Line<f32>> for SegmentMirror {
+    fn from(line: Line<f32>) -> Self {
+        Self::new(to_point(line.start), to_point(line.end))
+    }
+}
+
+impl From<&SegmentMirror> for Line<f32> {
+    fn from(segment: &SegmentMirror) -> Self {
+        Line::new(to_coord(&segment.start()), to_coord(&segment.end()))
+    }
+}
+
+/// Only the exterior ring is kept, holes are ignored.
+impl From<Polygon<f32>> for PolygonMirror {
+    fn from(polygon: Polygon<f32>) -> Self {
+        let (exterior, _) = polygon.into_inner();
+
+        // geo repeats the first coordinate at the end of a closed ring
+        let mut coords = exterior.0;
+        if coords.len() > 1 && coords.first() == coords.last() {
+            coords.pop();
+        }
+
+        Self::new(coords.into_iter().map(to_point).collect())
+    }
+}
+
+impl From<&PolygonMirror> for Polygon<f32> {
+    fn from(polygon: &PolygonMirror) -> Self {
+        let exterior = polygon.vertices().iter().map(to_coord).collect::<Vec<_>>();
+
+        Polygon::new(LineString::new(exterior), vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_round_trip() {
+        let line = Line::new(Coord { x: 1.0, y: 2.0 }, Coord { x: 3.0, y: 4.0 });
+
+        let segment = SegmentMirror::from(line);
+
+        assert_eq!(segment.start(), Point::from([1.0, 2.0]));
+        assert_eq!(Line::from(&segment), line);
+    }
+
+    #[test]
+    fn test_polygon_round_trip() {
+        let polygon = Polygon::new(
+            LineString::from(vec![(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (0.0, 1.0)]),
+            vec![],
+        );
+
+        let mirror = PolygonMirror::from(polygon.clone());
+
+        assert_eq!(mirror.vertices().len(), 4);
+        assert_eq!(mirror.edges().len(), 4);
+        assert_eq!(Polygon::from(&mirror), polygon);
+    }
+}
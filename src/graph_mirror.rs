@@ -0,0 +1,235 @@
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{
+    error::MirrorError,
+    mirror::{
+        degenerate, householder_matrix, is_valid_distance, sample_parameters, sort_by_distance,
+        Mirror, Reflections,
+    },
+    ray::Ray,
+    DIM,
+};
+
+/// Number of intervals of the domain used to bracket the intersections with a ray.
+const SAMPLES: usize = 256;
+/// Maximum number of bisections spent refining a single bracket.
+const MAX_ITERATIONS: usize = 50;
+/// Step of the central difference used when no derivative is given.
+const DERIVATIVE_STEP: f32 = 1e-3;
+
+/// A real function of x, or its derivative.
+pub type GraphFunction = dyn Fn(f32) -> f32;
+
+/// The graph of `y = f(x)` over a range of x, only meaningful in 2D.
+pub struct GraphMirror {
+    function: Box<GraphFunction>,
+    derivative: Box<GraphFunction>,
+    start: f32,
+    end: f32,
+    reflectivity: f32,
+}
+
+impl Mirror for GraphMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        sort_by_distance(
+            self.intersections(&ray)
+                .into_iter()
+                .filter_map(|x| {
+                    let distance = (self.point_at(x) - ray.origin).dot(&ray.direction);
+
+                    is_valid_distance(distance)
+                        .then(|| (distance, householder_matrix(&self.normal_at(x))))
+                })
+                .collect(),
+        )
+    }
+    fn get_type(&self) -> String {
+        "graph".to_string()
+    }
+    /// Intersections are found by bisection.
+    fn is_analytic(&self) -> bool {
+        false
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        // the vertical distance, projected on the normal, is the distance to the tangent
+        (self.start..=self.end).contains(&point[0])
+            && (point[1] - (self.function)(point[0])).abs() * self.normal_at(point[0])[1]
+                <= tolerance
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        sample_parameters(n)
+            .into_iter()
+            .map(|t| self.point_at(self.start + t * (self.end - self.start)))
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    /// The range of x, as taken by [`GraphMirror::point_at`].
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        Some((self.start, self.end))
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        [self.start, self.end]
+            .iter()
+            .any(|x| (point - self.point_at(*x)).norm() <= tolerance)
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !(self.start.is_finite() && self.end.is_finite() && self.start < self.end) {
+            degenerate(self, "its domain is empty or infinite")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl GraphMirror {
+    /// The graph of `function` for x from `start` to `end`, with its exact `derivative`.
+    pub fn new(
+        function: impl Fn(f32) -> f32 + 'static,
+        derivative: impl Fn(f32) -> f32 + 'static,
+        start: f32,
+        end: f32,
+    ) -> Self {
+        Self {
+            function: Box::new(function),
+            derivative: Box::new(derivative),
+            start,
+            end,
+            reflectivity: 1.0,
+        }
+    }
+
+    /// Like [`GraphMirror::new`], differentiating `function` numerically.
+    pub fn numeric(function: impl Fn(f32) -> f32 + Clone + 'static, start: f32, end: f32) -> Self {
+        let f = function.clone();
+        let derivative = move |x: f32| {
+            (f(x + DERIVATIVE_STEP) - f(x - DERIVATIVE_STEP)) / (2.0 * DERIVATIVE_STEP)
+        };
+
+        Self::new(function, derivative, start, end)
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    pub fn point_at(&self, x: f32) -> Point<f32, DIM> {
+        let y = (self.function)(x);
+        Point::from(SVector::from_fn(|axis, _| match axis {
+            0 => x,
+            1 => y,
+            _ => 0.0,
+        }))
+    }
+
+    /// The upward normal of the graph at `x`.
+    pub fn normal_at(&self, x: f32) -> Unit<SVector<f32, DIM>> {
+        let slope = (self.derivative)(x);
+        Unit::new_normalize(SVector::from_fn(|axis, _| match axis {
+            0 => -slope,
+            1 => 1.0,
+            _ => 0.0,
+        }))
+    }
+
+    /// The x of every point where the graph crosses the line supporting `ray`. Crossings
+    /// closer than the sampling step of the domain may be missed, as may tangent rays.
+    pub fn intersections(&self, ray: &Ray) -> Vec<f32> {
+        // signed distance from the graph's point to the ray's line, changing sign at crossings
+        let side = |x: f32| {
+            let offset = self.point_at(x) - ray.origin;
+            ray.direction[0] * offset[1] - ray.direction[1] * offset[0]
+        };
+
+        let xs = sample_parameters(SAMPLES + 1)
+            .into_iter()
+            .map(|t| self.start + t * (self.end - self.start))
+            .collect::<Vec<_>>();
+        let mut roots = vec![];
+
+        for pair in xs.windows(2) {
+            let (mut low, mut high) = (pair[0], pair[1]);
+            let (mut side_low, side_high) = (side(low), side(high));
+
+            // exact zeros are pushed as the start of their interval
+            if side_low == 0.0 {
+                roots.push(low);
+                continue;
+            }
+            if side_low * side_high >= 0.0 {
+                continue;
+            }
+
+            for _ in 0..MAX_ITERATIONS {
+                let middle = (low + high) / 2.0;
+                if middle <= low || middle >= high {
+                    break;
+                }
+
+                let side_middle = side(middle);
+                if side_middle * side_low > 0.0 {
+                    (low, side_low) = (middle, side_middle);
+                } else {
+                    high = middle;
+                }
+            }
+
+            roots.push((low + high) / 2.0);
+        }
+
+        // the last sample isn't the start of any interval
+        if side(self.end) == 0.0 {
+            roots.push(self.end);
+        }
+
+        roots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::least_squares_focus, ray::ray_from};
+
+    #[test]
+    fn test_parabola_focus() {
+        // y = x^2 focuses vertical rays at (0, 1/4)
+        let exact = GraphMirror::new(|x| x * x, |x| 2.0 * x, -1.0, 1.0);
+        let numeric = GraphMirror::numeric(|x| x * x, -1.0, 1.0);
+
+        for mirror in [exact, numeric] {
+            let reflected = (-8..=8)
+                .map(|i| {
+                    let ray = ray_from([i as f32 / 10.0 + 0.01, 2.0], [0.0, -1.0]);
+                    mirror.reflect_ray(&ray).unwrap()
+                })
+                .collect::<Vec<_>>();
+
+            let focus = least_squares_focus(&reflected).unwrap();
+            assert!((focus - Point::from([0.0, 0.25])).norm() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_intersections() {
+        let mirror = GraphMirror::new(|x| x * x, |x| 2.0 * x, -2.0, 2.0);
+
+        // the line y = 1 crosses the parabola twice
+        let ray = ray_from([-3.0, 1.0], [1.0, 0.0]);
+        let reflections = mirror.reflect(ray);
+        assert_eq!(reflections.len(), 2);
+        assert!((reflections[0].0 - 2.0).abs() < 1e-5);
+        assert!((reflections[1].0 - 4.0).abs() < 1e-5);
+
+        assert!(mirror
+            .reflect(ray_from([-3.0, -1.0], [1.0, 0.0]))
+            .is_empty());
+        assert!(mirror.contains_point(&[1.0, 1.0].into(), 1e-5));
+        assert!(!mirror.contains_point(&[1.0, 1.1].into(), 1e-5));
+        assert!(!mirror.contains_point(&[3.0, 9.0].into(), 1e-5));
+    }
+}
@@ -0,0 +1,145 @@
+use nalgebra::{Point, Unit};
+
+use crate::{
+    error::MirrorError,
+    mirror::{degenerate, householder_matrix, Mirror, PlaneMirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// A reflective diffraction grating, only meaningful in 2D: a plane whose grooves send each
+/// wavelength in a different direction, following the grating equation
+/// `sin θm = sin θi + m λ / period` for the diffraction order `m`. Positive orders are sent
+/// towards the second point of the plane.
+#[derive(Clone, Copy)]
+pub struct GratingMirror {
+    plane: PlaneMirror,
+    /// Distance between two grooves, in nanometers like wavelengths.
+    period: f32,
+    order: i32,
+}
+
+impl Mirror for GratingMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.plane
+            .reflect(ray)
+            .into_iter()
+            .filter_map(|(distance, matrix)| {
+                let normal = self.plane.normal().into_inner();
+                let points = self.plane.points();
+                let tangent = (points[1] - points[0]).normalize();
+
+                let direction = ray.direction.into_inner();
+                let along =
+                    direction.dot(&tangent) + self.order as f32 * ray.wavelength / self.period;
+                // this order doesn't exist for this wavelength and angle of incidence
+                if along.abs() > 1.0 {
+                    return None;
+                }
+
+                let across = -direction.dot(&normal).signum() * (1.0 - along * along).sqrt();
+                let outgoing = along * tangent + across * normal;
+
+                // any change of direction is a reflection across the plane orthogonal to it
+                Some(
+                    Unit::try_new(direction - outgoing, f32::EPSILON)
+                        .map_or((distance, matrix), |normal| {
+                            (distance, householder_matrix(&normal))
+                        }),
+                )
+            })
+            .collect()
+    }
+    fn get_type(&self) -> String {
+        "grating".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn is_flat(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.plane.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.plane.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.plane.reflectivity()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !(self.period.is_finite() && self.period > 0.0) {
+            degenerate(self, "its period isn't strictly positive")
+        } else {
+            self.plane.validate()
+        }
+    }
+}
+
+impl GratingMirror {
+    pub fn new(plane: PlaneMirror, period: f32, order: i32) -> Self {
+        Self {
+            plane,
+            period,
+            order,
+        }
+    }
+
+    pub fn period(&self) -> f32 {
+        self.period
+    }
+
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::ray_from;
+    use nalgebra::SVector;
+
+    fn grating(order: i32) -> GratingMirror {
+        GratingMirror::new(
+            PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]),
+            1000.0,
+            order,
+        )
+    }
+
+    fn outgoing(mirror: &GratingMirror, ray: Ray) -> SVector<f32, DIM> {
+        let (_, matrix) = mirror.reflect(ray)[0];
+        matrix.into_inner() * ray.direction.into_inner()
+    }
+
+    #[test]
+    fn test_zeroth_order_is_specular() {
+        let ray = ray_from([-1.0, 1.0], [1.0, -1.0]).with_wavelength(633.0);
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+
+        let (_, expected) = plane.reflect(ray)[0];
+
+        assert!(
+            (outgoing(&grating(0), ray) - expected.into_inner() * ray.direction.into_inner())
+                .norm()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_grating_equation() {
+        let ray = ray_from([0.0, 1.0], [0.0, -1.0]).with_wavelength(500.0);
+
+        let direction = outgoing(&grating(1), ray);
+
+        // at normal incidence, sin θ1 = λ / period
+        assert!((direction.norm() - 1.0).abs() < 1e-6);
+        assert!((direction[0] - 0.5).abs() < 1e-6);
+        assert!(direction[1] > 0.0);
+
+        // at normal incidence, there is no first order beyond λ = period
+        assert!(grating(1).reflect(ray.with_wavelength(2500.0)).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Gradient-index (GRIN) media, where rays bend along curved paths.
+
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{ray::Ray, DIM};
+
+/// Step of the central differences used when no gradient is given.
+const GRADIENT_STEP: f32 = 1e-3;
+/// Length of the integration steps along the path, unless set otherwise.
+const DEFAULT_STEP: f32 = 1e-2;
+
+/// The refractive index at a point, or its gradient.
+pub type IndexFunction = dyn Fn(&Point<f32, DIM>) -> f32;
+pub type GradientFunction = dyn Fn(&Point<f32, DIM>) -> SVector<f32, DIM>;
+
+/// A medium whose refractive index depends on the position, filling the space between the
+/// mirrors of a [`crate::simulation::Simulation`].
+///
+/// Rays follow the ray equation `d/ds (n dr/ds) = ∇n`, integrated with fixed steps of
+/// runge-kutta, so their path is a polyline approximating the curve.
+pub struct GrinMedium {
+    index: Box<IndexFunction>,
+    gradient: Box<GradientFunction>,
+    /// Length of each integration step.
+    step: f32,
+    /// How far a ray goes without hitting a mirror before it is considered escaped.
+    max_length: f32,
+}
+
+impl GrinMedium {
+    pub fn new(
+        index: impl Fn(&Point<f32, DIM>) -> f32 + 'static,
+        gradient: impl Fn(&Point<f32, DIM>) -> SVector<f32, DIM> + 'static,
+        max_length: f32,
+    ) -> Self {
+        Self {
+            index: Box::new(index),
+            gradient: Box::new(gradient),
+            step: DEFAULT_STEP,
+            max_length,
+        }
+    }
+
+    /// Like [`GrinMedium::new`], differentiating `index` numerically.
+    pub fn numeric(
+        index: impl Fn(&Point<f32, DIM>) -> f32 + Clone + 'static,
+        max_length: f32,
+    ) -> Self {
+        let n = index.clone();
+        let gradient = move |point: &Point<f32, DIM>| {
+            SVector::from_fn(|axis, _| {
+                let mut offset = SVector::<f32, DIM>::zeros();
+                offset[axis] = GRADIENT_STEP;
+                (n(&(point + offset)) - n(&(point - offset))) / (2.0 * GRADIENT_STEP)
+            })
+        };
+
+        Self::new(index, gradient, max_length)
+    }
+
+    pub fn with_step(self, step: f32) -> Self {
+        Self { step, ..self }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn max_length(&self) -> f32 {
+        self.max_length
+    }
+
+    pub fn index_at(&self, point: &Point<f32, DIM>) -> f32 {
+        (self.index)(point)
+    }
+
+    /// The ray after going one step along its path, starting from its new position and
+    /// pointing along the path there.
+    pub fn advance(&self, ray: &Ray) -> Ray {
+        // the state is the position and the optical direction n * dr/ds
+        let derivative = |position: &Point<f32, DIM>, optical: &SVector<f32, DIM>| {
+            (optical / (self.index)(position), (self.gradient)(position))
+        };
+        let h = self.step;
+
+        let position = ray.origin;
+        let optical = self.index_at(&position) * ray.direction.into_inner();
+        let (k1r, k1v) = derivative(&position, &optical);
+        let (k2r, k2v) = derivative(&(position + h / 2.0 * k1r), &(optical + h / 2.0 * k1v));
+        let (k3r, k3v) = derivative(&(position + h / 2.0 * k2r), &(optical + h / 2.0 * k2v));
+        let (k4r, k4v) = derivative(&(position + h * k3r), &(optical + h * k3v));
+
+        let optical = optical + h / 6.0 * (k1v + 2.0 * k2v + 2.0 * k3v + k4v);
+        let origin = position + h / 6.0 * (k1r + 2.0 * k2r + 2.0 * k3r + k4r);
+        Ray {
+            origin,
+            direction: Unit::try_new(optical, f32::EPSILON).unwrap_or(ray.direction),
+            // light slows down by the index, averaged over the step
+            time: ray.time + h * (self.index_at(&position) + self.index_at(&origin)) / 2.0,
+            ..*ray
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::ray_from;
+
+    #[test]
+    fn test_uniform_medium_is_straight() {
+        let medium = GrinMedium::numeric(|_| 1.5, 10.0);
+
+        let ray = medium.advance(&ray_from([1.0, 2.0], [3.0, 4.0]));
+
+        assert!((ray.origin - Point::from([1.006, 2.008])).norm() < 1e-6);
+        assert!((ray.direction.into_inner() - SVector::from([0.6, 0.8])).norm() < 1e-6);
+    }
+}
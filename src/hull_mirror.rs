@@ -0,0 +1,180 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    mirror::{degenerate, Mirror, Reflections},
+    polygon_mirror::PolygonMirror,
+    ray::Ray,
+    DIM,
+};
+
+/// The convex hull of a set of points, reflecting on its boundary, a quick way to close a
+/// region. The hull is computed once, when the mirror is built. Only meaningful in 2D.
+pub struct HullMirror {
+    points: Vec<Point<f32, DIM>>,
+    hull: PolygonMirror,
+}
+
+impl Mirror for HullMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.hull.reflect(ray)
+    }
+    fn get_type(&self) -> String {
+        "hull".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.hull.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.hull.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.hull.reflectivity()
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.hull.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.hull.vertices().len() < 3 {
+            degenerate(self, "its points are all on the same line")
+        } else {
+            self.hull.validate()
+        }
+    }
+}
+
+impl HullMirror {
+    pub fn new(points: Vec<Point<f32, DIM>>) -> Self {
+        let hull = PolygonMirror::new(convex_hull(&points));
+        Self { points, hull }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            hull: self.hull.with_reflectivity(reflectivity),
+            ..self
+        }
+    }
+
+    /// The points the hull was built from.
+    pub fn points(&self) -> &[Point<f32, DIM>] {
+        &self.points
+    }
+
+    /// The vertices of the hull, counterclockwise.
+    pub fn vertices(&self) -> &[Point<f32, DIM>] {
+        self.hull.vertices()
+    }
+}
+
+/// The vertices of the convex hull of `points`, counterclockwise from the lowest leftmost one,
+/// found with Andrew's monotone chain. Points in the middle of an edge are left out.
+fn convex_hull(points: &[Point<f32, DIM>]) -> Vec<Point<f32, DIM>> {
+    let mut sorted = points
+        .iter()
+        .filter(|point| point.iter().all(|c| c.is_finite()))
+        .copied()
+        .collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // positive when o, a, b turn counterclockwise
+    let cross = |o: &Point<f32, DIM>, a: &Point<f32, DIM>, b: &Point<f32, DIM>| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+
+    let mut hull: Vec<Point<f32, DIM>> = vec![];
+    // the lower chain left to right, then the upper one back, each dropping the last point
+    // while it doesn't make a left turn
+    for pass in [
+        &sorted[..],
+        &sorted.iter().rev().copied().collect::<Vec<_>>()[..],
+    ] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        // the last point of a chain starts the next one
+        hull.pop();
+    }
+
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mirror::test_helpers::assert_reflects_like, ray::ray_from};
+
+    #[test]
+    fn test_convex_hull() {
+        let points = [
+            [0.5, 0.5],
+            [1.0, 1.0],
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [0.5, 0.0],
+            [0.0, 1.0],
+            [0.2, 0.7],
+            [1.0, 0.0],
+        ]
+        .map(Point::from);
+
+        assert_eq!(
+            convex_hull(&points),
+            [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]].map(Point::from)
+        );
+        assert_eq!(convex_hull(&points[..2]).len(), 2);
+    }
+
+    #[test]
+    fn test_reflects_like_the_square() {
+        let corners = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]].map(Point::from);
+        let square = PolygonMirror::new(corners.to_vec());
+        // the corners in another order, with points inside
+        let hull = HullMirror::new(vec![
+            corners[2],
+            [0.5, 0.5].into(),
+            corners[0],
+            corners[3],
+            [0.3, 0.8].into(),
+            corners[1],
+        ]);
+
+        assert!(hull.validate().is_ok());
+        assert_reflects_like(
+            &hull,
+            &square,
+            &[
+                ray_from([0.5, 0.5], [1.0, 0.3]),
+                ray_from([-1.0, 0.4], [1.0, 0.0]),
+                ray_from([0.2, 2.0], [0.1, -1.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let line = HullMirror::new(vec![
+            [0.0, 0.0].into(),
+            [1.0, 1.0].into(),
+            [2.0, 2.0].into(),
+        ]);
+        assert!(line.validate().is_err());
+
+        let empty = HullMirror::new(vec![]);
+        assert!(empty.validate().is_err());
+        assert!(empty.sample_surface(4).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use nalgebra::{Point, SMatrix, SVector, Unit};
+
+use crate::{math::normal_from_reflection, ray::Ray, DIM};
+
+/// Where a ray hits a mirror, and how it is reflected there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    /// Distance travelled along the ray to reach the mirror.
+    pub distance: f32,
+    pub point: Point<f32, DIM>,
+    pub reflection_matrix: Unit<SMatrix<f32, DIM, DIM>>,
+    /// Whether the mirror was hit right at its edge, see [`crate::mirror::Mirror::is_on_boundary`].
+    /// Only filled by [`crate::scene::Scene`], which knows which mirror was hit.
+    pub on_boundary: bool,
+    /// Where the mirror was hit in its own frame, see [`crate::mirror::Mirror::local_point`].
+    /// Only filled by [`crate::scene::Scene`], like `on_boundary`.
+    pub local_point: Option<Point<f32, DIM>>,
+    /// Fraction of the light kept by the reflection, see
+    /// [`crate::mirror::Mirror::reflectivity_at`]. Only filled by [`crate::scene::Scene`], like
+    /// `on_boundary`, and 1 otherwise.
+    pub reflectivity: f32,
+}
+
+impl Intersection {
+    /// Builds the intersection of `ray` from one of the results of
+    /// [`crate::mirror::Mirror::reflect`].
+    pub fn new(ray: &Ray, distance: f32, reflection_matrix: Unit<SMatrix<f32, DIM, DIM>>) -> Self {
+        Self {
+            distance,
+            point: ray.origin + distance * ray.direction.into_inner(),
+            reflection_matrix,
+            on_boundary: false,
+            local_point: None,
+            reflectivity: 1.0,
+        }
+    }
+
+    pub fn reflect_direction(
+        &self,
+        direction: &Unit<SVector<f32, DIM>>,
+    ) -> Unit<SVector<f32, DIM>> {
+        Unit::new_normalize(self.reflection_matrix.into_inner() * direction.into_inner())
+    }
+
+    /// The squared sine of the angle between `direction` and the normal of the mirror.
+    pub fn sin2_incidence(&self, direction: &Unit<SVector<f32, DIM>>) -> f32 {
+        // with H = I - 2nn^T, d.Hd = 1 - 2(n.d)^2 = 1 - 2cos^2
+        let reflected = self.reflection_matrix.into_inner() * direction.into_inner();
+        ((1.0 + direction.dot(&reflected)) / 2.0).clamp(0.0, 1.0)
+    }
+
+    /// The normal of the mirror oriented against `direction`, so it points towards the side
+    /// the ray comes from. At grazing incidence, both orientations are equally valid.
+    pub fn facing_normal(&self, direction: &Unit<SVector<f32, DIM>>) -> Unit<SVector<f32, DIM>> {
+        // Hd - d = -2(n.d)n is a positive multiple of whichever of n and -n faces the ray
+        let reflected = self.reflection_matrix.into_inner() * direction.into_inner();
+        Unit::try_new(reflected - direction.into_inner(), f32::EPSILON)
+            .unwrap_or_else(|| normal_from_reflection(&self.reflection_matrix))
+    }
+
+    /// The ray leaving the mirror after `ray` was reflected, later by the distance travelled.
+    pub fn reflected_ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            origin: self.point,
+            direction: self.reflect_direction(&ray.direction),
+            time: ray.time + self.distance,
+            ..*ray
+        }
+    }
+}
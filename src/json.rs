@@ -0,0 +1,145 @@
+//! Helpers shared by the `from_json` and `to_json` implementations of the mirrors.
+
+use nalgebra::Point;
+use serde_json::Value;
+
+use crate::{error::MirrorError, DIM};
+
+fn invalid(field: &str, expected: &'static str) -> MirrorError {
+    MirrorError::InvalidField {
+        field: field.to_string(),
+        expected,
+    }
+}
+
+pub(crate) fn number(json: &Value, field: &str) -> Result<f32, MirrorError> {
+    json[field]
+        .as_f64()
+        .map(|value| value as f32)
+        .ok_or_else(|| invalid(field, "a number"))
+}
+
+pub(crate) fn array<'a>(json: &'a Value, field: &str) -> Result<&'a Vec<Value>, MirrorError> {
+    json[field]
+        .as_array()
+        .ok_or_else(|| invalid(field, "an array"))
+}
+
+/// Whether the points of `json` are in homogeneous coordinates, as set by its optional
+/// `"homogeneous"` flag: they then have an extra w coordinate which the others are divided by.
+fn homogeneous(json: &Value) -> Result<bool, MirrorError> {
+    match &json["homogeneous"] {
+        Value::Null => Ok(false),
+        value => value
+            .as_bool()
+            .ok_or_else(|| invalid("homogeneous", "a boolean")),
+    }
+}
+
+fn parse_point(
+    value: &Value,
+    field: &str,
+    index: Option<usize>,
+    homogeneous: bool,
+) -> Result<Point<f32, DIM>, MirrorError> {
+    let coordinates = value
+        .as_array()
+        .ok_or_else(|| invalid(field, "an array of points"))?
+        .iter()
+        .map(|coordinate| coordinate.as_f64().map(|c| c as f32))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid(field, "made of numbers"))?;
+
+    let expected = if homogeneous { DIM + 1 } else { DIM };
+    if coordinates.len() != expected {
+        return Err(MirrorError::DimensionMismatch {
+            field: field.to_string(),
+            index,
+            found: coordinates.len(),
+            expected,
+        });
+    }
+
+    if homogeneous {
+        let w = coordinates[DIM];
+        if w == 0.0 {
+            return Err(invalid(field, "points with a nonzero w"));
+        }
+        return Ok(Point::from_slice(&coordinates[..DIM]) / w);
+    }
+
+    Ok(Point::from_slice(&coordinates))
+}
+
+pub(crate) fn point(json: &Value, field: &str) -> Result<Point<f32, DIM>, MirrorError> {
+    parse_point(&json[field], field, None, homogeneous(json)?)
+}
+
+pub(crate) fn points(json: &Value, field: &str) -> Result<Vec<Point<f32, DIM>>, MirrorError> {
+    let homogeneous = homogeneous(json)?;
+
+    array(json, field)?
+        .iter()
+        .enumerate()
+        .map(|(index, value)| parse_point(value, field, Some(index), homogeneous))
+        .collect()
+}
+
+pub(crate) fn from_point(point: &Point<f32, DIM>) -> Value {
+    point.iter().copied().collect()
+}
+
+pub(crate) fn from_points<'a>(points: impl IntoIterator<Item = &'a Point<f32, DIM>>) -> Value {
+    points.into_iter().map(from_point).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_homogeneous_points() {
+        let json = json!({
+            "homogeneous": true,
+            "center": [2.0, 4.0, 2.0],
+            "points": [[2.0, 4.0, 2.0], [1.0, 1.0, 0.5]],
+        });
+
+        assert_eq!(point(&json, "center").unwrap(), Point::from([1.0, 2.0]));
+        assert_eq!(
+            points(&json, "points").unwrap(),
+            vec![Point::from([1.0, 2.0]), Point::from([2.0, 2.0])]
+        );
+
+        assert!(matches!(
+            point(
+                &json!({ "homogeneous": true, "center": [1.0, 2.0] }),
+                "center"
+            ),
+            Err(MirrorError::DimensionMismatch {
+                index: None,
+                expected: 3,
+                ..
+            })
+        ));
+        assert!(matches!(
+            points(&json!({ "points": [[1.0, 2.0], [1.0]] }), "points"),
+            Err(MirrorError::DimensionMismatch {
+                index: Some(1),
+                found: 1,
+                ..
+            })
+        ));
+        assert!(point(
+            &json!({ "homogeneous": true, "center": [1.0, 2.0, 0.0] }),
+            "center"
+        )
+        .is_err());
+        assert!(point(&json!({ "homogeneous": 1, "center": [1.0, 2.0] }), "center").is_err());
+        assert_eq!(
+            point(&json!({ "center": [2.0, 4.0] }), "center").unwrap(),
+            Point::from([2.0, 4.0])
+        );
+    }
+}
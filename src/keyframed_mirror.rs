@@ -0,0 +1,258 @@
+use nalgebra::{Point, SMatrix, Unit};
+use serde_json::Value;
+
+use crate::{
+    error::MirrorError,
+    mirror::{degenerate, mirror_from_json, sort_by_distance, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Iterations at most to find where a ray meets the moving mirror.
+const MAX_ARRIVAL_ITERATIONS: usize = 32;
+/// The hit on arrival is found once an iteration moves it by less than this, relative to its
+/// distance.
+const ARRIVAL_TOLERANCE: f32 = 1e-6;
+
+/// A mirror whose shape is animated by keyframes, each giving the mirror at some time.
+///
+/// Rays are reflected by the mirror as it is when they reach it, [`Ray::time`] plus the
+/// distance travelled: each hit on the mirror at the time the ray leaves is moved to where the
+/// mirror is on arrival, by fixed-point iteration.
+///
+/// Between two keyframes of the same type, their geometry (e.g. the control points of a
+/// bezier curve) is interpolated linearly, through their json form. Keyframes which can't be
+/// interpolated, because their types or shapes differ, hold until the next one. Before the
+/// first keyframe and after the last one, the mirror stays still.
+///
+/// Everything but the reflections is taken from the first keyframe.
+pub struct KeyframedMirror {
+    /// Sorted by time.
+    keyframes: Vec<(f32, Box<dyn Mirror>)>,
+}
+
+impl Mirror for KeyframedMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        if self.keyframes.is_empty() {
+            return Reflections::new();
+        }
+
+        sort_by_distance(
+            self.with_mirror_at(ray.time, |mirror| mirror.reflect(ray))
+                .into_iter()
+                .filter_map(|hit| self.hit_on_arrival(ray, hit))
+                .collect(),
+        )
+    }
+    fn get_type(&self) -> String {
+        "keyframed".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.keyframes
+            .iter()
+            .all(|(_, mirror)| mirror.is_analytic())
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.first()
+            .is_some_and(|mirror| mirror.contains_point(point, tolerance))
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.first()
+            .map(|mirror| mirror.sample_surface(n))
+            .unwrap_or_default()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.first().map_or(1.0, |mirror| mirror.reflectivity())
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.keyframes.is_empty() {
+            return degenerate(self, "it has no keyframes");
+        }
+        if !self.keyframes.iter().all(|(time, _)| time.is_finite()) {
+            return degenerate(self, "the time of a keyframe isn't finite");
+        }
+
+        self.keyframes
+            .iter()
+            .try_for_each(|(_, mirror)| mirror.validate())
+    }
+}
+
+impl KeyframedMirror {
+    /// `keyframes` are `(time, mirror)` pairs, in any order.
+    pub fn new(mut keyframes: Vec<(f32, Box<dyn Mirror>)>) -> Self {
+        keyframes.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self { keyframes }
+    }
+
+    pub fn keyframes(&self) -> &[(f32, Box<dyn Mirror>)] {
+        &self.keyframes
+    }
+
+    /// Calls `f` with the mirror at `time`. At the time of a keyframe, it is the keyframe
+    /// itself, in between it is built anew from the interpolated geometry.
+    ///
+    /// # Panics
+    ///
+    /// If there are no keyframes.
+    pub fn with_mirror_at<R>(&self, time: f32, f: impl FnOnce(&dyn Mirror) -> R) -> R {
+        let next = self.keyframes.partition_point(|(t, _)| *t <= time);
+        let (start_time, start) = &self.keyframes[next.saturating_sub(1)];
+        let Some((end_time, end)) = self.keyframes.get(next).filter(|_| next > 0) else {
+            return f(&**start);
+        };
+        if time == *start_time {
+            return f(&**start);
+        }
+
+        let t = (time - start_time) / (end_time - start_time);
+        let interpolated = start
+            .to_json()
+            .ok()
+            .zip(end.to_json().ok())
+            .and_then(|(a, b)| interpolate(&a, &b, t as f64))
+            .and_then(|json| mirror_from_json(&json).ok());
+
+        match interpolated {
+            Some(mirror) => f(&*mirror),
+            None => f(&**start),
+        }
+    }
+
+    /// Starting from `hit`, the hit of `ray` on the mirror as it is when the ray gets there.
+    /// Each step reflects the ray on the mirror at the time of arrival of the previous hit,
+    /// keeping the nearest one, which converges as long as the mirror moves slower than
+    /// light. `None` if the mirror moved out of the way.
+    fn hit_on_arrival(
+        &self,
+        ray: Ray,
+        mut hit: (f32, Unit<SMatrix<f32, DIM, DIM>>),
+    ) -> Option<(f32, Unit<SMatrix<f32, DIM, DIM>>)> {
+        for _ in 0..MAX_ARRIVAL_ITERATIONS {
+            let next = self.with_mirror_at(ray.time + hit.0, |mirror| {
+                mirror
+                    .reflect(ray)
+                    .into_iter()
+                    .min_by(|(a, _), (b, _)| (a - hit.0).abs().total_cmp(&(b - hit.0).abs()))
+            })?;
+            let step = (next.0 - hit.0).abs();
+            hit = next;
+
+            if step <= ARRIVAL_TOLERANCE * hit.0.abs().max(1.0) {
+                break;
+            }
+        }
+
+        Some(hit)
+    }
+
+    fn first(&self) -> Option<&dyn Mirror> {
+        self.keyframes.first().map(|(_, mirror)| &**mirror)
+    }
+}
+
+/// Every number of `a` moved by `t` towards the one at the same place in `b`, or `None` if
+/// they don't have the same structure.
+fn interpolate(a: &Value, b: &Value, t: f64) -> Option<Value> {
+    Some(match (a, b) {
+        (Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (a.as_f64()?, b.as_f64()?);
+            Value::from(a + t * (b - a))
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => Value::Array(
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| interpolate(a, b, t))
+                .collect::<Option<_>>()?,
+        ),
+        (Value::Object(a), Value::Object(b)) if a.len() == b.len() => Value::Object(
+            a.iter()
+                .map(|(key, a)| Some((key.clone(), interpolate(a, b.get(key)?, t)?)))
+                .collect::<Option<_>>()?,
+        ),
+        _ if a == b => a.clone(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bezier_mirror::BezierMirror,
+        mirror::{PlaneMirror, SphereMirror},
+        ray::ray_from,
+        scene::Scene,
+        simulation::Simulation,
+    };
+
+    fn bezier(points: [[f32; 2]; 3]) -> Box<dyn Mirror> {
+        Box::new(BezierMirror::new(
+            points.into_iter().map(Point::from).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_interpolates_between_keyframes() {
+        let keyframed = KeyframedMirror::new(vec![
+            (2.0, bezier([[0.0, 1.0], [1.0, 3.0], [2.0, 1.0]])),
+            (0.0, bezier([[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]])),
+        ]);
+        let json_at = |time| keyframed.with_mirror_at(time, |mirror| mirror.to_json().unwrap());
+
+        for (time, mirror) in keyframed.keyframes() {
+            assert_eq!(json_at(*time), mirror.to_json().unwrap());
+        }
+        assert_eq!(
+            json_at(1.0),
+            bezier([[0.0, 0.5], [1.0, 1.5], [2.0, 0.5]])
+                .to_json()
+                .unwrap()
+        );
+        assert_eq!(json_at(-1.0), json_at(0.0));
+        assert_eq!(json_at(3.0), json_at(2.0));
+    }
+
+    #[test]
+    fn test_mismatched_keyframes_hold() {
+        let keyframed = KeyframedMirror::new(vec![
+            (0.0, bezier([[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]])),
+            (1.0, Box::new(SphereMirror::new([0.0, 0.0].into(), 1.0))),
+        ]);
+
+        assert_eq!(
+            keyframed.with_mirror_at(0.5, |mirror| mirror.get_type()),
+            "bezier"
+        );
+    }
+
+    #[test]
+    fn test_reflects_at_arrival_time() {
+        // a floor going down from y = 0 to y = -1 over 10 units of time
+        let plane = |y: f32| -> Box<dyn Mirror> {
+            Box::new(PlaneMirror::new([[0.0, y].into(), [1.0, y].into()]))
+        };
+        let keyframed = KeyframedMirror::new(vec![(0.0, plane(0.0)), (10.0, plane(-1.0))]);
+        let ray = ray_from([0.0, 1.0], [0.0, -1.0]).with_time(5.0);
+
+        // when the ray leaves, the floor is at y = -0.5, but it reaches y = -2 / 3 by the time
+        // the ray gets there
+        let on_departure = keyframed.with_mirror_at(5.0, |mirror| mirror.reflect(ray));
+        assert!((on_departure[0].0 - 1.5).abs() < 1e-5);
+
+        let result = Simulation::new(Scene::new(vec![Box::new(keyframed)])).trace(ray, 1);
+        assert!((result.path[1] - Point::from([0.0, -2.0 / 3.0])).norm() < 1e-5);
+        assert!((result.last_ray.time - 20.0 / 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(KeyframedMirror::new(vec![]).validate().is_err());
+        assert!(KeyframedMirror::new(vec![(
+            f32::NAN,
+            bezier([[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]])
+        )])
+        .validate()
+        .is_err());
+    }
+}
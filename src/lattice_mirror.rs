@@ -0,0 +1,243 @@
+use nalgebra::{Point, SVector};
+
+use crate::{
+    error::MirrorError,
+    material::Polarization,
+    math::{to_local, translation},
+    mirror::{degenerate, sort_by_distance, Mirror, Reflections},
+    ray::Ray,
+    scene::tolerance,
+    DIM,
+};
+
+/// A base mirror repeated on a regular grid, without storing every copy.
+pub struct LatticeMirror {
+    base: Box<dyn Mirror>,
+    /// Offset between two neighbouring cells along each axis.
+    spacing: SVector<f32, DIM>,
+    /// Number of cells along each axis.
+    counts: [usize; DIM],
+}
+
+impl Mirror for LatticeMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        // a translation doesn't change distances nor directions, so the base's
+        // reflections in the local frame are also valid in the scene's frame
+        sort_by_distance(
+            self.cell_offsets()
+                .flat_map(|offset| self.base.reflect(to_local(&ray, &translation(offset))))
+                .collect(),
+        )
+    }
+    fn get_type(&self) -> String {
+        "lattice".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.base.is_analytic()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.cell_offsets()
+            .any(|offset| self.base.contains_point(&(point - offset), tolerance))
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        let cells = self.counts.iter().product::<usize>().max(1);
+        let base_samples = self.base.sample_surface(n.div_ceil(cells));
+
+        self.cell_offsets()
+            .flat_map(|offset| base_samples.iter().map(move |sample| sample + offset))
+            .take(n)
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.base.reflectivity()
+    }
+    /// The reflectivity of the base mirror, where `point` is on the copy of a cell.
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.in_cell(point).map_or(self.reflectivity(), |in_cell| {
+            self.base.reflectivity_at(&in_cell)
+        })
+    }
+    /// Every cell is a copy of the same base mirror.
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.base
+            .polarized_reflectivity(polarization, sin2_incidence)
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.base.retardance(sin2_incidence)
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.cell_offsets()
+            .any(|offset| self.base.is_on_boundary(&(point - offset), tolerance))
+    }
+    /// In the frame of the cell whose copy of the base mirror `point` is on, then in the
+    /// base mirror's own frame if it has one.
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        let in_cell = self.in_cell(point)?;
+        self.base.local_point(&in_cell).or(Some(in_cell))
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        let (center, radius) = self.base.bounding_sphere()?;
+        // from the first cell to the last one
+        let extent = self.cell_offset(&self.counts.map(|count| count.saturating_sub(1)));
+        Some((center + extent / 2.0, radius + extent.norm() / 2.0))
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !self.spacing.iter().all(|x| x.is_finite()) {
+            degenerate(self, "its spacing isn't finite")
+        } else if self.counts.contains(&0) {
+            degenerate(self, "it has no cells")
+        } else {
+            self.base.validate()
+        }
+    }
+}
+
+impl LatticeMirror {
+    pub fn new(base: Box<dyn Mirror>, spacing: SVector<f32, DIM>, counts: [usize; DIM]) -> Self {
+        Self {
+            base,
+            spacing,
+            counts,
+        }
+    }
+
+    /// Index of the cell whose copy of the base mirror `ray` hits first, if any.
+    pub fn hit_cell(&self, ray: &Ray) -> Option<[usize; DIM]> {
+        self.cell_indices()
+            .filter_map(|cell| {
+                self.base
+                    .reflect(to_local(ray, &translation(self.cell_offset(&cell))))
+                    .into_iter()
+                    .map(|(distance, _)| distance)
+                    .min_by(f32::total_cmp)
+                    .map(|distance| (distance, cell))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, cell)| cell)
+    }
+
+    /// `point` in the frame of the cell whose copy of the base mirror it is on, if any.
+    fn in_cell(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        let tolerance = tolerance(point, self.bounding_sphere());
+        self.cell_offsets()
+            .map(|offset| point - offset)
+            .find(|in_cell| self.base.contains_point(in_cell, tolerance))
+    }
+
+    fn cell_offset(&self, cell: &[usize; DIM]) -> SVector<f32, DIM> {
+        SVector::from_fn(|axis, _| cell[axis] as f32 * self.spacing[axis])
+    }
+
+    fn cell_offsets(&self) -> impl Iterator<Item = SVector<f32, DIM>> + '_ {
+        self.cell_indices().map(|cell| self.cell_offset(&cell))
+    }
+
+    fn cell_indices(&self) -> impl Iterator<Item = [usize; DIM]> + '_ {
+        let total = self.counts.iter().product::<usize>();
+
+        (0..total).map(|mut index| {
+            let mut cell = [0; DIM];
+            for (axis, count) in self.counts.iter().enumerate() {
+                cell[axis] = index % count;
+                index /= count;
+            }
+            cell
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circle_mirror::CircleMirror, mirror::PlaneMirror, polygon_mirror::PolygonMirror,
+        ray::ray_from, segment_mirror::SegmentMirror,
+    };
+
+    fn vertical_planes() -> LatticeMirror {
+        let base = PlaneMirror::new([[0.0, 0.0].into(), [0.0, 1.0].into()]);
+        LatticeMirror::new(Box::new(base), [1.0, 0.0].into(), [3, 1])
+    }
+
+    #[test]
+    fn test_reflect_correct_cell() {
+        let lattice = vertical_planes();
+        let ray = ray_from([1.5, 0.0], [1.0, 1.0]);
+
+        // only the plane at x = 2 is in front of the ray
+        let reflections = lattice.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+
+        let (distance, matrix) = reflections[0];
+        assert!((distance - 0.5 * 2.0_f32.sqrt()).abs() < 1e-6);
+        let reflected = matrix.into_inner() * ray.direction.into_inner();
+        assert!((reflected - SVector::from([-1.0, 1.0]).normalize()).norm() < 1e-6);
+
+        assert_eq!(lattice.hit_cell(&ray), Some([2, 0]));
+        assert_eq!(
+            lattice.hit_cell(&ray_from([0.5, 0.0], [1.0, 0.0])),
+            Some([1, 0])
+        );
+        assert_eq!(lattice.hit_cell(&ray_from([2.5, 0.0], [1.0, 0.0])), None);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let lattice = vertical_planes();
+
+        assert!(lattice.contains_point(&[1.0, 5.0].into(), 1e-6));
+        assert!(!lattice.contains_point(&[1.5, 5.0].into(), 1e-6));
+        assert!(!lattice.contains_point(&[3.0, 5.0].into(), 1e-6));
+    }
+
+    #[test]
+    fn test_is_on_boundary() {
+        let segments = LatticeMirror::new(
+            Box::new(SegmentMirror::new([0.0, 0.0].into(), [0.0, 1.0].into())),
+            [1.0, 0.0].into(),
+            [3, 1],
+        );
+
+        assert!(segments.is_on_boundary(&[2.0, 1.0].into(), 1e-4));
+        assert!(!segments.is_on_boundary(&[2.0, 0.5].into(), 1e-4));
+        assert!(!vertical_planes().is_on_boundary(&[2.0, 1.0].into(), 1e-4));
+    }
+
+    #[test]
+    fn test_reflectivity_at() {
+        // squares whose bottom edge reflects less
+        let square = PolygonMirror::new(
+            [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+                .map(Point::from)
+                .to_vec(),
+        )
+        .with_reflectivity(0.9)
+        .with_reflectivities(vec![0.5, 0.9, 0.9, 0.9]);
+        let lattice = LatticeMirror::new(Box::new(square), [2.0, 0.0].into(), [3, 1]);
+
+        assert_eq!(lattice.reflectivity(), 0.9);
+        assert_eq!(lattice.reflectivity_at(&[4.5, 0.0].into()), 0.5);
+        assert_eq!(lattice.reflectivity_at(&[4.5, 1.0].into()), 0.9);
+    }
+
+    #[test]
+    fn test_local_point() {
+        let lattice = vertical_planes();
+        assert_eq!(
+            lattice.local_point(&[2.0, 0.5].into()),
+            Some([0.0, 0.5].into())
+        );
+
+        // circles of radius 0.5 centered on the origin of each cell
+        let circles = LatticeMirror::new(
+            Box::new(CircleMirror::new(Point::origin(), 0.5)),
+            [2.0, 2.0].into(),
+            [2, 2],
+        );
+        assert_eq!(
+            circles.local_point(&[2.0, 2.5].into()),
+            Some([0.0, 1.0].into())
+        );
+        assert_eq!(circles.local_point(&[1.0, 1.0].into()), None);
+    }
+}
@@ -0,0 +1,44 @@
+pub mod analysis;
+pub mod angle_limited_mirror;
+pub mod anisotropic_mirror;
+pub mod bezier_mirror;
+pub mod bezier_patch_mirror;
+pub mod circle_mirror;
+pub mod deformable_mirror;
+pub mod error;
+pub mod export;
+pub mod figure_error_mirror;
+pub mod filter_mirror;
+#[cfg(feature = "geo")]
+pub mod geo;
+pub mod graph_mirror;
+pub mod grating_mirror;
+pub mod grin;
+pub mod hull_mirror;
+pub mod intersection;
+mod json;
+pub mod keyframed_mirror;
+pub mod lattice_mirror;
+pub mod masked_mirror;
+pub mod material;
+pub mod math;
+pub mod mirror;
+pub mod mls_mirror;
+pub mod opaque_mirror;
+pub mod optimize;
+pub mod polygon_mirror;
+pub mod profile_mirror;
+pub mod ray;
+pub mod reflection_model;
+pub mod resonator;
+pub mod scene;
+pub mod scenes;
+pub mod segment_mirror;
+pub mod simulation;
+pub mod slab_mirror;
+pub mod spline_mirror;
+pub mod study;
+#[cfg(feature = "toml")]
+mod toml;
+
+pub const DIM: usize = 2;
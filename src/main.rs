@@ -1,6 +1,3 @@
-mod bezier_mirror;
-mod mirror;
-mod ray;
 mod state;
 mod structs;
 
@@ -12,8 +9,6 @@ use winit::event_loop::EventLoop;
 use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::WindowBuilder;
 
-pub const DIM: usize = 2;
-
 fn main() {
     run().block_on();
 }
@@ -0,0 +1,126 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    material::Polarization,
+    mirror::{Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Decides whether a point of the surface of a [`MaskedMirror`] reflects.
+pub type Mask = dyn Fn(&Point<f32, DIM>) -> bool;
+
+/// Wraps a mirror so only the parts of its surface passing a mask reflect, like an apertured
+/// mirror or the shadow of a secondary. Rays hitting a masked out point go through it.
+pub struct MaskedMirror {
+    mirror: Box<dyn Mirror>,
+    mask: Box<Mask>,
+}
+
+impl Mirror for MaskedMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.mirror
+            .reflect(ray)
+            .into_iter()
+            .filter(|(distance, _)| {
+                (self.mask)(&(ray.origin + *distance * ray.direction.into_inner()))
+            })
+            .collect()
+    }
+    fn get_type(&self) -> String {
+        "masked".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.mirror.is_analytic()
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        self.mirror.is_opaque()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        (self.mask)(point) && self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror
+            .sample_surface(n)
+            .into_iter()
+            .filter(|point| (self.mask)(point))
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.mirror.reflectivity()
+    }
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.mirror.reflectivity_at(point)
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.mirror
+            .polarized_reflectivity(polarization, sin2_incidence)
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mirror.retardance(sin2_incidence)
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn max_incidence_angle(&self) -> Option<f32> {
+        self.mirror.max_incidence_angle()
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        self.mirror.validate()
+    }
+}
+
+impl MaskedMirror {
+    pub fn new(mirror: Box<dyn Mirror>, mask: impl Fn(&Point<f32, DIM>) -> bool + 'static) -> Self {
+        Self {
+            mirror,
+            mask: Box::new(mask),
+        }
+    }
+
+    /// Lets through the rays hitting within `radius` of `center`, like a central obstruction.
+    pub fn central_hole(mirror: Box<dyn Mirror>, center: Point<f32, DIM>, radius: f32) -> Self {
+        Self::new(mirror, move |point| (point - center).norm() > radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mirror::{test_helpers::in_front_of_plane, PlaneMirror},
+        ray::ray_from,
+    };
+
+    #[test]
+    fn test_central_hole() {
+        // a mirror at y = 1 with a hole around x = 0, in front of a plain mirror at y = 0
+        let simulation = in_front_of_plane(Box::new(MaskedMirror::central_hole(
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+            [0.0, 1.0].into(),
+            0.5,
+        )));
+
+        let central = simulation.trace(ray_from([0.0, 2.0], [0.0, -1.0]), 1);
+        let off_center = simulation.trace(ray_from([1.0, 2.0], [0.0, -1.0]), 1);
+
+        assert!((central.path[1] - Point::from([0.0, 0.0])).norm() < 1e-5);
+        assert!((off_center.path[1] - Point::from([1.0, 1.0])).norm() < 1e-5);
+    }
+}
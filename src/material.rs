@@ -0,0 +1,75 @@
+//! Reflectivities of common mirror coatings, so scenes can name a material instead of a number.
+
+use serde_json::Value;
+
+use crate::{error::MirrorError, json};
+
+/// Approximate reflectivities over the visible spectrum.
+pub const MATERIALS: &[(&str, f32)] = &[("aluminum", 0.92), ("silver", 0.97), ("gold", 0.94)];
+
+/// Polarization state of the light, relative to the plane of incidence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarization {
+    /// Electric field perpendicular to the plane of incidence.
+    S,
+    /// Electric field within the plane of incidence.
+    P,
+    /// An even mix of both.
+    Unpolarized,
+}
+
+pub fn reflectivity_of(material: &str) -> Result<f32, MirrorError> {
+    MATERIALS
+        .iter()
+        .find(|(name, _)| *name == material)
+        .map(|(_, reflectivity)| *reflectivity)
+        .ok_or_else(|| MirrorError::UnknownMaterial(material.to_string()))
+}
+
+/// Reads the reflectivity of a mirror, given either as `"material": "silver"` or as
+/// `"reflectivity": 0.9`. Mirrors reflect everything by default.
+pub(crate) fn reflectivity_from_json(json: &Value) -> Result<f32, MirrorError> {
+    if let Some(material) = json.get("material") {
+        let material = material.as_str().ok_or(MirrorError::InvalidField {
+            field: "material".to_string(),
+            expected: "a string",
+        })?;
+
+        return reflectivity_of(material);
+    }
+
+    if json.get("reflectivity").is_none() {
+        return Ok(1.0);
+    }
+
+    let reflectivity = json::number(json, "reflectivity")?;
+    if !(0.0..=1.0).contains(&reflectivity) {
+        return Err(MirrorError::InvalidField {
+            field: "reflectivity".to_string(),
+            expected: "between 0 and 1",
+        });
+    }
+
+    Ok(reflectivity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reflectivity_from_json() {
+        let silver = serde_json::json!({ "material": "silver" });
+        let number = serde_json::json!({ "reflectivity": 0.5 });
+        let unknown = serde_json::json!({ "material": "unobtainium" });
+
+        assert_eq!(reflectivity_from_json(&silver), Ok(0.97));
+        assert_eq!(reflectivity_from_json(&number), Ok(0.5));
+        assert_eq!(reflectivity_from_json(&serde_json::json!({})), Ok(1.0));
+        assert_eq!(
+            reflectivity_from_json(&unknown),
+            Err(MirrorError::UnknownMaterial("unobtainium".to_string()))
+        );
+        assert!(reflectivity_from_json(&serde_json::json!({ "reflectivity": 2.0 })).is_err());
+    }
+}
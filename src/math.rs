@@ -0,0 +1,315 @@
+//! Transforms between the scene's frame and the local frame of a mirror, so mirrors can be
+//! written around the origin and placed anywhere.
+
+use std::{
+    borrow::Cow,
+    ops::{Div, Mul, Sub},
+};
+
+use nalgebra::{Isometry, Point, Rotation, SMatrix, SVector, Translation, Unit};
+
+use crate::{ray::Ray, DIM};
+
+/// Placement of a local frame in the scene: it maps local coordinates to scene coordinates.
+pub type Frame = Isometry<f32, Rotation<f32, DIM>, DIM>;
+
+/// A frame only moved by `offset`, without any rotation.
+pub fn translation(offset: SVector<f32, DIM>) -> Frame {
+    Frame::from_parts(Translation::from(offset), Rotation::identity())
+}
+
+/// Largest absolute coordinate among `points`. Tolerances are scaled by it, since that's
+/// how the precision of f32 coordinates scales too.
+pub fn magnitude<'a>(points: impl IntoIterator<Item = &'a Point<f32, DIM>>) -> f32 {
+    points
+        .into_iter()
+        .map(|point| point.coords.amax())
+        .fold(0.0, f32::max)
+}
+
+/// The normal of a reflection matrix built by [`crate::mirror::householder_matrix`], which is
+/// its axis (the eigenvector of eigenvalue -1), with an arbitrary orientation.
+// I - 2nn^T maps its normal n to -n, so every column of I - H is a multiple of n, and the
+// largest one is the most precise
+pub fn normal_from_reflection(matrix: &SMatrix<f32, DIM, DIM>) -> Unit<SVector<f32, DIM>> {
+    let difference = SMatrix::<f32, DIM, DIM>::identity() - matrix;
+    let column = difference
+        .column_iter()
+        .max_by(|a, b| a.norm().total_cmp(&b.norm()))
+        .unwrap();
+
+    Unit::new_normalize(column.into_owned())
+}
+
+/// Whether `a` and `b` are at most `tolerance` apart.
+pub fn points_close(a: &Point<f32, DIM>, b: &Point<f32, DIM>, tolerance: f32) -> bool {
+    (a - b).norm() <= tolerance
+}
+
+/// Drops every point that is within `tolerance` of the last one kept, e.g. the near-duplicates
+/// left by tessellation or by a ray bouncing right where it started.
+pub fn dedup_consecutive(points: &[Point<f32, DIM>], tolerance: f32) -> Vec<Point<f32, DIM>> {
+    let mut kept: Vec<Point<f32, DIM>> = Vec::with_capacity(points.len());
+    for point in points {
+        if !kept
+            .last()
+            .is_some_and(|last| points_close(last, point, tolerance))
+        {
+            kept.push(*point);
+        }
+    }
+    kept
+}
+
+/// Expresses a ray given in the scene's frame in `frame`.
+pub fn to_local(ray: &Ray, frame: &Frame) -> Ray {
+    Ray {
+        origin: frame.inverse_transform_point(&ray.origin),
+        // rotations keep the norm
+        direction: Unit::new_unchecked(frame.inverse_transform_vector(&ray.direction)),
+        ..*ray
+    }
+}
+
+/// Expresses a ray given in `frame` in the scene's frame, undoing [`to_local`].
+pub fn from_local(ray: &Ray, frame: &Frame) -> Ray {
+    Ray {
+        origin: frame.transform_point(&ray.origin),
+        direction: Unit::new_unchecked(frame.transform_vector(&ray.direction)),
+        ..*ray
+    }
+}
+
+/// Expresses a reflection matrix computed in `frame` in the scene's frame. Distances don't
+/// need converting, since isometries keep them.
+pub fn reflection_from_local(
+    matrix: &Unit<SMatrix<f32, DIM, DIM>>,
+    frame: &Frame,
+) -> Unit<SMatrix<f32, DIM, DIM>> {
+    let rotation = frame.rotation.matrix();
+    Unit::new_unchecked(rotation * matrix.into_inner() * rotation.transpose())
+}
+
+/// Gauss-legendre nodes on `[-1, 1]` and their weights, for the rules used most.
+const GAUSS_LEGENDRE_TABLES: [&[(f32, f32)]; 5] = [
+    &[(0.0, 2.0)],
+    &[(-0.577_350_26, 1.0), (0.577_350_26, 1.0)],
+    &[
+        (-0.774_596_7, 0.555_555_6),
+        (0.0, 0.888_888_9),
+        (0.774_596_7, 0.555_555_6),
+    ],
+    &[
+        (-0.861_136_3, 0.347_854_85),
+        (-0.339_981_04, 0.652_145_15),
+        (0.339_981_04, 0.652_145_15),
+        (0.861_136_3, 0.347_854_85),
+    ],
+    &[
+        (-0.906_179_8, 0.236_926_89),
+        (-0.538_469_3, 0.478_628_67),
+        (0.0, 0.568_888_9),
+        (0.538_469_3, 0.478_628_67),
+        (0.906_179_8, 0.236_926_89),
+    ],
+];
+
+/// Integral of `f` over `[a, b]` with the `n`-point gauss-legendre rule, which is exact for
+/// polynomials of degree up to `2n - 1`, so a handful of points is enough for smooth
+/// integrands like the speed along a curve. Rules of up to 5 points come from a table, and
+/// the others are computed on the fly.
+pub fn gauss_legendre(f: impl Fn(f32) -> f32, a: f32, b: f32, n: usize) -> f32 {
+    let nodes = match GAUSS_LEGENDRE_TABLES.get(n.wrapping_sub(1)) {
+        Some(table) => Cow::Borrowed(*table),
+        None => Cow::Owned(gauss_legendre_nodes(n)),
+    };
+    let (half, middle) = ((b - a) / 2.0, (a + b) / 2.0);
+
+    half * nodes
+        .iter()
+        .map(|(node, weight)| weight * f(middle + half * node))
+        .sum::<f32>()
+}
+
+/// Nodes and weights of the `n`-point gauss-legendre rule, the roots of the legendre
+/// polynomial `P_n` found with newton's method. Computed in f64, since the recurrence loses
+/// precision quickly.
+fn gauss_legendre_nodes(n: usize) -> Vec<(f32, f32)> {
+    (0..n)
+        .map(|i| {
+            // a close guess of the i-th root, from the largest one down
+            let mut x = (std::f64::consts::PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+            let mut derivative = 1.0;
+            for _ in 0..100 {
+                // P_n(x) and P_(n-1)(x), from the three-term recurrence
+                let (mut current, mut previous) = (1.0, 0.0);
+                for k in 1..=n {
+                    let k = k as f64;
+                    (current, previous) = (
+                        ((2.0 * k - 1.0) * x * current - (k - 1.0) * previous) / k,
+                        current,
+                    );
+                }
+                derivative = n as f64 * (x * current - previous) / (x * x - 1.0);
+
+                let step = current / derivative;
+                x -= step;
+                if step.abs() < 1e-15 {
+                    break;
+                }
+            }
+
+            (
+                x as f32,
+                (2.0 / ((1.0 - x * x) * derivative * derivative)) as f32,
+            )
+        })
+        .collect()
+}
+
+/// Solves the symmetric tridiagonal system
+/// `off_diagonal[i - 1] x[i - 1] + diagonal[i] x[i] + off_diagonal[i] x[i + 1] = rhs[i]` with
+/// the thomas algorithm, `x` vanishing at both ends, which is how the second derivatives of a
+/// natural cubic spline are found. Only the interior rows of `diagonal` and `rhs` are used,
+/// and `x` may be a vector to solve for every axis at once.
+pub fn solve_natural_tridiagonal<T>(
+    mut diagonal: Vec<f32>,
+    off_diagonal: &[f32],
+    mut rhs: Vec<T>,
+) -> Vec<T>
+where
+    T: Copy + Default + Sub<Output = T> + Mul<f32, Output = T> + Div<f32, Output = T>,
+{
+    let n = rhs.len();
+    let mut x = vec![T::default(); n];
+    if n < 3 {
+        return x;
+    }
+
+    // eliminates the coefficient below the diagonal
+    for i in 2..n - 1 {
+        let factor = off_diagonal[i - 1] / diagonal[i - 1];
+        diagonal[i] -= factor * off_diagonal[i - 1];
+        rhs[i] = rhs[i] - rhs[i - 1] * factor;
+    }
+    for i in (1..n - 1).rev() {
+        x[i] = (rhs[i] - x[i + 1] * off_diagonal[i]) / diagonal[i];
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mirror::{householder_matrix, Mirror, PlaneMirror},
+        ray::ray_from,
+    };
+
+    fn frame() -> Frame {
+        Frame::from_parts(
+            Translation::from(SVector::<f32, DIM>::from([2.0, -1.0])),
+            Rotation::from_matrix_unchecked(SMatrix::<f32, DIM, DIM>::new(0.6, -0.8, 0.8, 0.6)),
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ray = ray_from([1.0, 3.0], [-2.0, 1.0]);
+
+        let local = to_local(&ray, &frame());
+        let back = from_local(&local, &frame());
+
+        assert!((local.origin - ray.origin).norm() > 1.0);
+        assert!((back.origin - ray.origin).norm() < 1e-5);
+        assert!((back.direction.into_inner() - ray.direction.into_inner()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_reflection_from_local() {
+        // the x axis of the local frame, which is the line going through (2, -1) along (0.6, 0.8)
+        let local_plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        let scene_plane = PlaneMirror::new([[2.0, -1.0].into(), [2.6, -0.2].into()]);
+        let ray = ray_from([0.0, 3.0], [1.0, -1.0]);
+
+        let (local_distance, local_matrix) = local_plane.reflect(to_local(&ray, &frame()))[0];
+        let (distance, matrix) = scene_plane.reflect(ray)[0];
+
+        assert!((local_distance - distance).abs() < 1e-4);
+        let converted = reflection_from_local(&local_matrix, &frame());
+        assert!((converted.into_inner() - matrix.into_inner()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_dedup_consecutive() {
+        let path = [
+            [0.0, 0.0].into(),
+            [1.0, 0.0].into(),
+            [1.0, 1e-7].into(),
+            [1.0, 1.0].into(),
+            [0.0, 0.0].into(),
+        ];
+
+        let cleaned = dedup_consecutive(&path, 1e-5);
+
+        assert_eq!(cleaned.len(), 4);
+        assert_eq!(cleaned[1], Point::from([1.0, 0.0]));
+        // only consecutive points are merged
+        assert_eq!(cleaned[3], Point::from([0.0, 0.0]));
+        assert!(points_close(&path[1], &path[2], 1e-5));
+    }
+
+    #[test]
+    fn test_normal_from_reflection() {
+        for normal in [[1.0, 2.0], [0.0, 1.0], [-3.0, 0.5]] {
+            let normal = Unit::new_normalize(SVector::from(normal));
+
+            let recovered = normal_from_reflection(&householder_matrix(&normal));
+
+            assert!((recovered.dot(&normal).abs() - 1.0).abs() < 1e-6);
+        }
+
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 1.0].into()]);
+        let recovered = normal_from_reflection(&plane.reflection_matrix());
+        assert!((recovered.dot(&plane.normal()).abs() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gauss_legendre() {
+        // (x + 1)^(2n - 1) is the highest degree the n-point rule integrates exactly
+        for n in 1..=8 {
+            let degree = 2 * n as i32 - 1;
+            let integral = gauss_legendre(|x| (x + 1.0).powi(degree), -1.0, 2.0, n);
+            let exact = 3f32.powi(degree + 1) / (degree + 1) as f32;
+            assert!((integral / exact - 1.0).abs() < 1e-5, "{n} points");
+        }
+
+        // one degree more isn't exact anymore
+        let integral = gauss_legendre(|x| x.powi(4), -1.0, 1.0, 2);
+        assert!((integral - 0.4).abs() > 0.1);
+
+        assert!((gauss_legendre(f32::sin, 0.0, std::f32::consts::PI, 6) - 2.0).abs() < 1e-5);
+        assert_eq!(gauss_legendre(|x| x, 0.0, 1.0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_solve_natural_tridiagonal() {
+        // 4 x1 + x2 = 6, x1 + 3 x2 + 2 x3 = 13, 2 x2 + 5 x3 = 19, solved by (1, 2, 3)
+        let diagonal = vec![0.0, 4.0, 3.0, 5.0, 0.0];
+        let off_diagonal = [0.0, 1.0, 2.0, 0.0];
+        let x = solve_natural_tridiagonal(diagonal, &off_diagonal, vec![0.0, 6.0, 13.0, 19.0, 0.0]);
+
+        for (solved, expected) in x.iter().zip([0.0, 1.0, 2.0, 3.0, 0.0]) {
+            assert!((solved - expected).abs() < 1e-5);
+        }
+
+        // every axis at once
+        let rhs = vec![SVector::<f32, DIM>::from([6.0, -6.0]); 3];
+        let x = solve_natural_tridiagonal(vec![4.0; 3], &[1.0; 2], rhs);
+        assert_eq!(x[0], SVector::<f32, DIM>::zeros());
+        assert!((x[1] - SVector::from([1.5, -1.5])).norm() < 1e-6);
+
+        assert!(solve_natural_tridiagonal::<f32>(vec![], &[], vec![]).is_empty());
+    }
+}
@@ -1,28 +1,236 @@
-use nalgebra::{Point, SMatrix, Unit};
+use nalgebra::{Point, SMatrix, SVector, Unit};
+use smallvec::{smallvec, SmallVec};
 
-use crate::{ray::Ray, DIM};
+use crate::{
+    bezier_mirror::BezierMirror,
+    circle_mirror::CircleMirror,
+    error::MirrorError,
+    intersection::Intersection,
+    json,
+    material::{self, Polarization},
+    math::{magnitude, normal_from_reflection},
+    polygon_mirror::PolygonMirror,
+    profile_mirror::ProfileMirror,
+    ray::Ray,
+    scene::{tolerance, EPSILON},
+    segment_mirror::SegmentMirror,
+    spline_mirror::SplineMirror,
+    DIM,
+};
+
+/// Number of points sampled by the default [`Mirror::miss_distance`].
+const MISS_DISTANCE_SAMPLES: usize = 256;
+
+/// The intersections returned by [`Mirror::reflect`]. Most mirrors are hit at most twice by a
+/// ray, which is stored inline without allocating.
+pub type Reflections = SmallVec<[(f32, Unit<SMatrix<f32, DIM, DIM>>); 2]>;
 
 pub trait Mirror {
-    fn reflect(&self, ray: Ray) -> Vec<(f32, Unit<SMatrix<f32, DIM, DIM>>)>;
+    /// Every intersection of the ray with the mirror as its distance along the ray and the
+    /// reflection matrix at that point, sorted by increasing distance.
+    ///
+    /// Reflection matrices should be built with [`householder_matrix`], which doesn't depend
+    /// on the orientation of the normal: the reflected ray always leaves on the side it came from.
+    fn reflect(&self, ray: Ray) -> Reflections;
+    /// The ray leaving the mirror after the nearest reflection, for quick one-bounce
+    /// experiments without a [`crate::simulation::Simulation`].
+    fn reflect_ray(&self, ray: &Ray) -> Option<Ray> {
+        self.reflect(*ray)
+            .first()
+            .map(|(distance, matrix)| Intersection::new(ray, *distance, *matrix).reflected_ray(ray))
+    }
+    /// The normal at the nearest reflection, oriented towards the side `ray` comes from,
+    /// whichever way the mirror defines its own normals.
+    fn facing_normal(&self, ray: &Ray) -> Option<Unit<SVector<f32, DIM>>> {
+        self.reflect(*ray).first().map(|(distance, matrix)| {
+            Intersection::new(ray, *distance, *matrix).facing_normal(&ray.direction)
+        })
+    }
+    /// The smallest distance between the ray, starting at its origin, and the reflecting
+    /// surface: 0 when the ray hits it, how far it misses it by otherwise, e.g. for aiming.
+    /// By default it is measured to points sampled over the surface, so it is approximate.
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        if !self.reflect(*ray).is_empty() {
+            return 0.0;
+        }
+
+        self.sample_surface(MISS_DISTANCE_SAMPLES)
+            .iter()
+            .map(|point| ray.distance_to(point))
+            .fold(f32::INFINITY, f32::min)
+    }
     fn get_type(&self) -> String;
+    /// Whether intersections are computed in closed form, as opposed to numerically.
+    fn is_analytic(&self) -> bool;
+    /// Whether the reflecting surface is flat, so a ray leaving it can't hit it again right
+    /// away. Tracers then skip it for the next bounce, rather than relying on
+    /// [`crate::scene::EPSILON`] alone to ignore the point the ray leaves from.
+    fn is_flat(&self) -> bool {
+        false
+    }
+    /// Whether the mirror absorbs every ray hitting it, casting a shadow on the mirrors behind
+    /// it. Tracers end the trace of such rays with [`crate::simulation::Termination::Absorbed`].
+    fn is_opaque(&self) -> bool {
+        false
+    }
+    /// Whether `point` is at most `tolerance` away from the reflecting surface.
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool;
+    /// `n` points spread over the reflecting surface, mostly meant for visualization.
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>>;
+    /// Fraction of the light intensity kept by a reflection on this mirror.
+    fn reflectivity(&self) -> f32 {
+        1.0
+    }
+    /// Fraction of the light intensity kept by a reflection at `point`, on the mirror, for
+    /// mirrors whose reflectivity isn't the same everywhere. Tracers weigh rays with it.
+    fn reflectivity_at(&self, _point: &Point<f32, DIM>) -> f32 {
+        self.reflectivity()
+    }
+    /// Fraction of the light intensity kept by a reflection of light with the given
+    /// polarization, `sin2_incidence` being the squared sine of the angle of incidence
+    /// (see [`crate::intersection::Intersection::sin2_incidence`]). Most mirrors depend on neither.
+    fn polarized_reflectivity(&self, _polarization: Polarization, _sin2_incidence: f32) -> f32 {
+        self.reflectivity()
+    }
+    /// Phase, in radians, the p-polarized part of the light gains over the s-polarized part
+    /// on reflection, at the given squared sine of the angle of incidence. Metallic coatings
+    /// retard one polarization, which turns linearly polarized light elliptical and rotates
+    /// its polarization plane. Ideal mirrors don't.
+    fn retardance(&self, _sin2_incidence: f32) -> f32 {
+        0.0
+    }
+    /// The range of the natural parameter of the surface, e.g. `t` for a bezier curve or the
+    /// angle along an arc, so generic samplers and exporters know which values are valid.
+    /// Surfaces without a bounded parametrization, like planes, have none.
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        None
+    }
+    /// Whether `point`, on the mirror, is at most `tolerance` away from one of its edges,
+    /// where hits are numerically ambiguous. Unbounded and smooth closed mirrors have no
+    /// edges, but mirrors made of parts have those of their parts.
+    fn is_on_boundary(&self, _point: &Point<f32, DIM>, _tolerance: f32) -> bool {
+        false
+    }
+    /// Where `point`, on the mirror, is in the mirror's own frame, for mirrors defined around
+    /// the origin and placed in the scene, e.g. on the unit circle for a circle. Mirrors
+    /// without a frame of their own have none.
+    fn local_point(&self, _point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        None
+    }
+    /// The largest angle of incidence, in radians, the coating of the mirror reflects at.
+    /// Rays hitting it more obliquely are absorbed, ending their trace. Most mirrors have none.
+    fn max_incidence_angle(&self) -> Option<f32> {
+        None
+    }
+    /// A sphere containing the whole reflecting surface, as its center and radius, used to
+    /// skip mirrors a ray can't reach. Unbounded mirrors, like planes, have none.
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        None
+    }
+    /// Checks that the mirror is well-formed, so mistakes are caught before tracing.
+    fn validate(&self) -> Result<(), MirrorError> {
+        Ok(())
+    }
+    /// The json read by [`mirror_from_json`], type included, to save scenes. Mirrors which
+    /// can't be read back from json, like those built from closures, can't be written either.
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        Err(MirrorError::Unserializable(self.get_type()))
+    }
 }
 
-struct CompositeMirror {
+pub struct CompositeMirror {
     mirrors: Vec<Box<dyn Mirror>>,
 }
 
 impl Mirror for CompositeMirror {
-    fn reflect(&self, ray: Ray) -> Vec<(f32, Unit<SMatrix<f32, DIM, DIM>>)> {
-        // use the other mirror to reflect the ray
-        vec![]
+    /// A ray hitting two parts where they meet is reflected by a single one, see
+    /// [`sort_resolving_seams`].
+    fn reflect(&self, ray: Ray) -> Reflections {
+        // use the other mirrors to reflect the ray
+        sort_resolving_seams(
+            self.mirrors
+                .iter()
+                .flat_map(|mirror| mirror.reflect(ray))
+                .collect(),
+            &ray,
+        )
     }
     fn get_type(&self) -> String {
         "composite".to_string()
     }
+    fn is_analytic(&self) -> bool {
+        self.mirrors.iter().all(|mirror| mirror.is_analytic())
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirrors
+            .iter()
+            .any(|mirror| mirror.contains_point(point, tolerance))
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        // share the samples between the mirrors, the first ones taking the remainder
+        let count = self.mirrors.len().max(1);
+        self.mirrors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, mirror)| mirror.sample_surface(n / count + usize::from(i < n % count)))
+            .collect()
+    }
+    /// The mean of the reflectivities of the parts, exact when they all share a coating.
+    fn reflectivity(&self) -> f32 {
+        self.mean_over_parts(1.0, |mirror| mirror.reflectivity())
+    }
+    /// The reflectivity of the first part `point` is on.
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.mirrors
+            .iter()
+            .find(|mirror| mirror.contains_point(point, tolerance(point, mirror.bounding_sphere())))
+            .map_or(self.reflectivity(), |mirror| mirror.reflectivity_at(point))
+    }
+    /// The mean of the polarized reflectivities of the parts: like
+    /// [`Mirror::polarized_reflectivity`], it only depends on the angle of incidence, not on
+    /// which part is hit, so it is only exact when they all share a coating.
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.mean_over_parts(1.0, |mirror| {
+            mirror.polarized_reflectivity(polarization, sin2_incidence)
+        })
+    }
+    /// The mean of the retardances of the parts, exact when they all share a coating.
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mean_over_parts(0.0, |mirror| mirror.retardance(sin2_incidence))
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirrors
+            .iter()
+            .any(|mirror| mirror.is_on_boundary(point, tolerance))
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        // a single unbounded mirror makes the whole composite unbounded
+        let spheres = self
+            .mirrors
+            .iter()
+            .map(|mirror| mirror.bounding_sphere())
+            .collect::<Option<Vec<_>>>()?;
+        enclosing_sphere(&spheres)
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        self.mirrors.iter().try_for_each(|mirror| mirror.validate())
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        let mirrors = self
+            .mirrors
+            .iter()
+            .map(|mirror| mirror.to_json())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "mirrors": mirrors,
+        }))
+    }
 }
 
 impl CompositeMirror {
-    fn from_json(json: &serde_json::Value) -> Self {
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
         /* example json
         {
             "mirrors": [
@@ -44,41 +252,158 @@ impl CompositeMirror {
             ]
         }
          */
-        let mirrors = json["mirrors"]
-            .as_array()
-            .unwrap()
+        let mirrors = json::array(json, "mirrors")?
             .iter()
-            .map(|mirror| {
-                let mirror_type = mirror["type"].as_str().unwrap();
+            .map(mirror_from_json)
+            .collect::<Result<Vec<_>, _>>()?;
 
-                match mirror_type {
-                    "plane" => Box::new(PlaneMirror::from_json(mirror)) as Box<dyn Mirror>,
-                    "sphere" => Box::new(SphereMirror::from_json(mirror)) as Box<dyn Mirror>,
-                    _ => panic!("Unknown mirror type: {}", mirror_type),
-                }
-            })
-            .collect::<Vec<_>>();
+        Ok(Self { mirrors })
+    }
 
-        Self { mirrors }
+    /// The mean of `f` over the parts, or `empty` if there are none.
+    fn mean_over_parts(&self, empty: f32, f: impl Fn(&dyn Mirror) -> f32) -> f32 {
+        if self.mirrors.is_empty() {
+            return empty;
+        }
+        self.mirrors.iter().map(|mirror| f(&**mirror)).sum::<f32>() / self.mirrors.len() as f32
     }
 }
 
+/// Builds a mirror of any type from its json, picking the type from its `"type"` field.
+pub fn mirror_from_json(json: &serde_json::Value) -> Result<Box<dyn Mirror>, MirrorError> {
+    let mirror_type = json["type"].as_str().ok_or(MirrorError::InvalidField {
+        field: "type".to_string(),
+        expected: "a string",
+    })?;
+
+    Ok(match mirror_type {
+        "plane" => Box::new(PlaneMirror::from_json(json)?),
+        "sphere" => Box::new(SphereMirror::from_json(json)?),
+        "composite" => Box::new(CompositeMirror::from_json(json)?),
+        "segment" => Box::new(SegmentMirror::from_json(json)?),
+        "circle" => Box::new(CircleMirror::from_json(json)?),
+        "bezier" => Box::new(BezierMirror::from_json(json)?),
+        "polygon" => Box::new(PolygonMirror::from_json(json)?),
+        "spline" => Box::new(SplineMirror::from_json(json)?),
+        "profile" => Box::new(ProfileMirror::from_json(json)?),
+        _ => return Err(MirrorError::UnknownMirrorType(mirror_type.to_string())),
+    })
+}
+
 #[derive(Clone, Copy)]
-struct PlaneMirror {
+pub struct PlaneMirror {
     points: [Point<f32, DIM>; DIM],
+    normal: Unit<SVector<f32, DIM>>,
+    // the householder matrix only depends on the normal, so it is computed once
+    reflection_matrix: Unit<SMatrix<f32, DIM, DIM>>,
+    reflectivity: f32,
 }
 
 impl Mirror for PlaneMirror {
-    fn reflect(&self, ray: Ray) -> Vec<(f32, Unit<SMatrix<f32, DIM, DIM>>)> {
-        vec![]
+    fn reflect(&self, ray: Ray) -> Reflections {
+        let denominator = self.normal.dot(&ray.direction);
+
+        // the ray is parallel to the plane
+        if denominator.abs() < f32::EPSILON {
+            return Reflections::new();
+        }
+
+        let distance = self.normal.dot(&(self.points[0] - ray.origin)) / denominator;
+
+        if is_valid_distance(distance) {
+            smallvec![(distance, self.reflection_matrix)]
+        } else {
+            Reflections::new()
+        }
+    }
+    /// A ray missing the plane is parallel to it or going away from it, so it is closest
+    /// at its origin.
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        if self.reflect(*ray).is_empty() {
+            self.signed_distance(&ray.origin).abs()
+        } else {
+            0.0
+        }
     }
     fn get_type(&self) -> String {
         "plane".to_string()
     }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn is_flat(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.signed_distance(point).abs() <= tolerance
+    }
+    /// Samples a grid over the parallelotope spanned by the points defining the plane,
+    /// which is the segment between them in 2D.
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        let per_axis = (n as f32).powf(1.0 / (DIM - 1) as f32).round() as usize;
+        let parameters = sample_parameters(per_axis);
+        let edges = self.points[1..]
+            .iter()
+            .map(|point| point - self.points[0])
+            .collect::<Vec<_>>();
+
+        let mut samples = vec![self.points[0]];
+        for edge in edges {
+            samples = samples
+                .iter()
+                .flat_map(|sample| parameters.iter().map(move |t| sample + *t * edge))
+                .collect();
+        }
+
+        samples
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self
+            .points
+            .iter()
+            .any(|point| !point.iter().all(|x| x.is_finite()))
+        {
+            degenerate(self, "a point isn't finite")
+        } else if plane_basis(&self.points).len() < DIM - 1 {
+            degenerate(self, "its points don't span a hyperplane")
+        } else if (self.normal.norm() - 1.0).abs() > 1e-4 {
+            degenerate(self, "its normal isn't normalized")
+        } else {
+            Ok(())
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "points": json::from_points(&self.points),
+            "reflectivity": self.reflectivity,
+        }))
+    }
 }
 
 impl PlaneMirror {
-    fn from_json(json: &serde_json::Value) -> Self {
+    pub fn new(points: [Point<f32, DIM>; DIM]) -> Self {
+        let normal = hyperplane_normal(&points);
+
+        Self {
+            points,
+            normal,
+            reflection_matrix: householder_matrix(&normal),
+            reflectivity: 1.0,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
         /* example json
         {
             "points": [
@@ -86,80 +411,368 @@ impl PlaneMirror {
                 [4.0, 5.0, 6.0, ...],
                 [7.0, 8.0, 9.0, ...],
                 ...
-            ]
+            ],
+            "homogeneous": false, // optional, when true points end with a w dividing the others
+            "material": "silver" // or "reflectivity": 0.97, optional
         }
          */
-        let points = json["points"]
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|point| {
-                let point = point
-                    .as_array()
-                    .unwrap()
-                    .iter()
-                    .map(|value| value.as_f64().unwrap() as f32)
-                    .collect::<Vec<_>>();
-
-                Point::from_slice(&point)
-            })
-            .collect::<Vec<_>>();
+        let points = json::points(json, "points")?;
+
+        let mirror_points: [Point<f32, DIM>; DIM] =
+            points.try_into().map_err(|_| MirrorError::InvalidField {
+                field: "points".to_string(),
+                expected: "an array of exactly DIM points",
+            })?;
+
+        Ok(Self::new(mirror_points).with_reflectivity(material::reflectivity_from_json(json)?))
+    }
+
+    pub fn points(&self) -> &[Point<f32, DIM>; DIM] {
+        &self.points
+    }
 
-        let mut mirror_points = [Point::origin(); DIM];
-        for (i, point) in points.iter().enumerate() {
-            mirror_points[i] = *point;
+    pub fn normal(&self) -> Unit<SVector<f32, DIM>> {
+        self.normal
+    }
+
+    /// Distance from `point` to the plane, positive on the side the normal points to.
+    pub fn signed_distance(&self, point: &Point<f32, DIM>) -> f32 {
+        self.normal.dot(&(point - self.points[0]))
+    }
+
+    /// The virtual image of `point`, i.e. its reflection across the plane.
+    pub fn mirror_point(&self, point: &Point<f32, DIM>) -> Point<f32, DIM> {
+        point - 2.0 * self.signed_distance(point) * self.normal.into_inner()
+    }
+
+    /// The virtual image of a curve across the plane, each of its control points being
+    /// mirrored. A path folded by the plane goes straight through the image instead.
+    pub fn reflect_mirror(&self, other: &BezierMirror) -> BezierMirror {
+        other.map_control_points(|point| self.mirror_point(point))
+    }
+
+    pub fn reflection_matrix(&self) -> Unit<SMatrix<f32, DIM, DIM>> {
+        self.reflection_matrix
+    }
+}
+
+/// Whether a distance can be returned by [`Mirror::reflect`]: in front of the ray and finite.
+/// Degenerate geometry (near parallel rays, infinite or NaN coordinates) can make intersection
+/// formulas produce NaN or infinities, which would corrupt the choice of the nearest hit.
+pub(crate) fn is_valid_distance(distance: f32) -> bool {
+    distance > 0.0 && distance.is_finite()
+}
+
+/// Sorts reflections by increasing distance, as expected from [`Mirror::reflect`], dropping
+/// the non-finite ones.
+pub(crate) fn sort_by_distance(mut reflections: Reflections) -> Reflections {
+    reflections.retain(|(distance, _)| distance.is_finite());
+    reflections.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    reflections
+}
+
+/// Sorts `reflections` of `ray` like [`sort_by_distance`], keeping a single reflection where
+/// the ray hits a seam between the parts of a mirror, like the vertex shared by two edges of
+/// a polygon, which both parts report at the same distance (within [`EPSILON`], scaled like
+/// the coordinates of the hit). The one kept is of the part whose normal best opposes the
+/// ray, which it hits the most head-on, or the nearest one on a tie, so corners always
+/// reflect the same way.
+pub(crate) fn sort_resolving_seams(reflections: Reflections, ray: &Ray) -> Reflections {
+    let facing = |matrix: &Unit<SMatrix<f32, DIM, DIM>>| facing(matrix, ray);
+    let origin_magnitude = magnitude([&ray.origin]);
+
+    let mut resolved = Reflections::new();
+    for (distance, matrix) in sort_by_distance(reflections) {
+        match resolved.last_mut() {
+            Some(last) if distance - last.0 <= EPSILON * (origin_magnitude + distance.abs()) => {
+                if facing(&matrix) > facing(&last.1) {
+                    *last = (distance, matrix);
+                }
+            }
+            _ => resolved.push((distance, matrix)),
         }
+    }
+    resolved
+}
 
-        Self {
-            points: mirror_points,
+/// How head-on `ray` hits a surface reflecting it with `matrix`, as the absolute cosine of
+/// the angle of incidence.
+pub(crate) fn facing(matrix: &Unit<SMatrix<f32, DIM, DIM>>, ray: &Ray) -> f32 {
+    normal_from_reflection(matrix).dot(&ray.direction).abs()
+}
+
+/// `n` evenly spaced parameters covering `[0, 1]`, both ends included.
+pub(crate) fn sample_parameters(n: usize) -> Vec<f32> {
+    match n {
+        0 => vec![],
+        1 => vec![0.0],
+        _ => (0..n).map(|i| i as f32 / (n - 1) as f32).collect(),
+    }
+}
+
+/// Finds a unit vector orthogonal to the hyperplane going through `points`,
+/// by removing the plane's directions from each axis (gram-schmidt) and keeping
+/// the axis that has the most left.
+fn hyperplane_normal(points: &[Point<f32, DIM>; DIM]) -> Unit<SVector<f32, DIM>> {
+    let basis = plane_basis(points);
+
+    let normal = (0..DIM)
+        .map(|axis| {
+            let mut candidate = SVector::<f32, DIM>::ith(axis, 1.0);
+            for vector in &basis {
+                candidate -= candidate.dot(vector) * vector;
+            }
+            candidate
+        })
+        .max_by(|a, b| a.norm().total_cmp(&b.norm()))
+        .unwrap();
+
+    Unit::new_normalize(normal)
+}
+
+/// An orthonormal basis of the directions spanned by `points`, which has less than
+/// `DIM - 1` vectors when the points are degenerate.
+fn plane_basis(points: &[Point<f32, DIM>; DIM]) -> Vec<SVector<f32, DIM>> {
+    let mut basis: Vec<SVector<f32, DIM>> = Vec::with_capacity(DIM - 1);
+    let threshold = f32::EPSILON * magnitude(points);
+
+    for point in &points[1..] {
+        let mut direction = point - points[0];
+        for vector in &basis {
+            direction -= direction.dot(vector) * vector;
+        }
+
+        if direction.norm() > threshold {
+            basis.push(direction.normalize());
         }
     }
+
+    basis
+}
+
+/// The error returned by [`Mirror::validate`] when `mirror` is degenerate.
+pub(crate) fn degenerate(mirror: &dyn Mirror, reason: &'static str) -> Result<(), MirrorError> {
+    Err(MirrorError::DegenerateMirror {
+        mirror_type: mirror.get_type(),
+        reason,
+    })
+}
+
+/// A sphere containing all of `spheres`, not necessarily the smallest one.
+/// Points can be given as spheres with no radius.
+pub(crate) fn enclosing_sphere(
+    spheres: &[(Point<f32, DIM>, f32)],
+) -> Option<(Point<f32, DIM>, f32)> {
+    if spheres.is_empty() {
+        return None;
+    }
+
+    let center = spheres
+        .iter()
+        .fold(Point::origin(), |sum, (center, _)| sum + center.coords)
+        / spheres.len() as f32;
+    let radius = spheres
+        .iter()
+        .map(|(other, radius)| (other - center).norm() + radius)
+        .fold(0.0, f32::max);
+
+    Some((center, radius))
+}
+
+/// The reflection matrix across the hyperplane orthogonal to `normal`: `I - 2nn^T`.
+///
+/// It is wrapped in a [`Unit`] without normalizing, since the matrix is already orthogonal.
+pub fn householder_matrix(normal: &Unit<SVector<f32, DIM>>) -> Unit<SMatrix<f32, DIM, DIM>> {
+    Unit::new_unchecked(
+        SMatrix::<f32, DIM, DIM>::identity() - 2.0 * normal.into_inner() * normal.transpose(),
+    )
 }
 
 #[derive(Clone, Copy)]
-struct SphereMirror {
+pub struct SphereMirror {
     center: Point<f32, DIM>,
     radius: f32,
+    reflectivity: f32,
 }
 
 impl Mirror for SphereMirror {
-    fn reflect(&self, ray: Ray) -> Vec<(f32, Unit<SMatrix<f32, DIM, DIM>>)> {
-        vec![]
+    fn reflect(&self, ray: Ray) -> Reflections {
+        // solve |origin + t * direction - center|^2 = radius^2 for t
+        let to_origin = ray.origin - self.center;
+
+        // from the center, every direction is along a normal, and goes straight back
+        if to_origin.norm() <= EPSILON * self.radius {
+            let distance = self.radius - to_origin.dot(&ray.direction);
+            return if is_valid_distance(distance) {
+                smallvec![(distance, householder_matrix(&ray.direction))]
+            } else {
+                Reflections::new()
+            };
+        }
+
+        let b = ray.direction.dot(&to_origin);
+        let c = to_origin.norm_squared() - self.radius * self.radius;
+        let discriminant = b * b - c;
+
+        if discriminant < 0.0 {
+            return Reflections::new();
+        }
+
+        let root = discriminant.sqrt();
+        // a ray starting on the sphere, e.g. right after bouncing on it, has one root at its
+        // origin, which rounding can make slightly positive: only the other one may be hit
+        let roots: SmallVec<[f32; 2]> = if c.abs() <= EPSILON * self.radius * self.radius {
+            smallvec![-b - b.signum() * root]
+        } else if root == 0.0 {
+            smallvec![-b]
+        } else {
+            smallvec![-b - root, -b + root]
+        };
+
+        // both the near and the far side, so callers modeling shells see where rays leave
+        roots
+            .into_iter()
+            .filter(|distance| is_valid_distance(*distance))
+            .map(|distance| {
+                let point = ray.origin + distance * ray.direction.into_inner();
+                let normal = Unit::new_normalize(point - self.center);
+                (distance, householder_matrix(&normal))
+            })
+            .collect()
+    }
+    /// Inside the sphere, rays always hit it.
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        (ray.distance_to(&self.center) - self.radius).max(0.0)
+    }
+    /// On the unit sphere.
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        Some(Point::from((point - self.center) / self.radius))
     }
     fn get_type(&self) -> String {
         "sphere".to_string()
     }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        ((point - self.center).norm() - self.radius).abs() <= tolerance
+    }
+    /// Samples the great circle in the plane of the first two axes, the whole circle in 2D.
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        (0..n)
+            .map(|i| {
+                let angle = i as f32 / n as f32 * std::f32::consts::TAU;
+                let mut offset = SVector::<f32, DIM>::zeros();
+                offset[0] = angle.cos();
+                offset[1] = angle.sin();
+
+                self.center + self.radius * offset
+            })
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        Some((self.center, self.radius))
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !self.center.iter().all(|x| x.is_finite()) {
+            degenerate(self, "its center isn't finite")
+        } else if !(self.radius.is_finite() && self.radius > 0.0) {
+            degenerate(self, "its radius isn't strictly positive")
+        } else {
+            Ok(())
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "center": json::from_point(&self.center),
+            "radius": self.radius,
+            "reflectivity": self.reflectivity,
+        }))
+    }
 }
 
 impl SphereMirror {
-    fn from_json(json: &serde_json::Value) -> Self {
+    pub fn new(center: Point<f32, DIM>, radius: f32) -> Self {
+        Self {
+            center,
+            radius,
+            reflectivity: 1.0,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
         /* example json
         {
             "center": [1.0, 2.0, 3.0],
-            "radius": 4.0
+            "radius": 4.0,
+            "material": "silver" // or "reflectivity": 0.97, optional
         }
          */
-        let center = json["center"]
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|value| value.as_f64().unwrap() as f32)
-            .collect::<Vec<_>>();
+        let center = json::point(json, "center")?;
+        let radius = json::number(json, "radius")?;
 
-        let radius = json["radius"].as_f64().unwrap() as f32;
+        Ok(Self::new(center, radius).with_reflectivity(material::reflectivity_from_json(json)?))
+    }
 
-        Self {
-            center: Point::from_slice(&center),
-            radius,
+    pub fn center(&self) -> Point<f32, DIM> {
+        self.center
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+/// Fixtures and assertions shared by the tests of several mirrors.
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use super::*;
+    use crate::{scene::Scene, simulation::Simulation};
+
+    /// Asserts that `mirror` sends every one of `rays` where `expected` does, e.g. a curve
+    /// fitted on straight samples and the plane going through them.
+    pub(crate) fn assert_reflects_like(mirror: &dyn Mirror, expected: &dyn Mirror, rays: &[Ray]) {
+        for ray in rays {
+            let reflected = mirror.reflect_ray(ray).unwrap();
+            let expected = expected.reflect_ray(ray).unwrap();
+
+            assert!((reflected.origin - expected.origin).norm() < 1e-4);
+            assert!(
+                (reflected.direction.into_inner() - expected.direction.into_inner()).norm() < 1e-4
+            );
         }
     }
+
+    /// A plain mirror along y = 0, behind `mirror`, usually put along y = 1: rays coming from
+    /// above end their first bounce on y = 1 when `mirror` reflects them and on y = 0 otherwise.
+    pub(crate) fn in_front_of_plane(mirror: Box<dyn Mirror>) -> Simulation {
+        Simulation::new(Scene::new(vec![
+            mirror,
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+        ]))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::any::Any;
-
     use super::*;
+    use crate::{
+        anisotropic_mirror::AnisotropicMirror,
+        bezier_mirror::BezierMirror,
+        ray::{ray_from, DEFAULT_WAVELENGTH},
+        segment_mirror::SegmentMirror,
+    };
 
     fn complete_with_0(mut vec: Vec<f32>) -> Vec<f32> {
         vec.resize(DIM, 0.0);
@@ -175,7 +788,7 @@ mod tests {
             ]
         });
 
-        let mirror = PlaneMirror::from_json(&json);
+        let mirror = PlaneMirror::from_json(&json).unwrap();
 
         assert_eq!(
             mirror.points[0],
@@ -188,7 +801,246 @@ mod tests {
     }
 
     #[test]
-    fn test_plane_mirror_reflect() {}
+    fn test_plane_mirror_reflect() {
+        let mirror = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 2.0])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![1.0, -1.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let reflections = mirror.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+
+        let (distance, matrix) = reflections[0];
+        assert!((distance - 2.0 * 2.0_f32.sqrt()).abs() < 1e-5);
+
+        let reflected = matrix.into_inner() * ray.direction.into_inner();
+        let expected = SVector::<f32, DIM>::from_vec(complete_with_0(vec![1.0, 1.0])).normalize();
+        assert!((reflected - expected).norm() < 1e-6);
+
+        // the mirror is behind the ray
+        let ray = Ray {
+            origin: ray.origin,
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![0.0, 1.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+        assert!(mirror.reflect(ray).is_empty());
+    }
+
+    #[test]
+    fn test_reflect_ray() {
+        let mirror = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 2.0])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![1.0, -1.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let outgoing = mirror.reflect_ray(&ray).unwrap();
+
+        let expected = SVector::<f32, DIM>::from_vec(complete_with_0(vec![1.0, 1.0])).normalize();
+        assert!(
+            (outgoing.origin - Point::from_slice(&complete_with_0(vec![2.0, 0.0]))).norm() < 1e-5
+        );
+        assert!((outgoing.direction.into_inner() - expected).norm() < 1e-6);
+
+        let away = Ray {
+            direction: -ray.direction,
+            ..ray
+        };
+        assert_eq!(mirror.reflect_ray(&away), None);
+    }
+
+    #[test]
+    fn test_plane_mirror_cached_matrix() {
+        let mirror = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![2.0, 3.0])),
+        ]);
+
+        let tangent = SVector::<f32, DIM>::from_vec(complete_with_0(vec![1.0, 2.0])).normalize();
+        let normal = SVector::<f32, DIM>::from_vec(complete_with_0(vec![-tangent[1], tangent[0]]));
+        let expected = SMatrix::<f32, DIM, DIM>::identity() - 2.0 * normal * normal.transpose();
+
+        assert!((mirror.reflection_matrix().into_inner() - expected).norm() < 1e-6);
+        assert_eq!(
+            mirror.reflection_matrix(),
+            householder_matrix(&mirror.normal())
+        );
+    }
+
+    #[test]
+    fn test_plane_mirror_contains_point() {
+        let mirror = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+        ]);
+
+        let on_plane = Point::<f32, DIM>::from_slice(&complete_with_0(vec![5.0, 1.0]));
+        let off_plane = Point::<f32, DIM>::from_slice(&complete_with_0(vec![5.0, 1.1]));
+
+        assert!(mirror.contains_point(&on_plane, 1e-6));
+        assert!(!mirror.contains_point(&off_plane, 1e-2));
+        assert!(mirror.contains_point(&off_plane, 0.2));
+    }
+
+    #[test]
+    fn test_plane_mirror_mirror_point() {
+        let mirror = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+
+        let point = Point::<f32, DIM>::from_slice(&complete_with_0(vec![3.0, 2.0]));
+        assert_eq!(
+            mirror.mirror_point(&point),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![3.0, -2.0]))
+        );
+
+        // points on the plane are their own image
+        let on_plane = Point::<f32, DIM>::from_slice(&complete_with_0(vec![-4.0, 0.0]));
+        assert_eq!(mirror.mirror_point(&on_plane), on_plane);
+    }
+
+    #[test]
+    fn test_plane_mirror_reflect_mirror() {
+        let mirror = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        let curve = BezierMirror::new(vec![
+            [0.0, 1.0].into(),
+            [1.0, 3.0].into(),
+            [2.0, -0.5].into(),
+        ])
+        .with_reflectivity(0.9);
+
+        let image = mirror.reflect_mirror(&curve);
+
+        assert_eq!(
+            image,
+            BezierMirror::new(vec![
+                [0.0, -1.0].into(),
+                [1.0, -3.0].into(),
+                [2.0, 0.5].into()
+            ])
+            .with_reflectivity(0.9)
+        );
+    }
+
+    #[test]
+    fn test_miss_distance() {
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        assert_eq!(plane.miss_distance(&ray_from([0.0, 2.0], [1.0, -1.0])), 0.0);
+        assert!((plane.miss_distance(&ray_from([0.0, 2.0], [1.0, 1.0])) - 2.0).abs() < 1e-6);
+
+        let sphere = SphereMirror::new([0.0, 0.0].into(), 1.0);
+        assert_eq!(
+            sphere.miss_distance(&ray_from([-3.0, 0.5], [1.0, 0.0])),
+            0.0
+        );
+        assert!((sphere.miss_distance(&ray_from([-3.0, 1.5], [1.0, 0.0])) - 0.5).abs() < 1e-6);
+
+        // sampled, for mirrors without a closed form
+        let segment = SegmentMirror::new([0.0, 0.0].into(), [1.0, 0.0].into());
+        assert_eq!(
+            segment.miss_distance(&ray_from([0.5, 1.0], [0.0, -1.0])),
+            0.0
+        );
+        assert!((segment.miss_distance(&ray_from([2.0, 1.0], [0.0, -1.0])) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_plane_mirror_material_from_json() {
+        let json = serde_json::json!({
+            "points": [
+                complete_with_0(vec![1.0, 2.0]),
+                complete_with_0(vec![3.0, 4.0]),
+            ],
+            "material": "silver"
+        });
+
+        let mirror = PlaneMirror::from_json(&json).unwrap();
+        assert_eq!(mirror.reflectivity(), 0.97);
+
+        let json = serde_json::json!({
+            "points": [
+                complete_with_0(vec![1.0, 2.0]),
+                complete_with_0(vec![3.0, 4.0]),
+            ],
+            "material": "cheese"
+        });
+
+        assert!(matches!(
+            PlaneMirror::from_json(&json),
+            Err(MirrorError::UnknownMaterial(material)) if material == "cheese"
+        ));
+    }
+
+    #[test]
+    fn test_plane_mirror_sample_surface() {
+        let mirror = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 1.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![2.0, 1.0])),
+        ]);
+
+        let samples = mirror.sample_surface(5);
+
+        assert_eq!(samples.len(), 5);
+        assert_eq!(samples[0], mirror.points[0]);
+        assert_eq!(samples[4], mirror.points[1]);
+        assert!(samples.iter().all(|p| mirror.contains_point(p, 1e-6)));
+    }
+
+    #[test]
+    fn test_sphere_mirror_sample_surface() {
+        let mirror = SphereMirror::new(Point::from_slice(&complete_with_0(vec![1.0, 2.0])), 3.0);
+
+        let samples = mirror.sample_surface(16);
+
+        assert_eq!(samples.len(), 16);
+        assert!(samples.iter().all(|p| mirror.contains_point(p, 1e-5)));
+    }
+
+    #[test]
+    fn test_is_analytic() {
+        let plane = PlaneMirror::new([
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 0.0])),
+        ]);
+        let bezier = crate::bezier_mirror::BezierMirror::new(vec![
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![0.0, 0.0])),
+            Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 1.0])),
+        ]);
+
+        assert!(plane.is_analytic());
+        assert!(!bezier.is_analytic());
+
+        let composite = CompositeMirror {
+            mirrors: vec![Box::new(plane), Box::new(bezier)],
+        };
+        assert!(!composite.is_analytic());
+    }
+
+    #[test]
+    fn test_validate() {
+        let point = Point::<f32, DIM>::from_slice(&complete_with_0(vec![1.0, 2.0]));
+        let other = Point::<f32, DIM>::from_slice(&complete_with_0(vec![3.0, 4.0]));
+
+        assert!(PlaneMirror::new([point, other]).validate().is_ok());
+        assert!(PlaneMirror::new([point, point]).validate().is_err());
+        assert!(SphereMirror::new(point, 1.0).validate().is_ok());
+        assert!(SphereMirror::new(point, -1.0).validate().is_err());
+        assert!(SphereMirror::new(point, f32::NAN).validate().is_err());
+    }
 
     #[test]
     fn test_sphere_mirror_from_json() {
@@ -198,7 +1050,7 @@ mod tests {
             "radius": 4.0
         });
 
-        let mirror = SphereMirror::from_json(&json);
+        let mirror = SphereMirror::from_json(&json).unwrap();
 
         assert_eq!(
             mirror.center,
@@ -208,7 +1060,60 @@ mod tests {
     }
 
     #[test]
-    fn test_sphere_mirror_reflect() {}
+    fn test_sphere_mirror_reflect() {
+        let mirror = SphereMirror::new(Point::origin(), 2.0);
+
+        // from the outside, the ray bounces back on the near side, and the far side comes next
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![-5.0, 0.0])),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![1.0, 0.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+        let reflections = mirror.reflect(ray);
+        assert_eq!(reflections.len(), 2);
+        assert!((reflections[0].0 - 3.0).abs() < 1e-6);
+        assert!((reflections[1].0 - 7.0).abs() < 1e-6);
+        for (_, matrix) in reflections {
+            let reflected = matrix.into_inner() * ray.direction.into_inner();
+            assert!((reflected + ray.direction.into_inner()).norm() < 1e-6);
+        }
+
+        // from the inside, only the far side is in front of the ray
+        let ray = Ray {
+            origin: Point::origin(),
+            direction: ray.direction,
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+        let reflections = mirror.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 2.0).abs() < 1e-6);
+
+        // the ray passes next to the sphere
+        let ray = Ray {
+            origin: Point::<f32, DIM>::from_slice(&complete_with_0(vec![-5.0, 3.0])),
+            direction: ray.direction,
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+        assert!(mirror.reflect(ray).is_empty());
+    }
+
+    #[test]
+    fn test_sphere_mirror_reflect_from_center() {
+        let mirror = SphereMirror::new([1.0, -2.0].into(), 3.0);
+
+        for direction in [[1.0, 0.0], [0.3, -0.7], [-1.0, 1e-4]] {
+            let ray = ray_from([1.0, -2.0], direction);
+            let reflected = mirror.reflect_ray(&ray).unwrap();
+
+            assert!(((reflected.origin - ray.origin).norm() - 3.0).abs() < 1e-6);
+            assert!((reflected.direction.into_inner() + ray.direction.into_inner()).norm() < 1e-6);
+            // back along the line it came from
+            assert!(reflected.distance_to(&ray.origin) < 1e-5);
+        }
+    }
 
     #[test]
     fn test_composite_mirror_from_json() {
@@ -229,11 +1134,197 @@ mod tests {
             ]
         });
 
-        let mirror = CompositeMirror::from_json(&json);
+        let mirror = CompositeMirror::from_json(&json).unwrap();
 
         assert_eq!(mirror.mirrors.len(), 2);
         //check the first is a plane mirror
         assert_eq!(mirror.mirrors[0].get_type(), "plane");
         assert_eq!(mirror.mirrors[1].get_type(), "sphere");
     }
+
+    #[test]
+    fn test_composite_mirror_is_on_boundary() {
+        let composite = CompositeMirror {
+            mirrors: vec![
+                Box::new(SegmentMirror::new([0.0, 0.0].into(), [1.0, 0.0].into())),
+                Box::new(SphereMirror::new([3.0, 0.0].into(), 1.0)),
+            ],
+        };
+
+        assert!(composite.is_on_boundary(&[1.0, 0.0].into(), 1e-4));
+        assert!(!composite.is_on_boundary(&[0.5, 0.0].into(), 1e-4));
+        assert!(!composite.is_on_boundary(&[2.0, 0.0].into(), 1e-4));
+    }
+
+    #[test]
+    fn test_composite_mirror_reflect_on_seam() {
+        let bottom = SegmentMirror::new([0.0, 0.0].into(), [4.0, 0.0].into());
+        let slope = SegmentMirror::new([4.0, 0.0].into(), [0.0, 1.0].into());
+        let composite = CompositeMirror {
+            mirrors: vec![Box::new(bottom), Box::new(slope)],
+        };
+        // straight at the vertex, facing the slope more
+        let ray = ray_from([5.0, 2.0], [-1.0, -2.0]);
+
+        let reflections = composite.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+        assert_eq!(reflections[0].1, slope.reflect(ray)[0].1);
+    }
+
+    #[test]
+    fn test_composite_mirror_reflectivity_at() {
+        let composite = CompositeMirror {
+            mirrors: vec![
+                Box::new(
+                    SegmentMirror::new([0.0, 0.0].into(), [1.0, 0.0].into()).with_reflectivity(0.5),
+                ),
+                Box::new(
+                    SegmentMirror::new([1.0, 0.0].into(), [2.0, 0.0].into()).with_reflectivity(0.8),
+                ),
+            ],
+        };
+
+        assert_eq!(composite.reflectivity_at(&[0.5, 0.0].into()), 0.5);
+        assert_eq!(composite.reflectivity_at(&[1.5, 0.0].into()), 0.8);
+        // off every part, the mean of both
+        assert!((composite.reflectivity() - 0.65).abs() < 1e-6);
+        assert!((composite.reflectivity_at(&[5.0, 5.0].into()) - 0.65).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_composite_mirror_polarization() {
+        let coated = |s, retardance| {
+            Box::new(
+                AnisotropicMirror::new(Box::new(SphereMirror::new([0.0, 0.0].into(), 1.0)), s, 1.0)
+                    .with_retardance(retardance),
+            )
+        };
+        let composite = CompositeMirror {
+            mirrors: vec![coated(0.9, 0.5), coated(0.7, 1.0)],
+        };
+
+        // 0.925 and 0.775 for each part
+        assert!((composite.polarized_reflectivity(Polarization::S, 0.5) - 0.85).abs() < 1e-6);
+        assert_eq!(composite.retardance(0.5), 0.375);
+
+        let empty = CompositeMirror { mirrors: vec![] };
+        assert_eq!(empty.polarized_reflectivity(Polarization::S, 0.5), 1.0);
+        assert_eq!(empty.retardance(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_facing_normal_both_sides() {
+        let point = |x: f32, y: f32| Point::<f32, DIM>::from_slice(&complete_with_0(vec![x, y]));
+        let ray = |origin: Point<f32, DIM>, y: f32| Ray {
+            origin,
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![0.3, y]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        let mirrors: Vec<Box<dyn Mirror>> = vec![
+            Box::new(PlaneMirror::new([point(0.0, 0.0), point(1.0, 0.0)])),
+            Box::new(SphereMirror::new(point(0.0, 0.0), 1.0)),
+            Box::new(BezierMirror::new(vec![
+                point(-1.0, 0.0),
+                point(0.0, 1.0),
+                point(1.0, 0.0),
+            ])),
+        ];
+        // starting points above and below each mirror, the sphere being hit from inside
+        let starts = [
+            (point(0.0, 2.0), point(0.0, -2.0)),
+            (point(0.0, 3.0), point(0.0, 0.0)),
+            (point(0.0, 2.0), point(0.0, -2.0)),
+        ];
+
+        for (mirror, (above, below)) in mirrors.iter().zip(starts) {
+            for incoming in [ray(above, -1.0), ray(below, 1.0)] {
+                let normal = mirror.facing_normal(&incoming).unwrap();
+                let outgoing = mirror.reflect_ray(&incoming).unwrap();
+                let direction = incoming.direction.into_inner();
+
+                assert!(normal.dot(&direction) < 0.0, "{}", mirror.get_type());
+                // the ray comes back on its side, with the same angle to the normal
+                let expected = direction - 2.0 * direction.dot(&normal) * normal.into_inner();
+                assert!(
+                    (outgoing.direction.into_inner() - expected).norm() < 1e-5,
+                    "{}",
+                    mirror.get_type()
+                );
+                assert!(
+                    outgoing.direction.dot(&normal) > 0.0,
+                    "{}",
+                    mirror.get_type()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reflections_spill() {
+        let point = |x: f32, y: f32| Point::<f32, DIM>::from_slice(&complete_with_0(vec![x, y]));
+        let plane = |x: f32| PlaneMirror::new([point(x, 0.0), point(x, 1.0)]);
+        let ray = Ray {
+            origin: point(-5.0, 0.0),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![1.0, 0.0]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+
+        // the common case fits inline
+        let reflections = SphereMirror::new(Point::origin(), 2.0).reflect(ray);
+        assert_eq!(reflections.len(), 2);
+        assert!(!reflections.spilled());
+
+        // more hits than that still come back complete and sorted
+        let composite = CompositeMirror {
+            mirrors: vec![
+                Box::new(plane(3.0)),
+                Box::new(plane(1.0)),
+                Box::new(plane(2.0)),
+            ],
+        };
+        let distances = composite
+            .reflect(ray)
+            .iter()
+            .map(|(distance, _)| *distance)
+            .collect::<Vec<_>>();
+        assert_eq!(distances, vec![6.0, 7.0, 8.0]);
+    }
+
+    #[test]
+    fn test_degenerate_geometry_gives_finite_distances() {
+        let point = |x: f32, y: f32| Point::<f32, DIM>::from_slice(&complete_with_0(vec![x, y]));
+        let ray = Ray {
+            origin: point(0.0, 0.0),
+            direction: Unit::new_normalize(SVector::from_vec(complete_with_0(vec![1.0, 1e-6]))),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        };
+        let degenerate = || -> Vec<Box<dyn Mirror>> {
+            vec![
+                // so far and so close to parallel that the distance overflows
+                Box::new(PlaneMirror::new([point(0.0, 1e38), point(1.0, 1e38)])),
+                Box::new(SphereMirror::new(point(10.0, 0.0), f32::INFINITY)),
+                Box::new(SphereMirror::new(point(f32::NAN, 0.0), 1.0)),
+                Box::new(BezierMirror::new(vec![
+                    point(2.0, -1.0),
+                    point(f32::INFINITY, 0.0),
+                    point(2.0, 1.0),
+                ])),
+            ]
+        };
+
+        let mut mirrors = degenerate();
+        mirrors.push(Box::new(CompositeMirror {
+            mirrors: degenerate(),
+        }));
+        for mirror in mirrors {
+            assert!(mirror
+                .reflect(ray)
+                .iter()
+                .all(|(distance, _)| distance.is_finite() && *distance > 0.0));
+        }
+    }
 }
@@ -0,0 +1,253 @@
+use nalgebra::{Point, SMatrix, SVector, Unit};
+use smallvec::smallvec;
+
+use crate::{
+    analysis,
+    error::MirrorError,
+    mirror::{degenerate, householder_matrix, is_valid_distance, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Maximum number of times the local plane is refitted around the latest hit.
+const MAX_ITERATIONS: usize = 16;
+/// Below this movement of the hit between two fits, relative to the radius, the fit settled.
+const CONVERGENCE: f32 = 1e-6;
+
+/// A surface known through a cloud of points on it, e.g. scanned data, reflecting through
+/// moving least squares.
+///
+/// Around where a ray reaches the cloud, a plane is fitted to the points, each weighted by a
+/// gaussian of their distance to the estimated hit, of standard deviation `radius`. The ray is
+/// intersected with that plane, and the plane refitted around the new hit until it settles,
+/// the final plane giving the normal. Rays only hit the surface where it is covered by the
+/// cloud, within `radius` of one of its points, and only the first approach of the ray to the
+/// cloud is considered.
+pub struct MlsMirror {
+    points: Vec<Point<f32, DIM>>,
+    radius: f32,
+    reflectivity: f32,
+}
+
+impl Mirror for MlsMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        // the first point of the cloud the ray passes near
+        let Some(start) = self
+            .points
+            .iter()
+            .filter(|point| (*point - ray.origin).dot(&ray.direction) > 0.0)
+            .filter(|point| ray.distance_to(point) <= self.radius)
+            .min_by(|a, b| {
+                let along = |point: &Point<f32, DIM>| (point - ray.origin).dot(&ray.direction);
+                along(a).total_cmp(&along(b))
+            })
+        else {
+            return Reflections::new();
+        };
+
+        let mut hit = *start;
+        let mut fit = None;
+        for _ in 0..MAX_ITERATIONS {
+            let Some((center, normal)) = self.fit_plane(&hit) else {
+                break;
+            };
+            let denominator = normal.dot(&ray.direction);
+            if denominator.abs() < f32::EPSILON {
+                break;
+            }
+
+            let distance = normal.dot(&(center - ray.origin)) / denominator;
+            let next = ray.origin + distance * ray.direction.into_inner();
+            let settled = (next - hit).norm() <= CONVERGENCE * self.radius;
+            (hit, fit) = (next, Some((distance, normal)));
+            if settled {
+                break;
+            }
+        }
+
+        match fit {
+            Some((distance, normal)) if is_valid_distance(distance) && self.covers(&hit) => {
+                smallvec![(distance, householder_matrix(&normal))]
+            }
+            _ => Reflections::new(),
+        }
+    }
+    fn get_type(&self) -> String {
+        "mls".to_string()
+    }
+    /// Intersections are found by refitting the local plane.
+    fn is_analytic(&self) -> bool {
+        false
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.covers(point)
+            && self
+                .fit_plane(point)
+                .is_some_and(|(center, normal)| normal.dot(&(point - center)).abs() <= tolerance)
+    }
+    /// Points of the cloud, taken at regular intervals.
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        if self.points.is_empty() {
+            return vec![];
+        }
+
+        (0..n)
+            .map(|i| self.points[i * self.points.len() / n])
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.points.len() < DIM {
+            degenerate(self, "it has too few points to fit a plane")
+        } else if !self
+            .points
+            .iter()
+            .all(|point| point.iter().all(|c| c.is_finite()))
+        {
+            degenerate(self, "one of its points isn't finite")
+        } else if !(self.radius.is_finite() && self.radius > 0.0) {
+            degenerate(self, "its radius isn't strictly positive")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl MlsMirror {
+    /// `radius` is the size of the neighbourhood each local plane is fitted to, which should
+    /// span a few points of the cloud.
+    pub fn new(points: Vec<Point<f32, DIM>>, radius: f32) -> Self {
+        Self {
+            points,
+            radius,
+            reflectivity: 1.0,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    pub fn points(&self) -> &[Point<f32, DIM>] {
+        &self.points
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Whether `point` is within `radius` of the cloud.
+    fn covers(&self, point: &Point<f32, DIM>) -> bool {
+        self.points
+            .iter()
+            .any(|p| (p - point).norm() <= self.radius)
+    }
+
+    /// The plane fitted to the points around `point`, as a point on it and its normal, or
+    /// `None` if no point is close enough to weigh anything.
+    fn fit_plane(
+        &self,
+        point: &Point<f32, DIM>,
+    ) -> Option<(Point<f32, DIM>, Unit<SVector<f32, DIM>>)> {
+        let weights = self
+            .points
+            .iter()
+            .map(|p| (-(p - point).norm_squared() / (2.0 * self.radius * self.radius)).exp())
+            .collect::<Vec<_>>();
+        let center = analysis::weighted_centroid(&self.points, &weights)?;
+
+        let covariance = self.points.iter().zip(&weights).fold(
+            SMatrix::<f32, DIM, DIM>::zeros(),
+            |covariance, (p, weight)| {
+                let offset = p - center;
+                covariance + *weight * offset * offset.transpose()
+            },
+        );
+        // the normal is the direction the points spread the least along
+        let eigen = covariance.symmetric_eigen();
+        let smallest = eigen.eigenvalues.imin();
+
+        Some((
+            center,
+            Unit::new_normalize(eigen.eigenvectors.column(smallest).into_owned()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mirror::{test_helpers::assert_reflects_like, PlaneMirror},
+        ray::ray_from,
+    };
+
+    #[test]
+    fn test_planar_cloud_reflects_like_a_plane() {
+        let cloud = MlsMirror::new(
+            (-20..=20)
+                .map(|i| Point::from([i as f32 * 0.1, 0.5 + i as f32 * 0.05]))
+                .collect(),
+            0.2,
+        );
+        let plane = PlaneMirror::new([[0.0, 0.5].into(), [2.0, 1.5].into()]);
+
+        assert_reflects_like(
+            &cloud,
+            &plane,
+            &[
+                ray_from([0.0, 3.0], [0.0, -1.0]),
+                ray_from([-1.0, 2.0], [0.3, -1.0]),
+                ray_from([1.0, -1.0], [-0.2, 1.0]),
+            ],
+        );
+
+        // beyond the end of the cloud, there is no surface
+        assert!(cloud.reflect(ray_from([4.0, 5.0], [0.0, -1.0])).is_empty());
+        assert!(cloud.contains_point(&[1.0, 1.0].into(), 1e-4));
+        assert!(!cloud.contains_point(&[1.0, 1.2].into(), 1e-4));
+    }
+
+    #[test]
+    fn test_curved_cloud_normal() {
+        // the lower half of the unit circle
+        let cloud = MlsMirror::new(
+            (0..=60)
+                .map(|i| {
+                    let angle = std::f32::consts::PI * (1.0 + i as f32 / 60.0);
+                    Point::from([angle.cos(), angle.sin()])
+                })
+                .collect(),
+            0.1,
+        );
+
+        // a ray from the center is reflected straight back
+        let ray = ray_from([0.0, 0.0], [0.6, -0.8]);
+        let reflected = cloud.reflect_ray(&ray).unwrap();
+
+        assert!((reflected.origin - Point::from([0.6, -0.8])).norm() < 1e-2);
+        assert!((reflected.direction.into_inner() + ray.direction.into_inner()).norm() < 1e-2);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(
+            MlsMirror::new(vec![[0.0, 0.0].into(), [1.0, 0.0].into()], 0.5)
+                .validate()
+                .is_ok()
+        );
+        assert!(MlsMirror::new(vec![[0.0, 0.0].into()], 0.5)
+            .validate()
+            .is_err());
+        assert!(
+            MlsMirror::new(vec![[0.0, 0.0].into(), [1.0, 0.0].into()], 0.0)
+                .validate()
+                .is_err()
+        );
+    }
+}
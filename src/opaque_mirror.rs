@@ -0,0 +1,114 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    mirror::{Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// Wraps a mirror so it absorbs every ray hitting it instead of reflecting it, like a baffle
+/// or the blades of an aperture. It shadows the mirrors behind it: rays stopped by it end
+/// their trace with [`crate::simulation::Termination::Absorbed`], without reaching them.
+pub struct OpaqueMirror {
+    mirror: Box<dyn Mirror>,
+}
+
+impl Mirror for OpaqueMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.mirror.reflect(ray)
+    }
+    fn get_type(&self) -> String {
+        "opaque".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.mirror.is_analytic()
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror.sample_surface(n)
+    }
+    /// Nothing is reflected.
+    fn reflectivity(&self) -> f32 {
+        0.0
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mirror.retardance(sin2_incidence)
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        self.mirror.validate()
+    }
+}
+
+impl OpaqueMirror {
+    pub fn new(mirror: Box<dyn Mirror>) -> Self {
+        Self { mirror }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        polygon_mirror::PolygonMirror,
+        ray::ray_from,
+        scene::Scene,
+        segment_mirror::SegmentMirror,
+        simulation::{Simulation, Termination},
+    };
+
+    #[test]
+    fn test_shadows_the_mirror_behind() {
+        let floor = || Box::new(SegmentMirror::new([-2.0, 0.0].into(), [2.0, 0.0].into()));
+        let source = ray_from([0.0, 3.0], [0.0, -1.0]);
+
+        let lit = Simulation::new(Scene::new(vec![floor()]));
+        let result = lit.trace(source, 2);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.termination, Termination::Escaped);
+
+        // a small absorbing square between the source and the floor
+        let occluder = PolygonMirror::new(vec![
+            [-0.5, 1.0].into(),
+            [0.5, 1.0].into(),
+            [0.5, 2.0].into(),
+            [-0.5, 2.0].into(),
+        ]);
+        let shadowed = Simulation::new(Scene::new(vec![
+            floor(),
+            Box::new(OpaqueMirror::new(Box::new(occluder))),
+        ]));
+
+        let result = shadowed.trace(source, 2);
+        assert!(result.is_empty());
+        assert_eq!(result.termination, Termination::Absorbed);
+        assert!((result.path[1] - Point::from([0.0, 2.0])).norm() < 1e-5);
+
+        // rays passing next to it still reach the floor
+        let result = shadowed.trace(ray_from([1.0, 3.0], [0.0, -1.0]), 2);
+        assert_eq!(result.len(), 1);
+    }
+}
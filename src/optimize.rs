@@ -0,0 +1,113 @@
+//! Finding where light goes from its endpoints, instead of tracing it forward.
+
+use nalgebra::Point;
+
+use crate::{bezier_mirror::BezierMirror, mirror::sample_parameters, DIM};
+
+/// Number of intervals of the curve used to bracket the stationary points of the path length.
+const SAMPLES: usize = 64;
+/// Maximum number of bisections spent refining a single bracket.
+const MAX_ITERATIONS: usize = 50;
+
+/// The parameter `t` of the point of `mirror` where light going from `source` reflects to
+/// reach `target`, only meaningful in 2D.
+///
+/// By fermat's principle, reflections happen where the length of the path
+/// `source -> mirror(t) -> target` is stationary, i.e. where the tangent of the curve makes
+/// equal angles with both legs. Stationary points where the path goes through the curve
+/// instead of bouncing on it are ignored, and if several reflections are possible, the
+/// shortest path wins. Returns `None` when there is no reflection.
+pub fn reflection_point_on(
+    mirror: &BezierMirror,
+    source: &Point<f32, DIM>,
+    target: &Point<f32, DIM>,
+) -> Option<f32> {
+    let path_length = |t: f32| {
+        (mirror.calculate_point(t) - source).norm() + (target - mirror.calculate_point(t)).norm()
+    };
+    // derivative of the path length along the curve, up to the speed of the parameter
+    let slope = |t: f32| {
+        let point = mirror.calculate_point(t);
+        let legs = (point - source).normalize() + (point - target).normalize();
+        mirror.calculate_tangent(t).dot(&legs)
+    };
+    // both endpoints must be on the same side of the curve for the light to bounce
+    let bounces = |t: f32| {
+        let point = mirror.calculate_point(t);
+        let tangent = mirror.calculate_tangent(t);
+        let side =
+            |p: &Point<f32, DIM>| tangent[0] * (p[1] - point[1]) - tangent[1] * (p[0] - point[0]);
+        side(source) * side(target) > 0.0
+    };
+
+    let ts = sample_parameters(SAMPLES + 1);
+    let mut candidates = vec![];
+
+    for pair in ts.windows(2) {
+        let (mut low, mut high) = (pair[0], pair[1]);
+        let mut slope_low = slope(low);
+
+        if slope_low == 0.0 {
+            candidates.push(low);
+            continue;
+        }
+        if slope_low * slope(high) > 0.0 {
+            continue;
+        }
+
+        for _ in 0..MAX_ITERATIONS {
+            let middle = (low + high) / 2.0;
+            let slope_middle = slope(middle);
+            if slope_middle * slope_low > 0.0 {
+                (low, slope_low) = (middle, slope_middle);
+            } else {
+                high = middle;
+            }
+        }
+
+        candidates.push((low + high) / 2.0);
+    }
+
+    candidates
+        .into_iter()
+        .filter(|t| bounces(*t))
+        .min_by(|a, b| path_length(*a).total_cmp(&path_length(*b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mirror::Mirror, ray::Ray};
+
+    #[test]
+    fn test_flat_mirror_equal_angles() {
+        let mirror = BezierMirror::new(vec![[0.0, 0.0].into(), [4.0, 0.0].into()]);
+        let (source, target) = (Point::from([0.0, 1.0]), Point::from([3.0, 2.0]));
+
+        // equal angles: x / 1 = (3 - x) / 2, so x = 1
+        let t = reflection_point_on(&mirror, &source, &target).unwrap();
+        assert!((t - 0.25).abs() < 1e-5);
+
+        let point = mirror.calculate_point(t);
+        let reflected = mirror
+            .reflect_ray(&Ray::new(source, point - source))
+            .unwrap();
+        assert!((reflected.direction.into_inner() - (target - point).normalize()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_no_reflection() {
+        let mirror = BezierMirror::new(vec![[0.0, 0.0].into(), [4.0, 0.0].into()]);
+
+        // on both sides, the light goes through the curve rather than bouncing on it
+        assert_eq!(
+            reflection_point_on(&mirror, &[1.0, 1.0].into(), &[2.0, -1.0].into()),
+            None
+        );
+        // the reflection would be past the end of the curve
+        assert_eq!(
+            reflection_point_on(&mirror, &[5.0, 1.0].into(), &[7.0, 1.0].into()),
+            None
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use nalgebra::Point;
+
+use crate::{
+    bezier_mirror::BezierMirror,
+    error::MirrorError,
+    json, material,
+    mirror::{degenerate, enclosing_sphere, sort_resolving_seams, Mirror, Reflections},
+    ray::Ray,
+    segment_mirror::SegmentMirror,
+    DIM,
+};
+
+/// A closed polygon whose edges are all reflective, only meaningful in 2D. A ray hitting a
+/// vertex is reflected by the edge it faces the most (see [`sort_resolving_seams`]).
+pub struct PolygonMirror {
+    vertices: Vec<Point<f32, DIM>>,
+    edges: Vec<SegmentMirror>,
+    reflectivity: f32,
+    /// The reflectivity of each edge, in the order of [`PolygonMirror::edges`], overriding
+    /// the one of the whole polygon.
+    reflectivities: Option<Vec<f32>>,
+}
+
+impl Mirror for PolygonMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        sort_resolving_seams(
+            self.edges
+                .iter()
+                .flat_map(|edge| edge.reflect(ray))
+                .collect(),
+            &ray,
+        )
+    }
+    fn get_type(&self) -> String {
+        "polygon".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.edges
+            .iter()
+            .any(|edge| edge.contains_point(point, tolerance))
+    }
+    /// Samples the perimeter at regular intervals, starting from the first vertex.
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        if self.edges.is_empty() {
+            return vec![];
+        }
+        let lengths = self
+            .edges
+            .iter()
+            .map(|edge| (edge.end() - edge.start()).norm())
+            .collect::<Vec<_>>();
+        let perimeter = lengths.iter().sum::<f32>();
+
+        (0..n)
+            .map(|i| {
+                let mut remaining = i as f32 / n as f32 * perimeter;
+                for (edge, length) in self.edges.iter().zip(&lengths) {
+                    if remaining <= *length && *length > 0.0 {
+                        return edge.start() + remaining / length * (edge.end() - edge.start());
+                    }
+                    remaining -= length;
+                }
+                self.vertices[0]
+            })
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    /// The reflectivity of the edge nearest to `point`.
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        let nearest = self
+            .edges
+            .iter()
+            .map(|edge| edge.distance_to(point))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(index, _)| index);
+
+        self.edge_reflectivity(nearest)
+    }
+    /// The vertices, where an edge ends and the next one starts.
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.edges
+            .iter()
+            .any(|edge| edge.is_on_boundary(point, tolerance))
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        let vertices = self.vertices.iter().map(|vertex| (*vertex, 0.0));
+        enclosing_sphere(&vertices.collect::<Vec<_>>())
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.vertices.len() < 3 {
+            degenerate(self, "it has less than 3 vertices")
+        } else if self.reflectivities.as_ref().is_some_and(|reflectivities| {
+            reflectivities.len() != self.edges.len()
+                || !reflectivities.iter().all(|r| (0.0..=1.0).contains(r))
+        }) {
+            degenerate(
+                self,
+                "it doesn't have one reflectivity between 0 and 1 per edge",
+            )
+        } else {
+            self.edges.iter().try_for_each(|edge| edge.validate())
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        let mut json = serde_json::json!({
+            "type": self.get_type(),
+            "vertices": json::from_points(&self.vertices),
+            "reflectivity": self.reflectivity,
+        });
+        if let Some(reflectivities) = &self.reflectivities {
+            json["reflectivities"] = serde_json::json!(reflectivities);
+        }
+
+        Ok(json)
+    }
+}
+
+impl PolygonMirror {
+    /// Creates a polygon going through `vertices` in order, the last one being joined to the first.
+    pub fn new(vertices: Vec<Point<f32, DIM>>) -> Self {
+        let edges = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(start, end)| SegmentMirror::new(*start, *end))
+            .collect();
+
+        Self {
+            vertices,
+            edges,
+            reflectivity: 1.0,
+            reflectivities: None,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    /// Gives each edge its own reflectivity, in the order of [`PolygonMirror::edges`].
+    pub fn with_reflectivities(self, reflectivities: Vec<f32>) -> Self {
+        Self {
+            reflectivities: Some(reflectivities),
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "vertices": [
+                [1.0, 2.0],
+                [3.0, 4.0],
+                [5.0, 6.0],
+                ...
+            ],
+            "material": "silver", // or "reflectivity": 0.97, optional
+            "reflectivities": [0.97, 0.5, 0.97, ...] // optional, one per edge
+        }
+         */
+        let vertices = json::points(json, "vertices")?;
+        let mut polygon =
+            Self::new(vertices).with_reflectivity(material::reflectivity_from_json(json)?);
+
+        if !json["reflectivities"].is_null() {
+            let reflectivities = json::array(json, "reflectivities")?
+                .iter()
+                .map(|value| value.as_f64().map(|value| value as f32))
+                .collect::<Option<Vec<_>>>()
+                .filter(|reflectivities| reflectivities.len() == polygon.edges.len())
+                .ok_or_else(|| MirrorError::InvalidField {
+                    field: "reflectivities".to_string(),
+                    expected: "one number per edge",
+                })?;
+            polygon = polygon.with_reflectivities(reflectivities);
+        }
+
+        Ok(polygon)
+    }
+
+    pub fn vertices(&self) -> &[Point<f32, DIM>] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> &[SegmentMirror] {
+        &self.edges
+    }
+
+    /// The reflectivity of the edge at `index` in [`PolygonMirror::edges`].
+    pub fn edge_reflectivity(&self, index: usize) -> f32 {
+        self.reflectivities
+            .as_ref()
+            .and_then(|reflectivities| reflectivities.get(index))
+            .copied()
+            .unwrap_or(self.reflectivity)
+    }
+
+    /// Rounds the corners of the polygon into quadratic bezier fillets, giving a closed chain
+    /// of curves. `smoothing` is the fraction of each edge eaten by the fillets at both of its
+    /// ends, clamped to `[0, 0.5]`: with 0 the chain is made of the straight edges only.
+    /// Fillets take the reflectivity of the edge they end.
+    pub fn to_bezier_chain(&self, smoothing: f32) -> Vec<BezierMirror> {
+        let smoothing = smoothing.clamp(0.0, 0.5);
+        // the points where each edge stops being straight, at its start and its end
+        let cuts = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let direction = edge.end() - edge.start();
+                (
+                    edge.start() + smoothing * direction,
+                    edge.end() - smoothing * direction,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut chain = vec![];
+        for (i, (start, end)) in cuts.iter().enumerate() {
+            let reflectivity = self.edge_reflectivity(i);
+            if start != end {
+                chain.push(BezierMirror::new(vec![*start, *end]).with_reflectivity(reflectivity));
+            }
+
+            if smoothing > 0.0 {
+                let (next_start, _) = cuts[(i + 1) % cuts.len()];
+                chain.push(
+                    BezierMirror::new(vec![*end, self.edges[i].end(), next_start])
+                        .with_reflectivity(reflectivity),
+                );
+            }
+        }
+
+        chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        export,
+        mirror::mirror_from_json,
+        ray::ray_from,
+        scene::Scene,
+        simulation::{Simulation, Termination},
+    };
+
+    fn unit_square() -> PolygonMirror {
+        PolygonMirror::new(vec![
+            [0.0, 0.0].into(),
+            [1.0, 0.0].into(),
+            [1.0, 1.0].into(),
+            [0.0, 1.0].into(),
+        ])
+    }
+
+    #[test]
+    fn test_edges() {
+        let square = unit_square();
+
+        assert_eq!(square.edges().len(), 4);
+        assert_eq!(square.edges()[3].start(), Point::from([0.0, 1.0]));
+        assert_eq!(square.edges()[3].end(), Point::from([0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_reflect() {
+        let square = unit_square();
+
+        // from the inside, only the right edge is in front of the ray
+        let reflections = square.reflect(ray_from([0.5, 0.5], [1.0, 0.0]));
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 0.5).abs() < 1e-6);
+
+        // from the outside, both the left and right edges are hit
+        let reflections = square.reflect(ray_from([-1.0, 0.5], [1.0, 0.0]));
+        assert_eq!(reflections.len(), 2);
+    }
+
+    #[test]
+    fn test_reflect_on_vertex() {
+        let triangle = PolygonMirror::new(
+            [[0.0, 0.0], [4.0, 0.0], [0.0, 1.0]]
+                .map(Point::from)
+                .to_vec(),
+        );
+        // straight at the vertex shared by the bottom edge and the hypotenuse, which the ray
+        // faces more
+        let ray = ray_from([5.0, 2.0], [-1.0, -2.0]);
+
+        let reflections = triangle.reflect(ray);
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 5f32.sqrt()).abs() < 1e-5);
+        assert_eq!(triangle.reflect(ray), reflections);
+
+        let expected = triangle.edges()[1].reflect(ray);
+        assert_eq!(reflections[0].1, expected[0].1);
+        let reflected = triangle.reflect_ray(&ray).unwrap();
+        // bounced back up, away from the triangle
+        assert!(reflected.direction[0] > 0.0);
+
+        assert!(triangle.is_on_boundary(&[4.0, 0.0].into(), 1e-4));
+        assert!(!triangle.is_on_boundary(&[2.0, 0.0].into(), 1e-4));
+    }
+
+    #[test]
+    fn test_sample_surface() {
+        let square = unit_square();
+
+        let samples = square.sample_surface(8);
+
+        assert_eq!(samples.len(), 8);
+        assert!((samples[1] - Point::from([0.5, 0.0])).norm() < 1e-6);
+        assert!((samples[5] - Point::from([0.5, 1.0])).norm() < 1e-6);
+        assert!(samples.iter().all(|p| square.contains_point(p, 1e-6)));
+    }
+
+    #[test]
+    fn test_validate_without_vertices() {
+        let empty =
+            mirror_from_json(&serde_json::json!({ "type": "polygon", "vertices": [] })).unwrap();
+        assert!(empty.sample_surface(8).is_empty());
+        // nothing to come close to
+        assert_eq!(
+            empty.miss_distance(&ray_from([0.0, 1.0], [1.0, 0.0])),
+            f32::INFINITY
+        );
+
+        // the other mirror is compared with it, which mustn't stop it from being reported
+        let scene = Scene::new(vec![empty, Box::new(unit_square())]);
+        let errors = scene.validate().unwrap_err();
+        assert!(matches!(errors[..], [MirrorError::DegenerateMirror { .. }]));
+        let _ = export::to_svg(&scene, &[]);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = serde_json::json!({
+            "vertices": [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+        });
+
+        let polygon = PolygonMirror::from_json(&json).unwrap();
+
+        assert_eq!(polygon.vertices(), unit_square().vertices());
+        assert!(polygon.contains_point(&[1.0, 0.5].into(), 1e-6));
+        assert!(!polygon.contains_point(&[0.5, 0.5].into(), 1e-6));
+    }
+
+    #[test]
+    fn test_per_edge_reflectivity() {
+        // the right edge absorbs half of the light, the left one a tenth
+        let square = unit_square().with_reflectivities(vec![1.0, 0.5, 1.0, 0.9]);
+        assert_eq!(square.reflectivity_at(&[1.0, 0.5].into()), 0.5);
+        assert_eq!(square.reflectivity_at(&[0.0, 0.5].into()), 0.9);
+        assert_eq!(square.reflectivity_at(&[0.5, 0.0].into()), 1.0);
+
+        let simulation = Simulation::new(Scene::new(vec![Box::new(square)]));
+        let right = simulation.trace_adaptive(ray_from([0.5, 0.5], [1.0, 0.0]), 0.6, 10);
+        let left = simulation.trace_adaptive(ray_from([0.5, 0.5], [-1.0, 0.0]), 0.6, 10);
+
+        assert_eq!(right.termination, Termination::Dimmed);
+        assert_eq!(right.len(), 1);
+        // 0.9, then 0.45 once back on the right edge
+        assert_eq!(left.termination, Termination::Dimmed);
+        assert_eq!(left.len(), 2);
+    }
+
+    #[test]
+    fn test_per_edge_reflectivity_from_json() {
+        let json = serde_json::json!({
+            "vertices": [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            "reflectivities": [1.0, 0.5, 1.0, 0.9],
+        });
+
+        let polygon = PolygonMirror::from_json(&json).unwrap();
+        assert_eq!(polygon.edge_reflectivity(1), 0.5);
+        assert_eq!(polygon.edge_reflectivity(3), 0.9);
+        assert!(polygon.validate().is_ok());
+        assert_eq!(
+            PolygonMirror::from_json(&polygon.to_json().unwrap())
+                .unwrap()
+                .edge_reflectivity(1),
+            0.5
+        );
+
+        let mut wrong_count = json.clone();
+        wrong_count["reflectivities"] = serde_json::json!([1.0, 0.5]);
+        assert!(PolygonMirror::from_json(&wrong_count).is_err());
+        assert!(unit_square()
+            .with_reflectivities(vec![1.0, 2.0, 1.0, 1.0])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_to_bezier_chain_without_smoothing() {
+        let square = unit_square();
+
+        let chain = square.to_bezier_chain(0.0);
+
+        assert_eq!(chain.len(), 4);
+        for (bezier, edge) in chain.iter().zip(square.edges()) {
+            assert_eq!(bezier.control_points(), [edge.start(), edge.end()]);
+        }
+    }
+
+    #[test]
+    fn test_to_bezier_chain_rounds_corners() {
+        let square = unit_square();
+
+        let chain = square.to_bezier_chain(0.25);
+
+        // a straight part and a fillet per corner
+        assert_eq!(chain.len(), 8);
+        assert_eq!(
+            chain[1].control_points(),
+            [[0.75, 0.0].into(), [1.0, 0.0].into(), [1.0, 0.25].into()]
+        );
+        // the chain is closed and continuous
+        for (i, bezier) in chain.iter().enumerate() {
+            let next = &chain[(i + 1) % chain.len()];
+            assert_eq!(bezier.calculate_point(1.0), next.calculate_point(0.0));
+        }
+        // the corner itself is cut
+        assert!(!chain
+            .iter()
+            .any(|bezier| bezier.contains_point(&[1.0, 0.0].into(), 1e-3)));
+    }
+}
@@ -0,0 +1,307 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    graph_mirror::GraphMirror,
+    json, material,
+    math::solve_natural_tridiagonal,
+    mirror::{degenerate, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// A surface known through its height `z` sampled at a few `x`, like a profile measured by
+/// metrology, with a natural cubic spline interpolating between the samples. Only
+/// meaningful in 2D, where `z` is the second axis.
+///
+/// The interpolated profile is reflected on like a [`GraphMirror`], over the range of the
+/// samples.
+pub struct ProfileMirror {
+    /// The samples, sorted by `x`.
+    samples: Vec<(f32, f32)>,
+    graph: GraphMirror,
+}
+
+impl Mirror for ProfileMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.graph.reflect(ray)
+    }
+    fn get_type(&self) -> String {
+        "profile".to_string()
+    }
+    /// Intersections are found by bisection.
+    fn is_analytic(&self) -> bool {
+        false
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.graph.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.graph.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.graph.reflectivity()
+    }
+    /// The range of x covered by the samples.
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.graph.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.graph.is_on_boundary(point, tolerance)
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.samples.len() < 2 {
+            degenerate(self, "it has less than 2 samples")
+        } else if !self
+            .samples
+            .iter()
+            .all(|(x, z)| x.is_finite() && z.is_finite())
+        {
+            degenerate(self, "one of its samples isn't finite")
+        } else if self.samples.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            degenerate(self, "two of its samples have the same x")
+        } else {
+            self.graph.validate()
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        let (xs, zs): (Vec<_>, Vec<_>) = self.samples.iter().copied().unzip();
+
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "x": xs,
+            "z": zs,
+            "reflectivity": self.reflectivity(),
+        }))
+    }
+}
+
+impl ProfileMirror {
+    /// Interpolates the profile going through `samples`, as `(x, z)`, in any order.
+    pub fn new(mut samples: Vec<(f32, f32)>) -> Self {
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let spline = Spline::new(&samples);
+        let derivative = spline.clone();
+        let (start, end) = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => (0.0, 0.0),
+        };
+
+        Self {
+            graph: GraphMirror::new(
+                move |x| spline.height(x),
+                move |x| derivative.slope(x),
+                start,
+                end,
+            ),
+            samples,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            graph: self.graph.with_reflectivity(reflectivity),
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "x": [0.0, 1.0, 2.0, ...],
+            "z": [0.0, 0.01, 0.04, ...], // one height per x
+            "material": "silver" // or "reflectivity": 0.97, optional
+        }
+         */
+        let numbers = |field: &str| {
+            json::array(json, field)?
+                .iter()
+                .map(|value| value.as_f64().map(|value| value as f32))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| MirrorError::InvalidField {
+                    field: field.to_string(),
+                    expected: "an array of numbers",
+                })
+        };
+        let (xs, zs) = (numbers("x")?, numbers("z")?);
+        if xs.len() != zs.len() {
+            return Err(MirrorError::InvalidField {
+                field: "z".to_string(),
+                expected: "one height per x",
+            });
+        }
+
+        Ok(Self::new(xs.into_iter().zip(zs).collect())
+            .with_reflectivity(material::reflectivity_from_json(json)?))
+    }
+
+    /// The samples the profile goes through, sorted by x.
+    pub fn samples(&self) -> &[(f32, f32)] {
+        &self.samples
+    }
+
+    /// The interpolated height of the profile at `x`.
+    pub fn height(&self, x: f32) -> f32 {
+        self.graph.point_at(x)[1]
+    }
+}
+
+/// A natural cubic spline `z(x)` through samples sorted by x, unevenly spaced.
+#[derive(Clone)]
+struct Spline {
+    xs: Vec<f32>,
+    zs: Vec<f32>,
+    /// The second derivative at each sample, vanishing at both ends.
+    second: Vec<f32>,
+}
+
+impl Spline {
+    /// Continuity of the curvature at every interior sample gives the tridiagonal system
+    /// `h[i - 1] m[i - 1] + 2 (h[i - 1] + h[i]) m[i] + h[i] m[i + 1] = 6 (s[i] - s[i - 1])`,
+    /// `h` being the widths of the intervals and `s` their slopes.
+    fn new(samples: &[(f32, f32)]) -> Self {
+        let (xs, zs): (Vec<_>, Vec<_>) = samples.iter().copied().unzip();
+        let n = xs.len();
+        let widths = xs.windows(2).map(|x| x[1] - x[0]).collect::<Vec<_>>();
+        let slopes = (0..n.saturating_sub(1))
+            .map(|i| (zs[i + 1] - zs[i]) / widths[i])
+            .collect::<Vec<_>>();
+        // the first and last rows are unused, the ends having no curvature
+        let diagonal = (0..n)
+            .map(|i| {
+                if i == 0 || i == n - 1 {
+                    1.0
+                } else {
+                    2.0 * (widths[i - 1] + widths[i])
+                }
+            })
+            .collect();
+        let rhs = (0..n)
+            .map(|i| {
+                if i == 0 || i == n - 1 {
+                    0.0
+                } else {
+                    6.0 * (slopes[i] - slopes[i - 1])
+                }
+            })
+            .collect();
+        let second = solve_natural_tridiagonal(diagonal, &widths, rhs);
+
+        Self { xs, zs, second }
+    }
+
+    /// The interval containing `x`, as the index of its first sample and its width, the
+    /// first or last one beyond the samples.
+    fn interval(&self, x: f32) -> Option<(usize, f32)> {
+        let last = self.xs.len().checked_sub(2)?;
+        let i = self
+            .xs
+            .partition_point(|sample| *sample <= x)
+            .clamp(1, last + 1)
+            - 1;
+        Some((i, self.xs[i + 1] - self.xs[i]))
+    }
+
+    fn height(&self, x: f32) -> f32 {
+        let Some((i, width)) = self.interval(x) else {
+            return self.zs.first().copied().unwrap_or(0.0);
+        };
+        let (before, after) = (x - self.xs[i], self.xs[i + 1] - x);
+
+        (self.second[i] * after.powi(3) + self.second[i + 1] * before.powi(3)) / (6.0 * width)
+            + (self.zs[i] / width - self.second[i] * width / 6.0) * after
+            + (self.zs[i + 1] / width - self.second[i + 1] * width / 6.0) * before
+    }
+
+    fn slope(&self, x: f32) -> f32 {
+        let Some((i, width)) = self.interval(x) else {
+            return 0.0;
+        };
+        let (before, after) = (x - self.xs[i], self.xs[i + 1] - x);
+
+        (self.second[i + 1] * before.powi(2) - self.second[i] * after.powi(2)) / (2.0 * width)
+            + (self.zs[i + 1] - self.zs[i]) / width
+            - (self.second[i + 1] - self.second[i]) * width / 6.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::least_squares_focus,
+        mirror::{test_helpers::assert_reflects_like, PlaneMirror},
+        ray::ray_from,
+    };
+
+    #[test]
+    fn test_flat_profile_reflects_like_a_plane() {
+        // a tilted line, sampled unevenly and out of order
+        let profile = ProfileMirror::new(vec![(0.0, 0.5), (3.0, 2.0), (0.5, 0.75), (2.0, 1.5)]);
+        let plane = PlaneMirror::new([[0.0, 0.5].into(), [2.0, 1.5].into()]);
+        assert!(profile.validate().is_ok());
+
+        assert_reflects_like(
+            &profile,
+            &plane,
+            &[
+                ray_from([1.0, 4.0], [0.0, -1.0]),
+                ray_from([0.2, 3.0], [0.4, -1.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parabolic_profile_focuses() {
+        // z = x^2 / 4, whose focus is at (0, 1)
+        let profile = ProfileMirror::new(
+            (-10..=10)
+                .map(|i| {
+                    let x = i as f32 * 0.2;
+                    (x, x * x / 4.0)
+                })
+                .collect(),
+        );
+        assert!((profile.height(0.3) - 0.0225).abs() < 1e-3);
+
+        let reflected = (-8..=8)
+            .map(|i| {
+                let ray = ray_from([i as f32 * 0.2 + 0.05, 3.0], [0.0, -1.0]);
+                profile.reflect_ray(&ray).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let focus = least_squares_focus(&reflected).unwrap();
+        assert!((focus - Point::from([0.0, 1.0])).norm() < 2e-2);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = serde_json::json!({
+            "x": [0.0, 1.0, 2.0, 3.0],
+            "z": [0.0, 0.5, 0.0, 0.5],
+            "reflectivity": 0.9,
+        });
+
+        let profile = ProfileMirror::from_json(&json).unwrap();
+        assert_eq!(profile.get_type(), "profile");
+        assert_eq!(profile.reflectivity(), 0.9);
+        for (x, z) in profile.samples() {
+            assert!((profile.height(*x) - z).abs() < 1e-6);
+        }
+        assert_eq!(
+            ProfileMirror::from_json(&profile.to_json().unwrap())
+                .unwrap()
+                .samples(),
+            profile.samples()
+        );
+
+        let mut mismatched = json.clone();
+        mismatched["z"] = serde_json::json!([0.0, 0.5]);
+        assert!(ProfileMirror::from_json(&mismatched).is_err());
+        assert!(ProfileMirror::new(vec![(1.0, 0.0), (1.0, 1.0)])
+            .validate()
+            .is_err());
+    }
+}
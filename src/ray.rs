@@ -1,7 +1,251 @@
-use crate::DIM;
+use std::io::BufRead;
+
+use crate::{error::MirrorError, json, DIM};
 use nalgebra::{Point, SVector, Unit};
+use oorandom::Rand32;
+
+/// Wavelength of rays which aren't given one, in nanometers (green light).
+pub const DEFAULT_WAVELENGTH: f32 = 550.0;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Ray {
     pub origin: Point<f32, DIM>,
     pub direction: Unit<SVector<f32, DIM>>,
+    /// In nanometers. Only dispersive mirrors depend on it, and it is kept across reflections.
+    pub wavelength: f32,
+    /// When the ray leaves its origin, measured as the distance light travels in vacuum
+    /// meanwhile, so every reflection adds the distance to the mirror. Only moving mirrors
+    /// like [`crate::keyframed_mirror::KeyframedMirror`] depend on it.
+    pub time: f32,
+}
+
+impl Ray {
+    /// Creates a ray of [`DEFAULT_WAVELENGTH`], normalizing `direction`.
+    pub fn new(origin: Point<f32, DIM>, direction: SVector<f32, DIM>) -> Self {
+        Self {
+            origin,
+            direction: Unit::new_normalize(direction),
+            wavelength: DEFAULT_WAVELENGTH,
+            time: 0.0,
+        }
+    }
+
+    pub fn with_wavelength(self, wavelength: f32) -> Self {
+        Self { wavelength, ..self }
+    }
+
+    pub fn with_time(self, time: f32) -> Self {
+        Self { time, ..self }
+    }
+
+    /// `count` rays leaving `origin` in random directions at most `half_angle` radians away
+    /// from `center_dir`, uniformly distributed in angle, for monte carlo estimations.
+    /// The same seeded `rng` always gives the same rays. Only meaningful in 2D.
+    pub fn jittered_fan(
+        origin: Point<f32, DIM>,
+        center_dir: SVector<f32, DIM>,
+        half_angle: f32,
+        count: usize,
+        rng: &mut Rand32,
+    ) -> Vec<Self> {
+        (0..count)
+            .map(|_| {
+                let angle = (2.0 * rng.rand_float() - 1.0) * half_angle;
+                let (sin, cos) = angle.sin_cos();
+                let direction = SVector::from_fn(|axis, _| match axis {
+                    0 => cos * center_dir[0] - sin * center_dir[1],
+                    1 => sin * center_dir[0] + cos * center_dir[1],
+                    _ => 0.0,
+                });
+
+                Self::new(origin, direction)
+            })
+            .collect()
+    }
+
+    /// The distance from `point` to the ray, which only extends forward from its origin.
+    pub fn distance_to(&self, point: &Point<f32, DIM>) -> f32 {
+        let offset = point - self.origin;
+        let along = offset.dot(&self.direction).max(0.0);
+        (offset - along * self.direction.into_inner()).norm()
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "origin": [1.0, 2.0],
+            "direction": [0.0, 1.0], // normalized
+            // or instead of the origin and direction, a point the ray aims at:
+            // "from": [1.0, 2.0],
+            // "to": [1.0, 5.0],
+            "wavelength": 650.0 // optional
+        }
+         */
+        let (origin, direction, field) = if json["from"].is_null() {
+            let origin = json::point(json, "origin")?;
+            (origin, json::point(json, "direction")?.coords, "direction")
+        } else {
+            let from = json::point(json, "from")?;
+            (from, json::point(json, "to")? - from, "to")
+        };
+        let norm = direction.norm();
+        if !(norm > f32::EPSILON && norm.is_finite()) {
+            return Err(MirrorError::InvalidField {
+                field: field.to_string(),
+                expected: "a non-zero direction",
+            });
+        }
+
+        let ray = Self::new(origin, direction);
+        Ok(match &json["wavelength"] {
+            serde_json::Value::Null => ray,
+            _ => ray.with_wavelength(json::number(json, "wavelength")?),
+        })
+    }
+
+    /// Reads one ray per row of a csv, its `DIM` origin coordinates followed by its `DIM`
+    /// direction coordinates, e.g. `0.0,1.0,1.0,0.0`. Directions are normalized, and blank
+    /// lines and lines starting with `#` are skipped.
+    pub fn many_from_csv(reader: impl BufRead) -> Result<Vec<Self>, MirrorError> {
+        let mut rays = vec![];
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| MirrorError::Io(error.to_string()))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_row = |expected| MirrorError::InvalidField {
+                field: format!("row {}", index + 1),
+                expected,
+            };
+            let values = line
+                .split(',')
+                .map(|value| value.trim().parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid_row("a list of numbers"))?;
+            if values.len() != 2 * DIM {
+                return Err(invalid_row("an origin and a direction"));
+            }
+
+            let direction = SVector::<f32, DIM>::from_column_slice(&values[DIM..]);
+            let norm = direction.norm();
+            if !(norm > f32::EPSILON && norm.is_finite()) {
+                return Err(invalid_row("a non-zero direction"));
+            }
+            rays.push(Self::new(Point::from_slice(&values[..DIM]), direction));
+        }
+
+        Ok(rays)
+    }
+}
+
+/// Shorthand for building a ray from plain arrays, e.g. `ray_from([0.0, 0.0], [1.0, 1.0])`.
+///
+/// Points and vectors themselves can already be built with `[x, y].into()`.
+pub fn ray_from(origin: [f32; DIM], direction: [f32; DIM]) -> Ray {
+    Ray::new(origin.into(), direction.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_from_array() {
+        let point: Point<f32, DIM> = [1.0, 0.0].into();
+        assert_eq!(point, Point::<f32, DIM>::from_slice(&[1.0, 0.0]));
+    }
+
+    #[test]
+    fn test_ray_from() {
+        let ray = ray_from([1.0, 2.0], [3.0, 4.0]);
+
+        assert_eq!(ray.origin, Point::<f32, DIM>::from_slice(&[1.0, 2.0]));
+        assert!((ray.direction.into_inner() - SVector::from([0.6, 0.8])).norm() < 1e-6);
+        assert_eq!(ray, Ray::new([1.0, 2.0].into(), [3.0, 4.0].into()));
+    }
+
+    #[test]
+    fn test_jittered_fan() {
+        let fan = |seed: u64| {
+            let mut rng = Rand32::new(seed);
+            Ray::jittered_fan([1.0, 2.0].into(), [0.0, 3.0].into(), 0.2, 100, &mut rng)
+        };
+
+        let rays = fan(42);
+        assert_eq!(rays.len(), 100);
+        assert_eq!(rays, fan(42));
+        assert_ne!(rays, fan(43));
+
+        let center = SVector::from([0.0, 1.0]);
+        let angles = rays
+            .iter()
+            .map(|ray| ray.direction.angle(&center))
+            .collect::<Vec<_>>();
+        assert!(rays.iter().all(|ray| ray.origin == [1.0, 2.0].into()));
+        assert!(angles.iter().all(|angle| *angle <= 0.2 + 1e-6));
+        // spread over the whole cone, on both sides of its axis
+        assert!(angles.iter().any(|angle| *angle > 0.15));
+        assert!(rays.iter().any(|ray| ray.direction[0] > 0.0));
+        assert!(rays.iter().any(|ray| ray.direction[0] < 0.0));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let ray = Ray::from_json(&serde_json::json!({
+            "origin": [1.0, 2.0],
+            "direction": [3.0, 4.0],
+            "wavelength": 650.0,
+        }))
+        .unwrap();
+        assert_eq!(ray, ray_from([1.0, 2.0], [3.0, 4.0]).with_wavelength(650.0));
+
+        let ray =
+            Ray::from_json(&serde_json::json!({ "from": [1.0, 2.0], "to": [4.0, 6.0] })).unwrap();
+        assert_eq!(ray.origin, Point::from([1.0, 2.0]));
+        assert!((ray.direction.into_inner() - SVector::from([0.6, 0.8])).norm() < 1e-6);
+        assert_eq!(ray.wavelength, DEFAULT_WAVELENGTH);
+
+        assert_eq!(
+            Ray::from_json(&serde_json::json!({ "from": [1.0, 2.0], "to": [1.0, 2.0] })),
+            Err(MirrorError::InvalidField {
+                field: "to".to_string(),
+                expected: "a non-zero direction",
+            })
+        );
+        assert!(Ray::from_json(&serde_json::json!({ "origin": [1.0, 2.0] })).is_err());
+    }
+
+    #[test]
+    fn test_many_from_csv() {
+        let csv = "# origin, direction\n0.0,1.0,3.0,4.0\n\n-1.5, 2.0, 0.0, -2.0\n";
+
+        let rays = Ray::many_from_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(
+            rays,
+            [
+                ray_from([0.0, 1.0], [3.0, 4.0]),
+                ray_from([-1.5, 2.0], [0.0, -1.0])
+            ]
+        );
+        assert!((rays[0].direction.into_inner() - SVector::from([0.6, 0.8])).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_many_from_csv_invalid() {
+        let error = |csv: &str| Ray::many_from_csv(csv.as_bytes()).unwrap_err();
+
+        assert_eq!(
+            error("0,0,1,0\n0,0,1\n"),
+            MirrorError::InvalidField {
+                field: "row 2".to_string(),
+                expected: "an origin and a direction",
+            }
+        );
+        assert!(matches!(error("0,0,x,1"), MirrorError::InvalidField { .. }));
+        assert!(matches!(error("0,0,0,0"), MirrorError::InvalidField { .. }));
+    }
 }
@@ -0,0 +1,221 @@
+use nalgebra::{Point, SMatrix, SVector, Unit};
+use oorandom::Rand32;
+
+use crate::{
+    error::MirrorError,
+    intersection::Intersection,
+    material::Polarization,
+    mirror::{degenerate, householder_matrix, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// How a [`ModeledMirror`] sends back the light hitting it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReflectionModel {
+    /// Like a polished mirror, the angle of reflection being the angle of incidence.
+    Specular,
+    /// Scatters the light off a rough surface, between specular (0) and lambertian (1).
+    /// The scattering is pseudo-random but only depends on the point hit, so traces can
+    /// be reproduced.
+    Diffuse(f32),
+    /// Sends the light back the way it came, like a corner cube.
+    Retro,
+}
+
+/// Wraps a mirror so it reflects light following a [`ReflectionModel`], letting a scene mix
+/// polished, rough and retroreflective surfaces.
+pub struct ModeledMirror {
+    mirror: Box<dyn Mirror>,
+    model: ReflectionModel,
+}
+
+impl Mirror for ModeledMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        let reflections = self.mirror.reflect(ray);
+        match self.model {
+            ReflectionModel::Specular => reflections,
+            ReflectionModel::Diffuse(roughness) => reflections
+                .into_iter()
+                .map(|(distance, matrix)| {
+                    let intersection = Intersection::new(&ray, distance, matrix);
+                    (distance, scatter(&intersection, &ray, roughness))
+                })
+                .collect(),
+            // a reflection across the plane normal to the ray sends it back, and unlike -I is
+            // a proper reflection, seen by the rest of the crate as a hit at normal incidence
+            ReflectionModel::Retro => reflections
+                .into_iter()
+                .map(|(distance, _)| (distance, householder_matrix(&ray.direction)))
+                .collect(),
+        }
+    }
+    fn get_type(&self) -> String {
+        "modeled".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        self.mirror.is_analytic()
+    }
+    fn is_flat(&self) -> bool {
+        self.mirror.is_flat()
+    }
+    fn is_opaque(&self) -> bool {
+        self.mirror.is_opaque()
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        self.mirror.sample_surface(n)
+    }
+    fn reflectivity(&self) -> f32 {
+        self.mirror.reflectivity()
+    }
+    fn reflectivity_at(&self, point: &Point<f32, DIM>) -> f32 {
+        self.mirror.reflectivity_at(point)
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        self.mirror
+            .polarized_reflectivity(polarization, sin2_incidence)
+    }
+    fn retardance(&self, sin2_incidence: f32) -> f32 {
+        self.mirror.retardance(sin2_incidence)
+    }
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        self.mirror.param_domain()
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.mirror.is_on_boundary(point, tolerance)
+    }
+    fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+        self.mirror.local_point(point)
+    }
+    fn miss_distance(&self, ray: &Ray) -> f32 {
+        self.mirror.miss_distance(ray)
+    }
+    fn max_incidence_angle(&self) -> Option<f32> {
+        self.mirror.max_incidence_angle()
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        self.mirror.bounding_sphere()
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        match self.model {
+            ReflectionModel::Diffuse(roughness) if !(0.0..=1.0).contains(&roughness) => {
+                degenerate(self, "its roughness isn't between 0 and 1")
+            }
+            _ => self.mirror.validate(),
+        }
+    }
+}
+
+impl ModeledMirror {
+    pub fn new(mirror: Box<dyn Mirror>, model: ReflectionModel) -> Self {
+        Self { mirror, model }
+    }
+
+    pub fn model(&self) -> ReflectionModel {
+        self.model
+    }
+}
+
+/// The reflection sending `ray` in a direction between the specular one and a lambertian one,
+/// the latter drawn from a generator seeded by the point hit.
+fn scatter(intersection: &Intersection, ray: &Ray, roughness: f32) -> Unit<SMatrix<f32, DIM, DIM>> {
+    let normal = intersection.facing_normal(&ray.direction);
+    let specular = intersection.reflect_direction(&ray.direction);
+
+    let [x, y] = intersection.point.coords.map(f32::to_bits).into();
+    let mut rng = Rand32::new(((x as u64) << 32) | y as u64);
+    // a lambertian surface in 2D scatters with a density proportional to the cosine of the
+    // angle from the normal, so the sine of that angle is uniform
+    let sin = 2.0 * rng.rand_float() - 1.0;
+    let cos = (1.0 - sin * sin).max(0.0).sqrt();
+    let lambertian = SVector::from([
+        cos * normal[0] - sin * normal[1],
+        sin * normal[0] + cos * normal[1],
+    ]);
+
+    // both directions leave on the side of the normal, and so does any blend of them
+    let outgoing = (1.0 - roughness) * specular.into_inner() + roughness * lambertian;
+    match Unit::try_new(outgoing - ray.direction.into_inner(), f32::EPSILON) {
+        Some(bisector) => householder_matrix(&bisector),
+        None => intersection.reflection_matrix,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mirror::PlaneMirror, ray::ray_from};
+
+    fn modeled_plane(model: ReflectionModel) -> ModeledMirror {
+        ModeledMirror::new(
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            model,
+        )
+    }
+
+    fn outgoing(mirror: &ModeledMirror, ray: Ray) -> SVector<f32, DIM> {
+        mirror.reflect_ray(&ray).unwrap().direction.into_inner()
+    }
+
+    #[test]
+    fn test_specular() {
+        let mirror = modeled_plane(ReflectionModel::Specular);
+
+        let direction = outgoing(&mirror, ray_from([-1.0, 1.0], [1.0, -1.0]));
+
+        assert!((direction - SVector::from([1.0, 1.0]).normalize()).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_diffuse() {
+        let smooth = modeled_plane(ReflectionModel::Diffuse(0.0));
+        let rough = modeled_plane(ReflectionModel::Diffuse(1.0));
+        let specular = SVector::from([1.0, 1.0]).normalize();
+
+        let ray = ray_from([-1.0, 1.0], [1.0, -1.0]);
+        assert!((outgoing(&smooth, ray) - specular).norm() < 1e-6);
+
+        let directions = (0..32)
+            .map(|i| outgoing(&rough, ray_from([i as f32 * 0.1 - 1.0, 1.0], [1.0, -1.0])))
+            .collect::<Vec<_>>();
+        for direction in &directions {
+            assert!((direction.norm() - 1.0).abs() < 1e-5);
+            assert!(direction[1] >= 0.0);
+        }
+        // scattered all over the hemisphere rather than around the specular direction
+        assert!(directions.iter().any(|direction| direction[0] < -0.5));
+        assert!(directions.iter().any(|direction| direction[0] > 0.9));
+
+        // the same point always scatters the same way
+        assert_eq!(outgoing(&rough, ray), outgoing(&rough, ray));
+    }
+
+    #[test]
+    fn test_retro() {
+        let mirror = modeled_plane(ReflectionModel::Retro);
+
+        let direction = outgoing(&mirror, ray_from([-1.0, 2.0], [1.0, -2.0]));
+
+        assert!((direction - SVector::from([-1.0, 2.0]).normalize()).norm() < 1e-6);
+
+        // hit head-on, whatever the angle of the surface
+        let ray = ray_from([-1.0, 2.0], [1.0, -2.0]);
+        let (distance, matrix) = mirror.reflect(ray)[0];
+        let intersection = Intersection::new(&ray, distance, matrix);
+        assert!(intersection.sin2_incidence(&ray.direction) < 1e-6);
+        assert!((matrix.determinant() + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(modeled_plane(ReflectionModel::Diffuse(0.5))
+            .validate()
+            .is_ok());
+        assert!(modeled_plane(ReflectionModel::Diffuse(1.5))
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,35 @@
+//! Design checks for optical cavities made of two facing mirrors.
+
+/// The `g1 * g2` product of a cavity of the given `length` between mirrors of radii of
+/// curvature `r1` and `r2`, with `g = 1 - length / r`. Radii are positive for mirrors concave
+/// towards the cavity, and infinite for flat mirrors.
+pub fn stability(r1: f32, r2: f32, length: f32) -> f32 {
+    let g = |radius: f32| 1.0 - length / radius;
+    g(r1) * g(r2)
+}
+
+/// Whether a cavity keeps its rays bounded, i.e. `0 <= g1 * g2 <= 1`. The edges of the range,
+/// like confocal or flat-flat cavities, are only marginally stable.
+pub fn is_stable(r1: f32, r2: f32, length: f32) -> bool {
+    (0.0..=1.0).contains(&stability(r1, r2, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stability() {
+        // g1 = g2 = 0.5
+        assert!((stability(2.0, 2.0, 1.0) - 0.25).abs() < 1e-6);
+        assert!(is_stable(2.0, 2.0, 1.0));
+
+        // mirrors more curved than half the length: g1 = g2 = -1.5
+        assert!((stability(0.4, 0.4, 1.0) - 2.25).abs() < 1e-6);
+        assert!(!is_stable(0.4, 0.4, 1.0));
+
+        // a convex mirror facing a flat one
+        assert!(!is_stable(-1.0, f32::INFINITY, 1.0));
+        assert_eq!(stability(f32::INFINITY, f32::INFINITY, 1.0), 1.0);
+    }
+}
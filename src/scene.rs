@@ -0,0 +1,803 @@
+use nalgebra::{Point, SMatrix, SVector, Unit};
+
+use crate::{
+    error::MirrorError,
+    intersection::Intersection,
+    json,
+    math::magnitude,
+    mirror::{enclosing_sphere, facing, mirror_from_json, Mirror},
+    ray::Ray,
+    DIM,
+};
+
+/// Hits closer than this are ignored, so a ray leaving a mirror doesn't hit it again right away.
+/// It is relative to the magnitude of the ray's origin, or to that of the mirror's coordinates
+/// when larger, so scenes behave the same at any scale.
+pub const EPSILON: f32 = 1e-4;
+
+/// The mirrors a ray can bounce on.
+pub struct Scene {
+    mirrors: Vec<Box<dyn Mirror>>,
+    /// Optional name of each mirror, at the same index.
+    names: Vec<Option<String>>,
+}
+
+impl Scene {
+    pub fn new(mirrors: Vec<Box<dyn Mirror>>) -> Self {
+        let names = vec![None; mirrors.len()];
+        Self { mirrors, names }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "mirrors": [
+                {
+                    "type": "plane",
+                    "name": "detector", // optional
+                    "points": [[1.0, 2.0], [3.0, 4.0]]
+                },
+                ...
+            ]
+        }
+         */
+        let mut scene = Self::new(vec![]);
+
+        for mirror in json::array(json, "mirrors")? {
+            let index = scene.add_mirror(mirror_from_json(mirror)?);
+
+            match &mirror["name"] {
+                serde_json::Value::Null => {}
+                serde_json::Value::String(name) => scene.names[index] = Some(name.clone()),
+                _ => {
+                    return Err(MirrorError::InvalidField {
+                        field: "name".to_string(),
+                        expected: "a string",
+                    })
+                }
+            }
+        }
+
+        Ok(scene)
+    }
+
+    /// Reads a scene from a toml file, with the same schema as [`Scene::from_json`]:
+    ///
+    /// ```toml
+    /// [[mirrors]]
+    /// type = "plane"
+    /// points = [[1.0, 2.0], [3.0, 4.0]]
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn load_toml(path: impl AsRef<std::path::Path>) -> Result<Self, MirrorError> {
+        let toml =
+            std::fs::read_to_string(path).map_err(|error| MirrorError::Io(error.to_string()))?;
+
+        Self::from_json(&crate::toml::to_json(&toml)?)
+    }
+
+    /// The json read by [`Scene::from_json`], failing if a mirror can't be written out.
+    ///
+    /// The fields of json objects are kept sorted, so the same scene always gives the same json.
+    pub fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        let mirrors = self
+            .mirrors
+            .iter()
+            .zip(&self.names)
+            .map(|(mirror, name)| {
+                let mut json = mirror.to_json()?;
+                if let (Some(name), Some(object)) = (name, json.as_object_mut()) {
+                    object.insert("name".to_string(), name.clone().into());
+                }
+                Ok(json)
+            })
+            .collect::<Result<Vec<_>, MirrorError>>()?;
+
+        Ok(serde_json::json!({ "mirrors": mirrors }))
+    }
+
+    /// Writes the scene to a json file readable by [`Scene::from_json`]. Saving the same scene
+    /// twice gives the same bytes, so saved scenes can be diffed and kept under version control.
+    pub fn save_json(&self, path: impl AsRef<std::path::Path>) -> Result<(), MirrorError> {
+        let json = serde_json::to_string_pretty(&self.to_json()?)
+            .map_err(|error| MirrorError::Io(error.to_string()))?;
+
+        std::fs::write(path, json + "\n").map_err(|error| MirrorError::Io(error.to_string()))
+    }
+
+    /// Names the mirror at `index`, so it can be looked up with [`Scene::find`].
+    ///
+    /// # Panics
+    ///
+    /// If there is no mirror at `index`.
+    pub fn with_name(mut self, index: usize, name: impl Into<String>) -> Self {
+        self.names[index] = Some(name.into());
+        self
+    }
+
+    pub fn mirrors(&self) -> &[Box<dyn Mirror>] {
+        &self.mirrors
+    }
+
+    /// Adds an unnamed mirror after the others, returning its index.
+    pub fn add_mirror(&mut self, mirror: Box<dyn Mirror>) -> usize {
+        self.mirrors.push(mirror);
+        self.names.push(None);
+        self.mirrors.len() - 1
+    }
+
+    /// Removes the mirror at `index` along with its name, shifting the following mirrors down.
+    ///
+    /// # Panics
+    ///
+    /// If there is no mirror at `index`.
+    pub fn remove_mirror(&mut self, index: usize) -> Box<dyn Mirror> {
+        self.names.remove(index);
+        self.mirrors.remove(index)
+    }
+
+    /// Puts `mirror` in place of the one at `index`, which keeps its name, and returns the
+    /// previous one.
+    ///
+    /// # Panics
+    ///
+    /// If there is no mirror at `index`.
+    pub fn replace_mirror(&mut self, index: usize, mirror: Box<dyn Mirror>) -> Box<dyn Mirror> {
+        std::mem::replace(&mut self.mirrors[index], mirror)
+    }
+
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.names.get(index)?.as_deref()
+    }
+
+    /// Index of the first mirror named `name`, if any.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .position(|candidate| candidate.as_deref() == Some(name))
+    }
+
+    /// Checks every mirror before tracing, returning all the problems found at once. Mirrors
+    /// sharing the same surface are reported too: the tracer only ever reflects on the first
+    /// of them, so the others are most likely an accidental duplicate. Mirrors which aren't
+    /// valid themselves aren't compared with the others, their surface being meaningless.
+    pub fn validate(&self) -> Result<(), Vec<MirrorError>> {
+        let mut errors = vec![];
+        let mut valid = vec![];
+        for mirror in &self.mirrors {
+            let result = mirror.validate();
+            valid.push(result.is_ok());
+            errors.extend(result.err());
+        }
+
+        for first in (0..self.mirrors.len()).filter(|index| valid[*index]) {
+            for second in (first + 1..self.mirrors.len()).filter(|index| valid[*index]) {
+                if coincide(&*self.mirrors[first], &*self.mirrors[second]) {
+                    errors.push(MirrorError::CoincidentMirrors { first, second });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The first mirror hit by `ray`, as its index in [`Scene::mirrors`] and the intersection.
+    /// Hits closer than [`EPSILON`] (scaled by the magnitude of the origin or the mirror) are
+    /// skipped. When two hits are at the same distance, within that same threshold, the mirror
+    /// the ray hits the most head-on wins, like on the vertex shared by two segments, then the
+    /// one with the lowest index, so coincident mirrors only reflect once.
+    pub fn nearest_hit(&self, ray: &Ray) -> Option<(usize, Intersection)> {
+        self.nearest_hit_after(ray, None)
+    }
+
+    /// Like [`Scene::nearest_hit`], for a ray which just left the mirror at index `previous`.
+    /// If that mirror is flat (see [`Mirror::is_flat`]), it can't be hit again and is skipped,
+    /// which is more robust than [`EPSILON`] when the ray starts right on its surface.
+    pub fn nearest_hit_after(
+        &self,
+        ray: &Ray,
+        previous: Option<usize>,
+    ) -> Option<(usize, Intersection)> {
+        let excluded = previous.filter(|index| self.mirrors[*index].is_flat());
+
+        self.nearest_hit_in(
+            ray,
+            (0..self.mirrors.len()).filter(|index| Some(*index) != excluded),
+        )
+    }
+
+    /// Like [`Scene::nearest_hit`], only looking at the mirrors at `candidates`, which must be
+    /// sorted, e.g. the ones found by [`Scene::bundle_candidates`].
+    pub fn nearest_hit_among(
+        &self,
+        ray: &Ray,
+        candidates: &[usize],
+    ) -> Option<(usize, Intersection)> {
+        self.nearest_hit_in(ray, candidates.iter().copied())
+    }
+
+    /// Indices of the mirrors which any of `rays` may hit first, in order. The rays are
+    /// bounded by a cone, with its apex spread over a sphere enclosing their origins, and
+    /// mirrors are kept when their bounding sphere may meet that cone, so a tight bundle of
+    /// rays is culled once instead of once per ray.
+    ///
+    /// This errs on the side of keeping mirrors: a bundle going every way keeps them all.
+    pub fn bundle_candidates(&self, rays: &[Ray]) -> Vec<usize> {
+        let origins = rays.iter().map(|ray| (ray.origin, 0.0)).collect::<Vec<_>>();
+        let Some((apex, spread)) = enclosing_sphere(&origins) else {
+            return vec![];
+        };
+        let Some(axis) = Unit::try_new(
+            rays.iter()
+                .map(|ray| ray.direction.into_inner())
+                .sum::<SVector<f32, DIM>>(),
+            f32::EPSILON,
+        ) else {
+            return (0..self.mirrors.len()).collect();
+        };
+        let aperture = rays
+            .iter()
+            .map(|ray| ray.direction.dot(&axis).clamp(-1.0, 1.0).acos())
+            .fold(0.0, f32::max);
+
+        (0..self.mirrors.len())
+            .filter(|index| {
+                let Some((center, radius)) = self.mirrors[*index].bounding_sphere() else {
+                    return true;
+                };
+                // moving a ray onto the apex moves it by at most the spread
+                let radius = (radius + spread) * (1.0 + EPSILON);
+                let to_center = center - apex;
+                let distance = to_center.norm();
+                if distance <= radius {
+                    return true;
+                }
+
+                let angle = (to_center.dot(&axis) / distance).clamp(-1.0, 1.0).acos();
+                angle <= aperture + (radius / distance).asin() + EPSILON
+            })
+            .collect()
+    }
+
+    /// The nearest hit of `ray` on the mirrors at `indices`, which come in order.
+    fn nearest_hit_in(
+        &self,
+        ray: &Ray,
+        indices: impl Iterator<Item = usize>,
+    ) -> Option<(usize, Intersection)> {
+        indices
+            .filter_map(|index| {
+                let mirror = &self.mirrors[index];
+                let sphere = mirror.bounding_sphere();
+                if sphere.is_some_and(|(center, radius)| !may_hit(ray, &center, radius)) {
+                    return None;
+                }
+
+                // reflections are sorted, so the first one far enough is the nearest
+                let threshold = tolerance(&ray.origin, sphere);
+                mirror
+                    .reflect(*ray)
+                    .into_iter()
+                    .find(|(distance, _)| *distance > threshold)
+                    .map(|(distance, matrix)| {
+                        let intersection = intersection(&**mirror, ray, distance, matrix);
+                        (index, intersection, threshold)
+                    })
+            })
+            .reduce(|best, hit| {
+                let threshold = best.2.max(hit.2);
+                let facing = |hit: &Intersection| facing(&hit.reflection_matrix, ray);
+
+                // mirrors come in order, so the earlier one is kept on a full tie
+                if hit.1.distance < best.1.distance - threshold
+                    || hit.1.distance <= best.1.distance + threshold
+                        && facing(&hit.1) > facing(&best.1)
+                {
+                    hit
+                } else {
+                    best
+                }
+            })
+            .map(|(index, intersection, _)| (index, intersection))
+    }
+
+    /// Every hit of `ray` on every mirror, as returned by [`Mirror::reflect`], with the index
+    /// of the mirror in [`Scene::mirrors`], sorted by distance. Nothing is reflected: the ray
+    /// goes straight through all of them, which is what picking or collision queries need.
+    ///
+    /// Like in [`Scene::nearest_hit`], hits closer than [`EPSILON`] are skipped.
+    pub fn all_intersections(&self, ray: &Ray) -> Vec<(usize, Intersection)> {
+        let mut hits = self
+            .mirrors
+            .iter()
+            .enumerate()
+            .flat_map(|(index, mirror)| {
+                let sphere = mirror.bounding_sphere();
+                let reachable = sphere.is_none_or(|(center, radius)| may_hit(ray, &center, radius));
+                let threshold = tolerance(&ray.origin, sphere);
+
+                reachable
+                    .then(|| mirror.reflect(*ray))
+                    .into_iter()
+                    .flatten()
+                    .filter(move |(distance, _)| *distance > threshold)
+                    .map(move |(distance, matrix)| {
+                        (index, intersection(&**mirror, ray, distance, matrix))
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        // stable, so hits at the same distance stay ordered by index
+        hits.sort_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+/// [`EPSILON`] scaled by the magnitude of `point`, but never below the magnitude of the
+/// coordinates of a mirror with `sphere` as its bounding sphere: the rounding errors of its
+/// intersections are relative to those, even for a ray leaving from near `(0, 0)`.
+pub(crate) fn tolerance(point: &Point<f32, DIM>, sphere: Option<(Point<f32, DIM>, f32)>) -> f32 {
+    let size = sphere.map_or(0.0, |(center, radius)| magnitude([&center]) + radius);
+    EPSILON * magnitude([point]).max(size)
+}
+
+/// The intersection of `ray` with `mirror` from one of the results of [`Mirror::reflect`],
+/// flagged when it is on an edge of the mirror, within [`tolerance`] of it,
+/// with the hit point in the mirror's frame and the reflectivity there.
+fn intersection(
+    mirror: &dyn Mirror,
+    ray: &Ray,
+    distance: f32,
+    matrix: Unit<SMatrix<f32, DIM, DIM>>,
+) -> Intersection {
+    let mut intersection = Intersection::new(ray, distance, matrix);
+    intersection.on_boundary = mirror.is_on_boundary(
+        &intersection.point,
+        tolerance(&intersection.point, mirror.bounding_sphere()),
+    );
+    intersection.local_point = mirror.local_point(&intersection.point);
+    intersection.reflectivity = mirror.reflectivity_at(&intersection.point);
+    intersection
+}
+
+/// Whether the surfaces of `a` and `b` are the same, as far as samples of each one lying on
+/// the other can tell. Mirrors which can't be sampled never coincide.
+fn coincide(a: &dyn Mirror, b: &dyn Mirror) -> bool {
+    const SAMPLES: usize = 16;
+
+    let covered = |from: &dyn Mirror, on: &dyn Mirror| {
+        let samples = from.sample_surface(SAMPLES);
+        !samples.is_empty()
+            && samples
+                .iter()
+                .all(|point| on.contains_point(point, EPSILON * magnitude([point]).max(1.0)))
+    };
+    covered(a, b) && covered(b, a)
+}
+
+/// Cheap check of whether `ray` can reach the sphere, which errs on the side of yes.
+fn may_hit(ray: &Ray, center: &Point<f32, DIM>, radius: f32) -> bool {
+    let to_center = center - ray.origin;
+    let along = to_center.dot(&ray.direction);
+    let radius = radius * (1.0 + EPSILON);
+
+    // starting inside, or heading towards the sphere and passing close enough to its center
+    to_center.norm_squared() <= radius * radius
+        || (along >= 0.0 && to_center.norm_squared() - along * along <= radius * radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graph_mirror::GraphMirror,
+        mirror::{PlaneMirror, Reflections, SphereMirror},
+        ray::{ray_from, Ray},
+        segment_mirror::SegmentMirror,
+        simulation::Simulation,
+    };
+
+    fn two_planes() -> Scene {
+        Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 3.0].into(), [1.0, 3.0].into()])),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ])
+    }
+
+    #[test]
+    fn test_nearest_hit() {
+        let scene = two_planes();
+
+        let (index, intersection) = scene
+            .nearest_hit(&ray_from([0.0, 0.0], [0.0, 1.0]))
+            .unwrap();
+        assert_eq!(index, 1);
+        assert!((intersection.distance - 1.0).abs() < 1e-6);
+        assert!((intersection.point - Point::from([0.0, 1.0])).norm() < 1e-6);
+
+        let (index, _) = scene
+            .nearest_hit(&ray_from([0.0, 2.0], [0.0, 1.0]))
+            .unwrap();
+        assert_eq!(index, 0);
+
+        assert!(scene
+            .nearest_hit(&ray_from([0.0, 4.0], [0.0, 1.0]))
+            .is_none());
+    }
+
+    #[test]
+    fn test_nearest_hit_skips_epsilon() {
+        let scene = two_planes();
+
+        // starting on the first plane, it must not be hit again
+        let (index, intersection) = scene
+            .nearest_hit(&ray_from([0.0, 1.0], [0.0, 1.0]))
+            .unwrap();
+        assert_eq!(index, 0);
+        assert!((intersection.distance - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_nearest_hit_skips_epsilon_near_origin() {
+        // finds the surface the ray leaves from again, a rounding error away, like a curved
+        // mirror would
+        struct Rounded(SegmentMirror);
+
+        impl Mirror for Rounded {
+            fn reflect(&self, ray: Ray) -> Reflections {
+                let mut reflections = self.0.reflect(ray);
+                reflections.insert(0, (1e-6, reflections[0].1));
+                reflections
+            }
+            fn get_type(&self) -> String {
+                self.0.get_type()
+            }
+            fn is_analytic(&self) -> bool {
+                false
+            }
+            fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+                self.0.contains_point(point, tolerance)
+            }
+            fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+                self.0.sample_surface(n)
+            }
+            fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+                self.0.bounding_sphere()
+            }
+        }
+
+        let scene = Scene::new(vec![Box::new(Rounded(SegmentMirror::new(
+            [-1.0, 1.0].into(),
+            [1.0, 1.0].into(),
+        )))]);
+
+        let (_, intersection) = scene
+            .nearest_hit(&ray_from([1e-3, 0.0], [0.0, 1.0]))
+            .unwrap();
+        assert!((intersection.distance - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(two_planes().validate(), Ok(()));
+
+        let scene = Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 3.0].into(), [1.0, 3.0].into()])),
+            Box::new(SphereMirror::new([0.0, 0.0].into(), 0.0)),
+            Box::new(SphereMirror::new([2.0, 0.0].into(), 1.0)),
+        ]);
+
+        assert_eq!(
+            scene.validate(),
+            Err(vec![MirrorError::DegenerateMirror {
+                mirror_type: "sphere".to_string(),
+                reason: "its radius isn't strictly positive",
+            }])
+        );
+
+        // two degenerate spheres at the same place are reported as such, not as duplicates
+        let point = || Box::new(SphereMirror::new([2.0, 0.0].into(), 0.0));
+        let errors = Scene::new(vec![point(), point()]).validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, MirrorError::DegenerateMirror { .. })));
+    }
+
+    #[test]
+    fn test_coincident_mirrors() {
+        let plane = || Box::new(PlaneMirror::new([[0.0, 3.0].into(), [1.0, 3.0].into()]));
+        let scene = Scene::new(vec![
+            plane(),
+            Box::new(SphereMirror::new([5.0, 0.0].into(), 1.0)),
+            plane(),
+        ]);
+
+        assert_eq!(
+            scene.validate(),
+            Err(vec![MirrorError::CoincidentMirrors {
+                first: 0,
+                second: 2
+            }])
+        );
+
+        let (index, _) = scene
+            .nearest_hit(&ray_from([0.0, 0.0], [0.0, 1.0]))
+            .unwrap();
+        assert_eq!(index, 0);
+
+        // reflected by the first copy, and not again by the second one right behind it
+        let result = Simulation::new(scene).trace(ray_from([0.0, 0.0], [0.0, 1.0]), 10);
+        assert_eq!(result.len(), 1);
+        assert!(result.last_ray.direction[1] < 0.0);
+    }
+
+    #[test]
+    fn test_nearest_hit_on_shared_vertex() {
+        let slope = SegmentMirror::new([4.0, 0.0].into(), [0.0, 1.0].into());
+        let scene = Scene::new(vec![
+            Box::new(SegmentMirror::new([0.0, 0.0].into(), [4.0, 0.0].into())),
+            Box::new(slope),
+        ]);
+        // straight at the vertex, facing the slope more
+        let ray = ray_from([5.0, 2.0], [-1.0, -2.0]);
+
+        let (index, intersection) = scene.nearest_hit(&ray).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(intersection.reflection_matrix, slope.reflect(ray)[0].1);
+    }
+
+    #[test]
+    fn test_bounding_spheres_dont_change_hits() {
+        // hides the bounding sphere of a mirror, so it is always intersected
+        struct Unbounded(Box<dyn Mirror>);
+
+        impl Mirror for Unbounded {
+            fn reflect(&self, ray: Ray) -> Reflections {
+                self.0.reflect(ray)
+            }
+            fn get_type(&self) -> String {
+                self.0.get_type()
+            }
+            fn is_analytic(&self) -> bool {
+                self.0.is_analytic()
+            }
+            fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+                self.0.contains_point(point, tolerance)
+            }
+            fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+                self.0.sample_surface(n)
+            }
+            fn local_point(&self, point: &Point<f32, DIM>) -> Option<Point<f32, DIM>> {
+                self.0.local_point(point)
+            }
+        }
+
+        let mirrors = || -> Vec<Box<dyn Mirror>> {
+            vec![
+                Box::new(SphereMirror::new([3.0, 0.0].into(), 1.0)),
+                Box::new(SphereMirror::new([-2.0, 4.0].into(), 0.5)),
+                Box::new(SegmentMirror::new([0.0, -3.0].into(), [2.0, -2.0].into())),
+                Box::new(PlaneMirror::new([[0.0, 6.0].into(), [1.0, 6.0].into()])),
+            ]
+        };
+        let bounded = Scene::new(mirrors());
+        let unbounded = Scene::new(
+            mirrors()
+                .into_iter()
+                .map(|mirror| Box::new(Unbounded(mirror)) as Box<dyn Mirror>)
+                .collect(),
+        );
+
+        for i in 0..100 {
+            let angle = i as f32 * 0.063;
+            for origin in [[0.0, 0.0], [3.0, 0.5], [-2.0, 4.0]] {
+                let ray = ray_from(origin, [angle.cos(), angle.sin()]);
+                assert_eq!(bounded.nearest_hit(&ray), unbounded.nearest_hit(&ray));
+            }
+        }
+
+        // a sphere behind the ray is skipped
+        assert!(!may_hit(
+            &ray_from([0.0, 0.0], [-1.0, 0.0]),
+            &[3.0, 0.0].into(),
+            1.0
+        ));
+    }
+
+    #[test]
+    fn test_names() {
+        let scene = two_planes().with_name(1, "bottom");
+
+        assert_eq!(scene.find("bottom"), Some(1));
+        assert_eq!(scene.find("top"), None);
+        assert_eq!(scene.name(0), None);
+        assert_eq!(scene.name(1), Some("bottom"));
+        assert_eq!(scene.name(2), None);
+    }
+
+    #[test]
+    fn test_all_intersections() {
+        let scene = Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 3.0].into(), [1.0, 3.0].into()])),
+            Box::new(SphereMirror::new([0.0, 5.0].into(), 1.0)),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ]);
+
+        let hits = scene.all_intersections(&ray_from([0.0, 0.0], [0.0, 1.0]));
+
+        let found = hits
+            .iter()
+            .map(|(index, intersection)| (*index, intersection.distance))
+            .collect::<Vec<_>>();
+        assert_eq!(found.len(), 4);
+        for ((index, distance), (expected_index, expected_distance)) in
+            found
+                .into_iter()
+                .zip([(2, 1.0), (0, 3.0), (1, 4.0), (1, 6.0)])
+        {
+            assert_eq!(index, expected_index);
+            assert!((distance - expected_distance).abs() < 1e-5);
+        }
+
+        assert!(scene
+            .all_intersections(&ray_from([0.0, 0.0], [0.0, -1.0]))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_edit_mirrors() {
+        let mut scene = two_planes().with_name(1, "bottom");
+        let ray = ray_from([0.0, 0.0], [0.0, 1.0]);
+
+        let index = scene.add_mirror(Box::new(PlaneMirror::new([
+            [0.0, 0.5].into(),
+            [1.0, 0.5].into(),
+        ])));
+        assert_eq!(index, 2);
+        assert_eq!(scene.nearest_hit(&ray).unwrap().0, 2);
+
+        let removed = scene.remove_mirror(1);
+        assert_eq!(removed.get_type(), "plane");
+        assert_eq!(scene.mirrors().len(), 2);
+        assert_eq!(scene.find("bottom"), None);
+        assert_eq!(scene.nearest_hit(&ray).unwrap().0, 1);
+
+        // a sphere with a bounding sphere, which must not be stale
+        scene.replace_mirror(1, Box::new(SphereMirror::new([0.0, 5.0].into(), 1.0)));
+        let (index, intersection) = scene.nearest_hit(&ray).unwrap();
+        assert_eq!(index, 0);
+        assert!((intersection.distance - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let scene = Scene::from_json(&serde_json::json!({
+            "mirrors": [
+                { "type": "segment", "start": [0.0, 1.0], "end": [1.0, 1.0] },
+                { "type": "sphere", "center": [0.0, 5.0], "radius": 1.0, "name": "ball" },
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(scene.mirrors().len(), 2);
+        assert_eq!(scene.find("ball"), Some(1));
+        assert!(Scene::from_json(&serde_json::json!({
+            "mirrors": [{ "type": "sphere", "center": [0.0, 5.0], "radius": 1.0, "name": 3 }]
+        }))
+        .is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_load_toml() {
+        let json = Scene::from_json(&serde_json::json!({
+            "mirrors": [
+                { "type": "plane", "points": [[0.0, 3.0], [1.0, 3.0]], "reflectivity": 0.5 },
+                { "type": "circle", "center": [0.0, 0.0], "radius": 5, "name": "ring" },
+            ]
+        }))
+        .unwrap();
+
+        let path = std::env::temp_dir().join("mirror_verse_test_load_toml.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [[mirrors]]
+            type = "plane"
+            points = [[0.0, 3.0], [1.0, 3.0]]
+            reflectivity = 0.5
+
+            [[mirrors]]
+            type = "circle"
+            center = [0.0, 0.0]
+            radius = 5
+            name = "ring"
+            "#,
+        )
+        .unwrap();
+        let toml = Scene::load_toml(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(toml.mirrors().len(), json.mirrors().len());
+        assert_eq!(toml.find("ring"), Some(1));
+        for (a, b) in toml.mirrors().iter().zip(json.mirrors()) {
+            assert_eq!(a.get_type(), b.get_type());
+            assert_eq!(a.reflectivity(), b.reflectivity());
+        }
+        for direction in [[0.0, 1.0], [1.0, 0.0], [0.6, -0.8]] {
+            let ray = ray_from([0.0, 0.0], direction);
+            assert_eq!(toml.nearest_hit(&ray), json.nearest_hit(&ray));
+        }
+
+        assert!(matches!(
+            Scene::load_toml(std::env::temp_dir().join("mirror_verse_missing.toml")),
+            Err(MirrorError::Io(_))
+        ));
+        assert!(matches!(
+            crate::toml::to_json("mirrors = ["),
+            Err(MirrorError::InvalidToml(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_json_is_stable() {
+        let json = serde_json::json!({
+            "mirrors": [
+                { "type": "sphere", "center": [0.0, 0.0], "radius": 1.0, "name": "ball" },
+                { "type": "polygon", "vertices": [[2.0, 0.0], [3.0, 0.0], [3.0, 1.0]] },
+                {
+                    "type": "composite",
+                    "mirrors": [
+                        { "type": "plane", "points": [[0.0, 3.0], [1.0, 3.0]], "reflectivity": 0.5 },
+                        { "type": "segment", "start": [0.0, -3.0], "end": [1.0, -3.0] },
+                        { "type": "bezier", "control_points": [[4.0, 0.0], [5.0, 1.0]] }
+                    ]
+                },
+                {
+                    "type": "circle",
+                    "center": [0.0, 0.0],
+                    "radius": 5.0,
+                    "end_angle": 1.5,
+                    "name": "arc"
+                }
+            ]
+        });
+        let scene = Scene::from_json(&json).unwrap();
+        let save = |name: &str| {
+            let path = std::env::temp_dir().join(format!("mirror_verse_{name}.json"));
+            scene.save_json(&path).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            bytes
+        };
+
+        let saved = save("stable_a");
+        assert_eq!(saved, save("stable_b"));
+
+        // fields come out sorted, and the file reads back as the same scene
+        let text = String::from_utf8(saved).unwrap();
+        assert!(text.find(r#""center""#) < text.find(r#""name": "ball""#));
+        assert!(text.find(r#""name": "ball""#) < text.find(r#""radius""#));
+        let reloaded = Scene::from_json(&serde_json::from_str(&text).unwrap()).unwrap();
+        assert_eq!(reloaded.find("arc"), Some(3));
+        assert_eq!(reloaded.to_json(), scene.to_json());
+        for direction in [[0.0, 1.0], [1.0, 0.0], [0.6, -0.8], [1.0, 0.1]] {
+            let ray = ray_from([-0.5, 0.5], direction);
+            assert_eq!(
+                reloaded.all_intersections(&ray),
+                scene.all_intersections(&ray)
+            );
+        }
+
+        let graph = Scene::new(vec![Box::new(GraphMirror::numeric(|x| x * x, -1.0, 1.0))]);
+        assert_eq!(
+            graph.to_json(),
+            Err(MirrorError::Unserializable("graph".to_string()))
+        );
+    }
+}
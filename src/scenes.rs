@@ -0,0 +1,135 @@
+//! A few canonical scenes, to check a build behaves and as examples of setting up a
+//! simulation. Each one documents what rays traced through it should do.
+
+use crate::{
+    circle_mirror::CircleMirror, graph_mirror::GraphMirror, scene::Scene,
+    segment_mirror::SegmentMirror,
+};
+
+/// The parabola `y = x² / 4` for x from -2 to 2, with its focus at (0, 1). Rays leaving the
+/// focus downwards all come out going straight up, and rays coming straight down all go
+/// through the focus.
+pub fn parabolic_collimator() -> Scene {
+    Scene::new(vec![Box::new(GraphMirror::new(
+        |x| x * x / 4.0,
+        |x| x / 2.0,
+        -2.0,
+        2.0,
+    ))])
+}
+
+/// Two concave mirrors of radius 2, one unit apart around the origin along the x axis,
+/// making a stable cavity (`g1 = g2 = 0.5`, see [`crate::resonator::stability`]). A ray
+/// starting near the axis bounces between them forever, never drifting away from it.
+pub fn two_mirror_cavity() -> Scene {
+    const RADIUS: f32 = 2.0;
+    const HALF_SPAN: f32 = 0.4;
+
+    // each mirror is the arc of a circle centered beyond the other one
+    Scene::new(vec![
+        Box::new(CircleMirror::arc(
+            [RADIUS - 0.5, 0.0].into(),
+            RADIUS,
+            std::f32::consts::PI - HALF_SPAN,
+            std::f32::consts::PI + HALF_SPAN,
+        )),
+        Box::new(CircleMirror::arc(
+            [0.5 - RADIUS, 0.0].into(),
+            RADIUS,
+            -HALF_SPAN,
+            HALF_SPAN,
+        )),
+    ])
+}
+
+/// Two perpendicular mirrors meeting at the origin, along the positive x and y axes. A ray
+/// coming from the first quadrant and hitting both leaves the way it came from.
+pub fn corner_reflector() -> Scene {
+    Scene::new(vec![
+        Box::new(SegmentMirror::new([0.0, 0.0].into(), [2.0, 0.0].into())),
+        Box::new(SegmentMirror::new([0.0, 0.0].into(), [0.0, 2.0].into())),
+    ])
+}
+
+/// The lower half of the ellipse `x² / 4 + y² / 3 = 1`, whose foci are (-1, 0) and (1, 0).
+/// Rays leaving one focus downwards all go through the other one.
+pub fn elliptical_focus() -> Scene {
+    const A: f32 = 2.0;
+    let b = 3f32.sqrt();
+    // the ends are left out, where the graph is vertical
+    const END: f32 = 1.9;
+
+    Scene::new(vec![Box::new(GraphMirror::new(
+        move |x| -b * (1.0 - x * x / (A * A)).sqrt(),
+        move |x| b * x / (A * A * (1.0 - x * x / (A * A)).sqrt()),
+        -END,
+        END,
+    ))])
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point, SVector};
+
+    use super::*;
+    use crate::{
+        ray::{ray_from, Ray},
+        simulation::{Simulation, Termination},
+        DIM,
+    };
+
+    /// Distance from `point` to the line supporting `ray`.
+    fn distance_to_line(ray: &Ray, point: &Point<f32, DIM>) -> f32 {
+        let offset = point - ray.origin;
+        (offset - offset.dot(&ray.direction) * ray.direction.into_inner()).norm()
+    }
+
+    #[test]
+    fn test_parabolic_collimator() {
+        let simulation = Simulation::new(parabolic_collimator());
+
+        for i in -4..=4 {
+            let result = simulation.trace(ray_from([0.0, 1.0], [i as f32 * 0.3, -1.0]), 2);
+
+            assert_eq!(result.len(), 1);
+            assert_eq!(result.termination, Termination::Escaped);
+            let up = SVector::from([0.0, 1.0]);
+            assert!((result.last_ray.direction.into_inner() - up).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_two_mirror_cavity() {
+        let simulation = Simulation::new(two_mirror_cavity());
+
+        let result = simulation.trace(ray_from([0.0, 0.1], [1.0, 0.05]), 200);
+
+        assert_eq!(result.len(), 200);
+        assert!(result.path.iter().all(|point| point[1].abs() < 0.5));
+    }
+
+    #[test]
+    fn test_corner_reflector() {
+        let simulation = Simulation::new(corner_reflector());
+
+        for direction in [[-1.0, -0.5], [-0.5, -1.0], [-1.0, -0.8]] {
+            let result = simulation.trace(ray_from([1.5, 1.5], direction), 3);
+
+            assert_eq!(result.len(), 2);
+            let back = -SVector::from(direction).normalize();
+            assert!((result.last_ray.direction.into_inner() - back).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_elliptical_focus() {
+        let simulation = Simulation::new(elliptical_focus());
+
+        for i in -4..=4 {
+            let result = simulation.trace(ray_from([-1.0, 0.0], [i as f32 * 0.4, -1.0]), 1);
+
+            assert_eq!(result.len(), 1);
+            assert!(distance_to_line(&result.last_ray, &[1.0, 0.0].into()) < 1e-4);
+        }
+    }
+}
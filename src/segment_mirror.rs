@@ -0,0 +1,194 @@
+use nalgebra::Point;
+
+use crate::{
+    error::MirrorError,
+    json, material,
+    math::magnitude,
+    mirror::{degenerate, enclosing_sphere, sample_parameters, Mirror, PlaneMirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// A flat mirror bounded by two endpoints, only meaningful in 2D.
+#[derive(Clone, Copy)]
+pub struct SegmentMirror {
+    start: Point<f32, DIM>,
+    end: Point<f32, DIM>,
+    // the infinite line going through both endpoints
+    plane: PlaneMirror,
+}
+
+impl Mirror for SegmentMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        self.plane
+            .reflect(ray)
+            .into_iter()
+            .filter(|(distance, _)| {
+                self.parameter_of(&(ray.origin + *distance * ray.direction.into_inner()))
+                    .is_some_and(|t| (0.0..=1.0).contains(&t))
+            })
+            .collect()
+    }
+    fn get_type(&self) -> String {
+        "segment".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn is_flat(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.distance_to(point) <= tolerance
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        sample_parameters(n)
+            .into_iter()
+            .map(|t| self.start + t * (self.end - self.start))
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.plane.reflectivity()
+    }
+    /// 0 at the start and 1 at the end.
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        Some((0.0, 1.0))
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        [self.start, self.end]
+            .iter()
+            .any(|endpoint| (point - endpoint).norm() <= tolerance)
+    }
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        enclosing_sphere(&[(self.start, 0.0), (self.end, 0.0)])
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if (self.end - self.start).norm() <= f32::EPSILON * magnitude([&self.start, &self.end]) {
+            degenerate(self, "its endpoints are the same")
+        } else {
+            self.plane.validate()
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "start": json::from_point(&self.start),
+            "end": json::from_point(&self.end),
+            "reflectivity": self.reflectivity(),
+        }))
+    }
+}
+
+impl SegmentMirror {
+    pub fn new(start: Point<f32, DIM>, end: Point<f32, DIM>) -> Self {
+        Self {
+            start,
+            end,
+            plane: PlaneMirror::new([start, end]),
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            plane: self.plane.with_reflectivity(reflectivity),
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "start": [1.0, 2.0],
+            "end": [3.0, 4.0],
+            "material": "silver" // or "reflectivity": 0.97, optional
+        }
+         */
+        let start = json::point(json, "start")?;
+        let end = json::point(json, "end")?;
+
+        Ok(Self::new(start, end).with_reflectivity(material::reflectivity_from_json(json)?))
+    }
+
+    pub fn start(&self) -> Point<f32, DIM> {
+        self.start
+    }
+
+    pub fn end(&self) -> Point<f32, DIM> {
+        self.end
+    }
+
+    /// Distance from `point` to the closest point of the segment.
+    pub fn distance_to(&self, point: &Point<f32, DIM>) -> f32 {
+        let t = self.parameter_of(point).unwrap_or(0.0).clamp(0.0, 1.0);
+        (point - (self.start + t * (self.end - self.start))).norm()
+    }
+
+    // position of the projection of `point` on the segment's line, 0 at the start and 1 at the end
+    fn parameter_of(&self, point: &Point<f32, DIM>) -> Option<f32> {
+        let direction = self.end - self.start;
+        let length_squared = direction.norm_squared();
+
+        (length_squared > 0.0).then(|| (point - self.start).dot(&direction) / length_squared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ray::ray_from, scene::Scene};
+    use nalgebra::SVector;
+
+    #[test]
+    fn test_reflect() {
+        let mirror = SegmentMirror::new([0.0, 0.0].into(), [1.0, 0.0].into());
+
+        let reflections = mirror.reflect(ray_from([0.5, 1.0], [0.0, -1.0]));
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 1.0).abs() < 1e-6);
+
+        // the infinite line would be hit, but not the segment
+        assert!(mirror.reflect(ray_from([1.5, 1.0], [0.0, -1.0])).is_empty());
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let mirror = SegmentMirror::new([0.0, 0.0].into(), [1.0, 0.0].into());
+
+        assert!(mirror.contains_point(&[0.5, 0.0].into(), 1e-6));
+        assert!(!mirror.contains_point(&[2.0, 0.0].into(), 1e-6));
+        assert!((mirror.distance_to(&[2.0, 0.0].into()) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = serde_json::json!({
+            "start": [1.0, 2.0],
+            "end": [3.0, 4.0]
+        });
+
+        let mirror = SegmentMirror::from_json(&json).unwrap();
+
+        assert_eq!(mirror.start(), Point::from([1.0, 2.0]));
+        assert_eq!(mirror.end(), Point::from([3.0, 4.0]));
+        assert!(mirror.plane.normal().dot(&SVector::from([1.0, 1.0])).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_hit_on_boundary() {
+        let scene = Scene::new(vec![Box::new(SegmentMirror::new(
+            [0.0, 0.0].into(),
+            [1.0, 0.0].into(),
+        ))]);
+
+        let (_, at_end) = scene
+            .nearest_hit(&ray_from([1.0, 1.0], [0.0, -1.0]))
+            .unwrap();
+        assert_eq!(at_end.point, [1.0, 0.0].into());
+        assert!(at_end.on_boundary);
+
+        let (_, inside) = scene
+            .nearest_hit(&ray_from([0.5, 1.0], [0.0, -1.0]))
+            .unwrap();
+        assert!(!inside.on_boundary);
+    }
+}
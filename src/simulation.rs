@@ -0,0 +1,1315 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI},
+    ops::{ControlFlow, Deref},
+    time::Instant,
+};
+
+use nalgebra::{Complex, Point, SVector, Unit};
+
+use crate::{
+    grin::GrinMedium,
+    intersection::Intersection,
+    material::Polarization,
+    math::magnitude,
+    mirror::{Mirror, PlaneMirror},
+    ray::Ray,
+    scene::{Scene, EPSILON},
+    DIM,
+};
+
+pub struct Simulation {
+    scene: Scene,
+    /// The medium between the mirrors, or a uniform one where rays go straight.
+    medium: Option<GrinMedium>,
+}
+
+/// Why the tracing of a ray stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The ray doesn't hit any mirror anymore.
+    Escaped,
+    /// The ray was still bouncing when the bounce limit was reached.
+    MaxBounces,
+    /// The caller of [`Simulation::trace_progressive`] asked to stop.
+    Stopped,
+    /// The ray hit the target of [`Simulation::trace_until`], which is its last point.
+    ReachedTarget,
+    /// The ray hit an opaque mirror, or a mirror beyond its
+    /// [`crate::mirror::Mirror::max_incidence_angle`], which absorbed it. The hit is the last
+    /// point of the path, but isn't a reflection.
+    Absorbed,
+    /// The intensity of the ray fell below the threshold of [`Simulation::trace_adaptive`].
+    Dimmed,
+}
+
+/// The outcome of tracing a ray. It dereferences to the intersections of every bounce, so
+/// they can be iterated directly.
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceResult {
+    /// The origin of the ray, followed by every reflection point, and in a gradient-index
+    /// medium the points of the curved path between them.
+    pub path: Vec<Point<f32, DIM>>,
+    /// Every reflection, in order.
+    pub intersections: Vec<Intersection>,
+    /// The ray leaving the last reflection point (or the initial ray if nothing was hit). An
+    /// absorbed ray is left where it was absorbed, still going the way it came.
+    pub last_ray: Ray,
+    pub termination: Termination,
+}
+
+impl TraceResult {
+    // a ray which didn't bounce yet
+    fn new(ray: Ray) -> Self {
+        Self {
+            path: vec![ray.origin],
+            intersections: vec![],
+            last_ray: ray,
+            termination: Termination::Escaped,
+        }
+    }
+
+    // the ray stops at the intersection, going no further
+    fn absorb(&mut self, intersection: Intersection) {
+        self.last_ray.origin = intersection.point;
+        self.last_ray.time += intersection.distance;
+        self.path.push(intersection.point);
+        self.termination = Termination::Absorbed;
+    }
+
+    fn bounce(&mut self, intersection: Intersection) {
+        self.last_ray = intersection.reflected_ray(&self.last_ray);
+        self.path.push(intersection.point);
+        self.intersections.push(intersection);
+    }
+}
+
+impl TraceResult {
+    /// Where the ray lands on `detector`, in its own coordinates: the distance from the
+    /// detector's first point along the edge towards each of its other points, `0.0` for axes
+    /// the scene doesn't have (the second one in 2D).
+    ///
+    /// A path ending on the detector, because it was reflected or absorbed there last, lands
+    /// at its last point. Otherwise the last ray is followed to the detector, and `None` is
+    /// returned if it never gets there.
+    pub fn detector_position(&self, detector: &PlaneMirror) -> Option<(f32, f32)> {
+        let end = *self.path.last().unwrap();
+        let tolerance = EPSILON * magnitude([&end]).max(1.0);
+        let hit = if self.path.len() > 1 && detector.contains_point(&end, tolerance) {
+            end
+        } else {
+            let (distance, _) = *detector.reflect(self.last_ray).first()?;
+            self.last_ray.origin + distance * self.last_ray.direction.into_inner()
+        };
+
+        let coordinate = |axis: usize| {
+            detector.points().get(axis).map_or(0.0, |point| {
+                let edge = point - detector.points()[0];
+                (hit - detector.points()[0]).dot(&edge) / edge.norm()
+            })
+        };
+        Some((coordinate(1), coordinate(2)))
+    }
+
+    /// The pixel of `detector` the ray lands on, see [`TraceResult::detector_position`],
+    /// with square pixels of side `pixel_pitch`. Pixel `(0, 0)` starts at the detector's first
+    /// point, and pixels on the other side of it have negative indices.
+    pub fn detector_hit(&self, detector: &PlaneMirror, pixel_pitch: f32) -> Option<(i32, i32)> {
+        let (u, v) = self.detector_position(detector)?;
+        Some((
+            (u / pixel_pitch).floor() as i32,
+            (v / pixel_pitch).floor() as i32,
+        ))
+    }
+
+    /// A table of every bounce, one row each after a header: the hit point, the angle of
+    /// incidence in degrees, the normal of the mirror facing the incoming ray, and the
+    /// intensity of the ray leaving the mirror, starting at 1 and multiplied by the
+    /// reflectivity of every mirror (see [`Intersection::reflectivity`]).
+    ///
+    /// This is the human-readable counterpart of [`Simulation::trace_recorded`].
+    pub fn report(&self) -> String {
+        let vector = |coords: &[f32]| {
+            let coords = coords.iter().map(|c| format!("{c:.4}")).collect::<Vec<_>>();
+            format!("({})", coords.join(", "))
+        };
+
+        let mut report = format!(
+            "{:>6}  {:>24}  {:>9}  {:>20}  {:>9}\n",
+            "bounce", "hit point", "incidence", "normal", "intensity"
+        );
+        // the hits are in the path after the origin, but in a gradient-index medium the
+        // points of the curve between them are too, and the ray arrives from the last one
+        let (mut intensity, mut next) = (1.0, 1);
+        for (bounce, intersection) in self.intersections.iter().enumerate() {
+            let at = next
+                + self.path[next..]
+                    .iter()
+                    .position(|point| *point == intersection.point)
+                    .unwrap_or(0);
+            let incoming = Unit::new_normalize(intersection.point - self.path[at - 1]);
+            let incidence = intersection.sin2_incidence(&incoming).sqrt().asin();
+            intensity *= intersection.reflectivity;
+            next = at + 1;
+
+            report += &format!(
+                "{:>6}  {:>24}  {:>8.2}°  {:>20}  {:>9.4}\n",
+                bounce + 1,
+                vector(intersection.point.coords.as_slice()),
+                incidence.to_degrees(),
+                vector(intersection.facing_normal(&incoming).as_slice()),
+                intensity,
+            );
+        }
+
+        report
+    }
+}
+
+impl Deref for TraceResult {
+    type Target = [Intersection];
+
+    fn deref(&self) -> &Self::Target {
+        &self.intersections
+    }
+}
+
+/// The light a mirror received during [`Simulation::heat_load`], which its thermal load is
+/// proportional to.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MirrorLoad {
+    /// Number of times a ray hit the mirror, whether it was reflected or absorbed there.
+    pub hits: usize,
+    /// Sum of the intensities of the rays reaching the mirror, each ray starting at 1 and
+    /// losing intensity with the reflectivity of every mirror it bounces on.
+    pub intensity: f32,
+}
+
+/// Everything about one reflection of a ray, as recorded by [`Simulation::trace_recorded`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BounceRecord {
+    /// Index of the mirror in [`Scene::mirrors`].
+    pub mirror_index: usize,
+    /// The ray reaching the mirror. In a gradient-index medium, it starts on the last chord of
+    /// the curved path rather than on the previous mirror.
+    pub incident_ray: Ray,
+    /// The ray leaving the mirror, from the hit point.
+    pub reflected_ray: Ray,
+    pub hit_point: Point<f32, DIM>,
+    /// The normal of the mirror at the hit point, facing the incident ray.
+    pub normal: Unit<SVector<f32, DIM>>,
+}
+
+impl Simulation {
+    pub fn new(scene: Scene) -> Self {
+        Self {
+            scene,
+            medium: None,
+        }
+    }
+
+    /// Fills the space between the mirrors with a gradient-index medium, so rays bend between
+    /// reflections. Their paths then also go through the points of the curve between bounces.
+    pub fn with_medium(self, medium: GrinMedium) -> Self {
+        Self {
+            medium: Some(medium),
+            ..self
+        }
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// The scene can be edited between traces, nothing about it is cached.
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+
+    /// Follows `ray` through the mirrors, reflecting at most `max_bounces` times.
+    ///
+    /// Intersections behind the ray (negative distances) are never reflected on, so a ray
+    /// which has every mirror behind it escapes right away with its origin as the only point.
+    pub fn trace(&self, ray: Ray, max_bounces: usize) -> TraceResult {
+        self.trace_with(ray, max_bounces, None, |_, _, _, _| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Follows `ray` like [`Simulation::trace`] for as long as it is bright enough: its
+    /// intensity starts at 1 and is multiplied by the reflectivity of every mirror it bounces
+    /// on, and tracing stops with [`Termination::Dimmed`] once it is below `min_intensity`.
+    /// Bright rays in a cavity would bounce forever, so `max_bounces` still caps the trace.
+    pub fn trace_adaptive(&self, ray: Ray, min_intensity: f32, max_bounces: usize) -> TraceResult {
+        let mut intensity = 1.0;
+
+        self.trace_with(
+            ray,
+            max_bounces,
+            None,
+            |index, intersection, _, absorbed| {
+                if absorbed {
+                    return ControlFlow::Continue(());
+                }
+                intensity *= self.scene.mirrors()[index].reflectivity_at(&intersection.point);
+
+                if intensity < min_intensity {
+                    ControlFlow::Break(Termination::Dimmed)
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        )
+    }
+
+    /// Follows `ray` through the mirrors like [`Simulation::trace`], calling `on_bounce` after
+    /// every reflection so the caller can display the path as it grows and stop early.
+    ///
+    /// There is no bounce limit: unless the ray escapes, tracing goes on until `on_bounce`
+    /// returns [`ControlFlow::Break`].
+    pub fn trace_progressive(
+        &self,
+        ray: Ray,
+        mut on_bounce: impl FnMut(&Intersection) -> ControlFlow<()>,
+    ) -> TraceResult {
+        self.trace_with(ray, usize::MAX, None, |_, intersection, _, absorbed| {
+            if !absorbed && on_bounce(intersection).is_break() {
+                ControlFlow::Break(Termination::Stopped)
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// Follows `ray` like [`Simulation::trace`], also recording every reflection in full, in
+    /// order. The hit points of the records are the path without its origin (and without the
+    /// points of the curve in a gradient-index medium), and an absorbing hit isn't recorded.
+    pub fn trace_recorded(&self, ray: Ray, max_bounces: usize) -> (TraceResult, Vec<BounceRecord>) {
+        let mut records = vec![];
+
+        let result = self.trace_with(
+            ray,
+            max_bounces,
+            None,
+            |index, intersection, result, absorbed| {
+                if !absorbed {
+                    let incident_ray = result.last_ray;
+                    records.push(BounceRecord {
+                        mirror_index: index,
+                        incident_ray,
+                        reflected_ray: intersection.reflected_ray(&incident_ray),
+                        hit_point: intersection.point,
+                        normal: intersection.facing_normal(&incident_ray.direction),
+                    });
+                }
+                ControlFlow::Continue(())
+            },
+        );
+
+        (result, records)
+    }
+
+    /// Follows `ray` like [`Simulation::trace`], carrying light linearly polarized at
+    /// `polarization_angle` radians from the s direction (perpendicular to the plane of
+    /// incidence, out of the scene). Every reflection scales the s and p parts of the field
+    /// by the square roots of [`Mirror::polarized_reflectivity`] and retards the p part by
+    /// [`Mirror::retardance`], which can turn the light elliptical.
+    ///
+    /// Also returns, for every reflection, how much it rotated the polarization plane (the
+    /// major axis of the ellipse), in radians within `[-π/2, π/2)`. Their sum is the rotation
+    /// accumulated over the whole trace.
+    pub fn trace_polarized(
+        &self,
+        ray: Ray,
+        max_bounces: usize,
+        polarization_angle: f32,
+    ) -> (TraceResult, Vec<f32>) {
+        let mut rotations = vec![];
+        // the jones vector of the field, as its s and p parts
+        let mut field = (
+            Complex::new(polarization_angle.cos(), 0.0),
+            Complex::new(polarization_angle.sin(), 0.0),
+        );
+
+        let result = self.trace_with(
+            ray,
+            max_bounces,
+            None,
+            |index, intersection, result, absorbed| {
+                if absorbed {
+                    return ControlFlow::Continue(());
+                }
+                let mirror = &self.scene.mirrors()[index];
+                let sin2 = intersection.sin2_incidence(&result.last_ray.direction);
+                let (sin, cos) = mirror.retardance(sin2).sin_cos();
+                let before = orientation(field);
+                field = (
+                    field.0 * mirror.polarized_reflectivity(Polarization::S, sin2).sqrt(),
+                    field.1
+                        * mirror.polarized_reflectivity(Polarization::P, sin2).sqrt()
+                        * Complex::new(cos, sin),
+                );
+                rotations
+                    .push((orientation(field) - before + FRAC_PI_2).rem_euclid(PI) - FRAC_PI_2);
+
+                ControlFlow::Continue(())
+            },
+        );
+
+        (result, rotations)
+    }
+
+    /// Follows `ray` like [`Simulation::trace`] until it hits the mirror named `target_name`,
+    /// which ends the path with [`Termination::ReachedTarget`]. The hit on the target is
+    /// included, so the last point of the path is where the ray reached it, and only counts as
+    /// a reflection if the target doesn't absorb the ray, e.g. an opaque detector.
+    ///
+    /// If no mirror has that name, the ray is traced as usual and never reaches it.
+    pub fn trace_until(&self, ray: Ray, target_name: &str, max_bounces: usize) -> TraceResult {
+        let target = self.scene.find(target_name);
+
+        self.trace_with(ray, max_bounces, None, |index, _, _, _| {
+            if Some(index) == target {
+                ControlFlow::Break(Termination::ReachedTarget)
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// Traces every ray like [`Simulation::trace`], returning their results in the same order.
+    ///
+    /// With the `coherent` feature, the rays are first bucketed by the direction they go in, so
+    /// rays likely to hit the same mirrors are traced one after the other while those mirrors'
+    /// data is still in cache. The results don't change.
+    pub fn trace_many(&self, rays: &[Ray], max_bounces: usize) -> Vec<TraceResult> {
+        #[cfg(feature = "coherent")]
+        {
+            // a counting sort, the sectors being known in advance
+            let sectors = rays.iter().map(direction_sector).collect::<Vec<_>>();
+            let mut starts = vec![0; DIRECTION_SECTORS + 1];
+            for sector in &sectors {
+                starts[sector + 1] += 1;
+            }
+            for sector in 0..DIRECTION_SECTORS {
+                starts[sector + 1] += starts[sector];
+            }
+            let mut order = vec![0; rays.len()];
+            for (i, sector) in sectors.iter().enumerate() {
+                order[starts[*sector]] = i;
+                starts[*sector] += 1;
+            }
+
+            let mut results = vec![None; rays.len()];
+            for i in order {
+                results[i] = Some(self.trace(rays[i], max_bounces));
+            }
+            results.into_iter().flatten().collect()
+        }
+
+        #[cfg(not(feature = "coherent"))]
+        rays.iter()
+            .map(|ray| self.trace(*ray, max_bounces))
+            .collect()
+    }
+
+    /// Traces a bundle of rays going roughly the same way, returning the same results as
+    /// [`Simulation::trace`] in the same order. The mirrors the rays may hit first are culled
+    /// once for the whole bundle (see [`Scene::bundle_candidates`]), and each ray is only
+    /// intersected with those until its first bounce. Rays spread out after that, so later
+    /// bounces look at the whole scene.
+    ///
+    /// In a gradient-index medium, rays don't go straight, and are traced one by one.
+    pub fn trace_bundle(&self, rays: &[Ray], max_bounces: usize) -> Vec<TraceResult> {
+        if self.medium.is_some() {
+            return rays
+                .iter()
+                .map(|ray| self.trace(*ray, max_bounces))
+                .collect();
+        }
+
+        let candidates = self.scene.bundle_candidates(rays);
+        rays.iter()
+            .map(|ray| {
+                self.trace_with(*ray, max_bounces, Some(&candidates), |_, _, _, _| {
+                    ControlFlow::Continue(())
+                })
+            })
+            .collect()
+    }
+
+    /// Traces the rays in order like [`Simulation::trace`] until `deadline`, so a heavy scene
+    /// doesn't freeze an interactive app. Returns the results of the rays traced so far and
+    /// the number of rays left, which are the last ones of `rays`. A ray being traced when the
+    /// deadline passes is finished first.
+    pub fn trace_many_budgeted(
+        &self,
+        rays: &[Ray],
+        max_bounces: usize,
+        deadline: Instant,
+    ) -> (Vec<TraceResult>, usize) {
+        let mut results = vec![];
+        for ray in rays {
+            if Instant::now() >= deadline {
+                break;
+            }
+            results.push(self.trace(*ray, max_bounces));
+        }
+
+        let remaining = rays.len() - results.len();
+        (results, remaining)
+    }
+
+    /// Traces one ray per wavelength, all starting from `origin` towards `direction`, and
+    /// returns their results in the same order as `wavelengths`. Only dispersive mirrors,
+    /// like [`crate::grating_mirror::GratingMirror`], make them diverge.
+    pub fn trace_spectrum(
+        &self,
+        origin: Point<f32, DIM>,
+        direction: SVector<f32, DIM>,
+        wavelengths: &[f32],
+        max_bounces: usize,
+    ) -> Vec<TraceResult> {
+        wavelengths
+            .iter()
+            .map(|wavelength| {
+                let ray = Ray::new(origin, direction).with_wavelength(*wavelength);
+                self.trace(ray, max_bounces)
+            })
+            .collect()
+    }
+
+    /// Traces every ray like [`Simulation::trace`], tallying the hits on each mirror, keyed by
+    /// its index in [`Scene::mirrors`]. A ray absorbed by a mirror counts as a hit all the same,
+    /// its light heating the mirror too. Mirrors which were never hit are left out.
+    pub fn heat_load(&self, rays: &[Ray], max_bounces: usize) -> HashMap<usize, MirrorLoad> {
+        let mut loads = HashMap::<usize, MirrorLoad>::new();
+
+        for ray in rays {
+            let mut intensity = 1.0;
+
+            let _ = self.trace_with(
+                *ray,
+                max_bounces,
+                None,
+                |index, intersection, _, absorbed| {
+                    let load = loads.entry(index).or_default();
+                    load.hits += 1;
+                    load.intensity += intensity;
+
+                    // absorbed light heats the mirror all the same
+                    if !absorbed {
+                        intensity *=
+                            self.scene.mirrors()[index].reflectivity_at(&intersection.point);
+                    }
+                    ControlFlow::Continue(())
+                },
+            );
+        }
+
+        loads
+    }
+
+    /// The loop behind every trace: follows `ray` through the mirrors, reflecting at most
+    /// `max_bounces` times. When `candidates` are given, the first hit is only looked for among
+    /// them, see [`Scene::nearest_hit_among`].
+    ///
+    /// `on_hit` is called on every hit before the ray is reflected or absorbed there, with the
+    /// index of the mirror, the intersection, the result so far (whose last ray is the one
+    /// hitting the mirror) and whether the mirror absorbs the ray. Breaking still applies the
+    /// hit, then ends the trace with the given termination.
+    fn trace_with(
+        &self,
+        ray: Ray,
+        max_bounces: usize,
+        candidates: Option<&[usize]>,
+        mut on_hit: impl FnMut(usize, &Intersection, &TraceResult, bool) -> ControlFlow<Termination>,
+    ) -> TraceResult {
+        let (mut result, mut previous) = (TraceResult::new(ray), None);
+
+        for bounce in 0..max_bounces {
+            let hit = match candidates {
+                Some(candidates) if bounce == 0 => self.scene.nearest_hit_among(&ray, candidates),
+                _ => self.next_hit(&mut result, previous),
+            };
+            let Some((index, intersection)) = hit else {
+                return result;
+            };
+
+            let absorbed = self.absorbs(index, &intersection, &result.last_ray);
+            let flow = on_hit(index, &intersection, &result, absorbed);
+            if absorbed {
+                result.absorb(intersection);
+            } else {
+                result.bounce(intersection);
+                previous = Some(index);
+            }
+
+            match flow {
+                ControlFlow::Break(termination) => {
+                    result.termination = termination;
+                    return result;
+                }
+                ControlFlow::Continue(()) if absorbed => return result,
+                ControlFlow::Continue(()) => {}
+            }
+        }
+
+        result.termination = Termination::MaxBounces;
+        result
+    }
+
+    /// Whether the mirror at `index` absorbs `ray` hitting it at `intersection`, see
+    /// [`absorbs`].
+    fn absorbs(&self, index: usize, intersection: &Intersection, ray: &Ray) -> bool {
+        absorbs(&*self.scene.mirrors()[index], intersection, ray)
+    }
+
+    /// The next mirror hit by the last ray of `result`, as its index and the intersection.
+    /// `previous` is the mirror the ray just left, see [`Scene::nearest_hit_after`].
+    ///
+    /// In a gradient-index medium, the ray is moved along its curved path one step at a time,
+    /// each step being a straight chord, until a chord hits a mirror. The points of the curve are
+    /// added to the path, and the last ray is left on the chord reaching the mirror.
+    fn next_hit(
+        &self,
+        result: &mut TraceResult,
+        mut previous: Option<usize>,
+    ) -> Option<(usize, Intersection)> {
+        let Some(medium) = &self.medium else {
+            return self.scene.nearest_hit_after(&result.last_ray, previous);
+        };
+
+        let steps = (medium.max_length() / medium.step()).ceil() as usize;
+        for _ in 0..steps {
+            let next = medium.advance(&result.last_ray);
+            let chord = next.origin - result.last_ray.origin;
+            // a chord of length 0 means the ray is stuck, e.g. where the index blows up
+            let chord_ray = Ray {
+                direction: Unit::try_new(chord, f32::EPSILON)?,
+                ..result.last_ray
+            };
+
+            if let Some(hit) = self
+                .scene
+                .nearest_hit_after(&chord_ray, previous)
+                .filter(|(_, intersection)| intersection.distance <= chord.norm())
+            {
+                result.last_ray = chord_ray;
+                return Some(hit);
+            }
+            // a curved path may come back to the mirror it left
+            previous = None;
+
+            result.path.push(next.origin);
+            result.last_ray = next;
+        }
+
+        None
+    }
+
+    /// Traces `ray` with its direction reversed. Since reflections are reversible,
+    /// starting from the end of a forward path retraces it back to its origin.
+    pub fn trace_backward(&self, ray: Ray, max_bounces: usize) -> TraceResult {
+        self.trace(
+            Ray {
+                direction: -ray.direction,
+                ..ray
+            },
+            max_bounces,
+        )
+    }
+}
+
+/// Number of direction sectors rays are bucketed in by [`Simulation::trace_many`].
+#[cfg(feature = "coherent")]
+const DIRECTION_SECTORS: usize = 64;
+
+/// The sector of the direction of `ray` in its first two coordinates, out of
+/// [`DIRECTION_SECTORS`] equal ones around the full turn. Costs a single `atan2`, unlike
+/// guessing the first mirror hit from every bounding sphere.
+#[cfg(feature = "coherent")]
+fn direction_sector(ray: &Ray) -> usize {
+    let angle = ray.direction[1].atan2(ray.direction[0]);
+    let sector = ((angle + PI) / (2.0 * PI) * DIRECTION_SECTORS as f32) as usize;
+    sector.min(DIRECTION_SECTORS - 1)
+}
+
+/// Whether `mirror` absorbs `ray` hitting it at `intersection` instead of reflecting it,
+/// because it is opaque or the angle of incidence is beyond its
+/// [`crate::mirror::Mirror::max_incidence_angle`].
+pub(crate) fn absorbs(mirror: &dyn Mirror, intersection: &Intersection, ray: &Ray) -> bool {
+    mirror.is_opaque()
+        || mirror.max_incidence_angle().is_some_and(|max| {
+            let sin2 = intersection.sin2_incidence(&ray.direction);
+            sin2.sqrt().asin() > max
+        })
+}
+
+/// Angle of the major axis of the polarization ellipse of the jones vector `(s, p)`, from
+/// the s direction.
+fn orientation((s, p): (Complex<f32>, Complex<f32>)) -> f32 {
+    0.5 * (2.0 * (s.conj() * p).re).atan2(s.norm_sqr() - p.norm_sqr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        anisotropic_mirror::AnisotropicMirror,
+        bezier_mirror::BezierMirror,
+        grating_mirror::GratingMirror,
+        mirror::{Mirror, PlaneMirror, Reflections, SphereMirror},
+        opaque_mirror::OpaqueMirror,
+        ray::{ray_from, DEFAULT_WAVELENGTH},
+        segment_mirror::SegmentMirror,
+    };
+    use nalgebra::{SMatrix, Unit};
+
+    fn single_plane() -> Simulation {
+        Simulation::new(Scene::new(vec![Box::new(PlaneMirror::new([
+            [0.0, 0.0].into(),
+            [1.0, 0.0].into(),
+        ]))]))
+    }
+
+    #[test]
+    fn test_trace() {
+        let simulation = single_plane();
+
+        let result = simulation.trace(ray_from([-1.0, 1.0], [1.0, -1.0]), 10);
+
+        assert_eq!(result.termination, Termination::Escaped);
+        assert_eq!(result.path.len(), 2);
+        assert!((result.path[1] - Point::from([0.0, 0.0])).norm() < 1e-6);
+        let expected = SVector::<f32, DIM>::from([1.0, 1.0]).normalize();
+        assert!((result.last_ray.direction.into_inner() - expected).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_trace_escapes_when_mirrors_are_behind() {
+        let simulation = single_plane();
+        let ray = ray_from([0.0, 1.0], [0.0, 1.0]);
+
+        let result = simulation.trace(ray, 10);
+
+        assert_eq!(result.termination, Termination::Escaped);
+        assert_eq!(result.path, vec![ray.origin]);
+        assert_eq!(result.last_ray, ray);
+    }
+
+    #[test]
+    fn test_trace_ignores_negative_distances() {
+        struct BehindMirror;
+
+        impl Mirror for BehindMirror {
+            fn reflect(&self, _ray: Ray) -> Reflections {
+                smallvec::smallvec![(-1.0, Unit::new_unchecked(-SMatrix::identity()))]
+            }
+            fn get_type(&self) -> String {
+                "behind".to_string()
+            }
+            fn is_analytic(&self) -> bool {
+                true
+            }
+            fn contains_point(&self, _point: &Point<f32, DIM>, _tolerance: f32) -> bool {
+                false
+            }
+            fn sample_surface(&self, _n: usize) -> Vec<Point<f32, DIM>> {
+                vec![]
+            }
+        }
+
+        let simulation = Simulation::new(Scene::new(vec![Box::new(BehindMirror)]));
+        let ray = ray_from([0.0, 0.0], [1.0, 0.0]);
+
+        let result = simulation.trace(ray, 10);
+
+        assert_eq!(result.termination, Termination::Escaped);
+        assert_eq!(result.path.len(), 1);
+    }
+
+    #[test]
+    fn test_trace_max_bounces() {
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ]));
+
+        let result = simulation.trace(ray_from([0.0, 0.5], [1.0, 1.0]), 5);
+
+        assert_eq!(result.termination, Termination::MaxBounces);
+        assert_eq!(result.path.len(), 6);
+    }
+
+    #[test]
+    fn test_trace_backward() {
+        let simulation = single_plane();
+        let origin = Point::from([-1.0, 2.0]);
+
+        let forward = simulation.trace(Ray::new(origin, [1.0, -2.0].into()), 10);
+        assert_eq!(forward.path.len(), 2);
+
+        // start somewhere along the outgoing ray and go back
+        let end = forward.last_ray.origin + 3.0 * forward.last_ray.direction.into_inner();
+        let backward = simulation.trace_backward(
+            Ray {
+                origin: end,
+                direction: forward.last_ray.direction,
+                wavelength: DEFAULT_WAVELENGTH,
+                time: 0.0,
+            },
+            10,
+        );
+
+        assert_eq!(backward.termination, Termination::Escaped);
+        assert_eq!(backward.path.len(), 2);
+        assert!((backward.path[1] - forward.path[1]).norm() < 1e-5);
+
+        // the ray leaving the mirror goes straight back to the original origin
+        let to_origin = origin - backward.last_ray.origin;
+        assert!((to_origin.normalize() - backward.last_ray.direction.into_inner()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_trace_progressive_stops() {
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ]));
+
+        let mut distances = vec![];
+        let result =
+            simulation.trace_progressive(ray_from([0.0, 0.5], [1.0, 1.0]), |intersection| {
+                distances.push(intersection.distance);
+                if distances.len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+
+        assert_eq!(result.termination, Termination::Stopped);
+        assert_eq!(distances.len(), 2);
+        assert_eq!(result.path.len(), 3);
+        assert!((result.path[2] - Point::from([1.5, 0.0])).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_trace_progressive_escapes() {
+        let simulation = single_plane();
+
+        let mut bounces = 0;
+        let result = simulation.trace_progressive(ray_from([-1.0, 1.0], [1.0, -1.0]), |_| {
+            bounces += 1;
+            ControlFlow::Continue(())
+        });
+
+        assert_eq!(result.termination, Termination::Escaped);
+        assert_eq!(bounces, 1);
+        assert_eq!(
+            result.path,
+            simulation
+                .trace(ray_from([-1.0, 1.0], [1.0, -1.0]), 10)
+                .path
+        );
+    }
+
+    #[test]
+    fn test_heat_load() {
+        // a ray bouncing between a half-reflective mirror at y = 0 and a perfect one at y = 1
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(
+                PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]).with_reflectivity(0.5),
+            ),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ]));
+        let rays = [
+            ray_from([0.0, 0.5], [1.0, -1.0]),
+            ray_from([0.0, 0.5], [-1.0, -1.0]),
+        ];
+
+        let loads = simulation.heat_load(&rays, 4);
+
+        let bounces = rays
+            .iter()
+            .map(|ray| simulation.trace(*ray, 4).len())
+            .sum::<usize>();
+        assert_eq!(loads.values().map(|load| load.hits).sum::<usize>(), bounces);
+
+        // each ray hits y = 0 with 1 and 0.5, and y = 1 with 0.5 and 0.25
+        assert_eq!(loads[&0].hits, 4);
+        assert!((loads[&0].intensity - 3.0).abs() < 1e-6);
+        assert_eq!(loads[&1].hits, 4);
+        assert!((loads[&1].intensity - 1.5).abs() < 1e-6);
+
+        assert!(simulation
+            .heat_load(&[ray_from([0.0, 0.5], [1.0, 0.0])], 4)
+            .is_empty());
+
+        // the ray is absorbed by y = 1 after bouncing once on y = 0, which still counts as a hit
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(
+                PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]).with_reflectivity(0.5),
+            ),
+            Box::new(OpaqueMirror::new(Box::new(SegmentMirror::new(
+                [-2.0, 1.0].into(),
+                [2.0, 1.0].into(),
+            )))),
+        ]));
+
+        let loads = simulation.heat_load(&rays[..1], 4);
+
+        assert_eq!(loads[&0].hits, 1);
+        assert!((loads[&0].intensity - 1.0).abs() < 1e-6);
+        assert_eq!(loads[&1].hits, 1);
+        assert!((loads[&1].intensity - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_trace_after_edit() {
+        let mut simulation = single_plane();
+        let ray = ray_from([-1.0, 1.0], [1.0, -1.0]);
+        assert_eq!(simulation.trace(ray, 10).path.len(), 2);
+
+        let index = simulation
+            .scene_mut()
+            .add_mirror(Box::new(PlaneMirror::new([
+                [0.0, 2.0].into(),
+                [1.0, 2.0].into(),
+            ])));
+        assert_eq!(simulation.trace(ray, 10).path.len(), 11);
+
+        simulation.scene_mut().remove_mirror(index);
+        assert_eq!(simulation.trace(ray, 10).path.len(), 2);
+    }
+
+    #[test]
+    fn test_trace_until() {
+        // a fold mirror sending the ray up to a detector at y = 2, backed by a mirror at y = 3
+        let simulation = Simulation::new(
+            Scene::new(vec![
+                Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 1.0].into()])),
+                Box::new(PlaneMirror::new([[0.0, 2.0].into(), [1.0, 2.0].into()])),
+                Box::new(PlaneMirror::new([[0.0, 3.0].into(), [1.0, 3.0].into()])),
+            ])
+            .with_name(1, "detector"),
+        );
+        let ray = ray_from([-1.0, 1.0], [1.0, 0.0]);
+
+        let result = simulation.trace_until(ray, "detector", 10);
+        assert_eq!(result.termination, Termination::ReachedTarget);
+        assert_eq!(result.path.len(), 3);
+        assert!((result.path[2] - Point::from([1.0, 2.0])).norm() < 1e-5);
+
+        let result = simulation.trace_until(ray, "camera", 3);
+        assert_eq!(result.termination, Termination::MaxBounces);
+
+        // an opaque detector absorbs the ray, which still reached it
+        let simulation = Simulation::new(
+            Scene::new(vec![
+                Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 1.0].into()])),
+                Box::new(OpaqueMirror::new(Box::new(SegmentMirror::new(
+                    [0.0, 2.0].into(),
+                    [2.0, 2.0].into(),
+                )))),
+            ])
+            .with_name(1, "detector"),
+        );
+        let result = simulation.trace_until(ray, "detector", 10);
+        assert_eq!(result.termination, Termination::ReachedTarget);
+        assert_eq!(result.len(), 1);
+        assert!((result.path[2] - Point::from([1.0, 2.0])).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_iterate_bounces() {
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+        ]));
+
+        let result = simulation.trace(ray_from([0.0, 0.5], [1.0, 1.0]), 4);
+
+        assert_eq!(result.len(), 4);
+        let length = result
+            .iter()
+            .map(|intersection| intersection.distance)
+            .sum::<f32>();
+        let expected = result
+            .path
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).norm())
+            .sum::<f32>();
+        assert!((length - expected).abs() < 1e-5);
+        assert!((length - 3.5 * 2f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_trace_spectrum() {
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        let simulation = Simulation::new(Scene::new(vec![Box::new(GratingMirror::new(
+            plane, 1500.0, 1,
+        ))]));
+
+        let results = simulation.trace_spectrum(
+            [0.0, 1.0].into(),
+            [0.0, -1.0].into(),
+            &[450.0, 550.0, 650.0],
+            10,
+        );
+
+        assert_eq!(results.len(), 3);
+        for (result, wavelength) in results.iter().zip([450.0, 550.0, 650.0]) {
+            assert_eq!(result.path.len(), 2);
+            assert_eq!(result.last_ray.wavelength, wavelength);
+            assert!((result.last_ray.direction[0] - wavelength / 1500.0).abs() < 1e-5);
+        }
+
+        // longer wavelengths are diffracted further
+        let angles = results
+            .iter()
+            .map(|result| result.last_ray.direction[0].asin())
+            .collect::<Vec<_>>();
+        assert!(angles[0] < angles[1] && angles[1] < angles[2]);
+    }
+
+    #[test]
+    fn test_trace_scale_invariance() {
+        let trace_at = |scale: f32| {
+            let point = |x: f32, y: f32| Point::from([x * scale, y * scale]);
+            let simulation = Simulation::new(Scene::new(vec![
+                Box::new(SphereMirror::new(point(0.0, 0.0), 5.0 * scale)),
+                Box::new(SegmentMirror::new(point(-1.0, 2.0), point(2.0, 3.0))),
+                Box::new(BezierMirror::new(vec![
+                    point(-2.0, -3.0),
+                    point(0.0, -1.0),
+                    point(2.0, -3.5),
+                ])),
+            ]));
+
+            simulation.trace(Ray::new(point(0.3, 0.1), [1.0, 0.7].into()), 8)
+        };
+
+        let reference = trace_at(1.0);
+        assert_eq!(reference.path.len(), 9);
+
+        for scale in [1e-6, 1e6] {
+            let result = trace_at(scale);
+
+            assert_eq!(result.termination, reference.termination);
+            assert_eq!(result.path.len(), reference.path.len());
+            for (point, expected) in result.path.iter().zip(&reference.path) {
+                assert!((point / scale - expected).norm() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_trace_through_grin_medium() {
+        // with n^2 = 1 + 0.2y, n * ∇n is constant, so rays follow parabolas: starting
+        // horizontally from the origin, y = 0.05x^2
+        let index = |point: &Point<f32, DIM>| (1.0 + 0.2 * point[1]).sqrt();
+        let detector = PlaneMirror::new([[2.0, 0.0].into(), [2.0, 1.0].into()]);
+        let simulation = Simulation::new(Scene::new(vec![Box::new(detector)]))
+            .with_medium(GrinMedium::numeric(index, 10.0));
+
+        let result = simulation.trace(ray_from([0.0, 0.0], [1.0, 0.0]), 1);
+
+        assert_eq!(result.termination, Termination::MaxBounces);
+        assert_eq!(result.len(), 1);
+        assert!((result[0].point - Point::from([2.0, 0.2])).norm() < 1e-4);
+        // the path goes through the curve, which a straight ray would never leave
+        assert!(result.path.len() > 100);
+        for point in &result.path {
+            assert!((point[1] - 0.05 * point[0] * point[0]).abs() < 1e-4);
+        }
+
+        // without mirrors, the ray escapes once it went far enough
+        let empty = Simulation::new(Scene::new(vec![]))
+            .with_medium(GrinMedium::numeric(index, 1.0).with_step(0.125));
+        let result = empty.trace(ray_from([0.0, 0.0], [1.0, 0.0]), 1);
+        assert_eq!(result.termination, Termination::Escaped);
+        assert_eq!(result.path.len(), 9);
+    }
+
+    #[test]
+    fn test_trace_many() {
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(SphereMirror::new([4.0, 0.0].into(), 1.0)),
+            Box::new(SphereMirror::new([-4.0, 0.0].into(), 1.0)),
+            Box::new(SegmentMirror::new([0.0, 3.0].into(), [1.0, 3.0].into())),
+            Box::new(PlaneMirror::new([[0.0, -5.0].into(), [1.0, -5.0].into()])),
+        ]));
+        let rays = (0..64)
+            .map(|i| {
+                let angle = i as f32 * 0.41;
+                ray_from([0.3, 0.2], [angle.cos(), angle.sin()])
+            })
+            .collect::<Vec<_>>();
+
+        let results = simulation.trace_many(&rays, 5);
+
+        assert_eq!(results.len(), rays.len());
+        for (ray, result) in rays.iter().zip(&results) {
+            assert_eq!(*result, simulation.trace(*ray, 5));
+        }
+        assert!(simulation.trace_many(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_trace_does_not_hit_flat_mirror_twice() {
+        // tilted planes through the origin, where the EPSILON threshold vanishes
+        for angle in [0.1f32, 0.7, 1.3, 2.9] {
+            let (sin, cos) = angle.sin_cos();
+            let simulation = Simulation::new(Scene::new(vec![Box::new(PlaneMirror::new([
+                [-7.3 * cos, -7.3 * sin].into(),
+                [3.1 * cos, 3.1 * sin].into(),
+            ]))]));
+
+            let result = simulation.trace(ray_from([-0.3, 1.7], [0.3, -1.7]), 3);
+
+            assert_eq!(result.len(), 1, "re-hit the plane at angle {angle}");
+            assert_eq!(result.termination, Termination::Escaped);
+        }
+    }
+
+    #[test]
+    fn test_detector_hit() {
+        // a detector along y = 2 from x = -1, folded to by a mirror at 45 degrees
+        let detector = PlaneMirror::new([[-1.0, 2.0].into(), [1.0, 2.0].into()]);
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 1.0].into()])),
+            Box::new(detector),
+        ]));
+
+        // reflected up at x = 0.37, then on the detector
+        let result = simulation.trace(ray_from([-3.0, 0.37], [1.0, 0.0]), 2);
+        assert_eq!(result.len(), 2);
+        let (u, v) = result.detector_position(&detector).unwrap();
+        assert!((u - 1.37).abs() < 1e-5);
+        assert_eq!(v, 0.0);
+        assert_eq!(result.detector_hit(&detector, 0.1), Some((13, 0)));
+        assert_eq!(result.detector_hit(&detector, 0.5), Some((2, 0)));
+
+        // stopped before the detector, the last ray is followed to it
+        let result = simulation.trace(ray_from([-3.0, -0.42], [1.0, 0.0]), 1);
+        assert_eq!(result.detector_hit(&detector, 0.1), Some((5, 0)));
+        // left of the detector's first point
+        let result = simulation.trace(ray_from([-3.0, -1.25], [1.0, 0.0]), 1);
+        assert_eq!(result.detector_hit(&detector, 0.1), Some((-3, 0)));
+
+        // going away from the detector
+        let result = simulation.trace(ray_from([-3.0, 3.0], [-1.0, 0.0]), 1);
+        assert_eq!(result.detector_hit(&detector, 0.1), None);
+    }
+
+    #[test]
+    fn test_trace_many_budgeted() {
+        let simulation = Simulation::new(crate::scenes::two_mirror_cavity());
+        let rays = (0..50)
+            .map(|i| ray_from([0.0, i as f32 * 0.002], [1.0, 0.05]))
+            .collect::<Vec<_>>();
+
+        // the deadline already passed, nothing gets traced
+        let (results, remaining) = simulation.trace_many_budgeted(&rays, 1000, Instant::now());
+        assert!(results.is_empty());
+        assert_eq!(remaining, rays.len());
+
+        let far = Instant::now() + std::time::Duration::from_secs(3600);
+        let (results, remaining) = simulation.trace_many_budgeted(&rays, 10, far);
+        assert_eq!(remaining, 0);
+        assert_eq!(results, simulation.trace_many(&rays, 10));
+    }
+
+    #[test]
+    fn test_trace_recorded() {
+        let simulation = Simulation::new(crate::scenes::two_mirror_cavity());
+        let ray = ray_from([0.0, 0.1], [1.0, 0.05]);
+
+        let (result, records) = simulation.trace_recorded(ray, 20);
+
+        assert_eq!(result, simulation.trace(ray, 20));
+        assert_eq!(records.len(), 20);
+        assert_eq!(records[0].incident_ray, ray);
+        for (i, record) in records.iter().enumerate() {
+            assert_eq!(record.hit_point, result.path[i + 1]);
+            assert_eq!(record.reflected_ray.origin, record.hit_point);
+            // the cavity's mirrors take turns
+            assert_eq!(record.mirror_index, (i + 1) % 2);
+            if let Some(next) = records.get(i + 1) {
+                assert_eq!(next.incident_ray, record.reflected_ray);
+            }
+
+            // the angles of incidence and reflection are equal, on either side of the normal
+            let incident = record.incident_ray.direction.into_inner();
+            let normal = record.normal.into_inner();
+            let expected = incident - 2.0 * incident.dot(&normal) * normal;
+            assert!((record.reflected_ray.direction.into_inner() - expected).norm() < 1e-5);
+            assert!(incident.dot(&normal) < 0.0);
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        // bouncing at 45 degrees between a mirror reflecting everything and one keeping half
+        let simulation = Simulation::new(Scene::new(vec![
+            Box::new(PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])),
+            Box::new(
+                PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()]).with_reflectivity(0.5),
+            ),
+        ]));
+        let result = simulation.trace(ray_from([0.0, 0.5], [1.0, -1.0]), 3);
+
+        let report = result.report();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1 + result.len());
+        for column in ["bounce", "hit point", "incidence", "normal", "intensity"] {
+            assert!(lines[0].contains(column));
+        }
+
+        // columns are separated by at least two spaces
+        fn cells(line: &str) -> Vec<&str> {
+            line.split("  ")
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .collect()
+        }
+        assert_eq!(
+            cells(lines[1]),
+            [
+                "1",
+                "(0.5000, 0.0000)",
+                "45.00°",
+                "(0.0000, 1.0000)",
+                "1.0000"
+            ]
+        );
+        assert_eq!(
+            cells(lines[2]),
+            [
+                "2",
+                "(1.5000, 1.0000)",
+                "45.00°",
+                "(0.0000, -1.0000)",
+                "0.5000"
+            ]
+        );
+        assert_eq!(cells(lines[3])[4], "0.5000");
+
+        assert_eq!(
+            TraceResult::new(ray_from([0.0, 0.0], [1.0, 0.0]))
+                .report()
+                .lines()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_trace_bundle() {
+        // small spheres in a grid, with a bundle of rays fanning out towards a corner of it
+        let simulation = Simulation::new(Scene::new(
+            (0..100)
+                .map(|i| {
+                    Box::new(SphereMirror::new(
+                        [(i % 10) as f32 * 3.0, (i / 10) as f32 * 3.0].into(),
+                        1.0,
+                    )) as Box<dyn Mirror>
+                })
+                .collect(),
+        ));
+        let rays = (0..64)
+            .map(|i| {
+                let angle = 0.6 + i as f32 * 0.002;
+                ray_from([-5.0, -5.0 + i as f32 * 0.01], [angle.cos(), angle.sin()])
+            })
+            .collect::<Vec<_>>();
+
+        let candidates = simulation.scene().bundle_candidates(&rays);
+        assert!(!candidates.is_empty());
+        assert!(candidates.len() < 100);
+
+        let bundle = simulation.trace_bundle(&rays, 8);
+        assert_eq!(bundle.len(), rays.len());
+        for (ray, result) in rays.iter().zip(&bundle) {
+            assert_eq!(*result, simulation.trace(*ray, 8));
+        }
+        assert!(bundle.iter().any(|result| result.len() > 1));
+
+        // rays going every way keep every mirror
+        let everywhere = [
+            ray_from([0.0, 0.0], [1.0, 0.0]),
+            ray_from([0.0, 0.0], [-1.0, 0.1]),
+        ];
+        assert_eq!(simulation.scene().bundle_candidates(&everywhere).len(), 100);
+    }
+
+    #[test]
+    fn test_trace_polarized() {
+        // a periscope: two reflections at 45 degrees, each turning the ray by 90 degrees
+        let periscope = |retardance: f32| {
+            let mirror = |start: [f32; 2], end: [f32; 2]| {
+                Box::new(
+                    AnisotropicMirror::new(
+                        Box::new(SegmentMirror::new(start.into(), end.into())),
+                        0.9,
+                        0.9,
+                    )
+                    .with_retardance(retardance),
+                )
+            };
+            Simulation::new(Scene::new(vec![
+                mirror([0.0, 0.0], [1.0, 1.0]),
+                mirror([0.0, 3.0], [1.0, 4.0]),
+            ]))
+        };
+        let ray = ray_from([-1.0, 0.5], [1.0, 0.0]);
+        let angle = 30f32.to_radians();
+
+        // at 45 degrees, sin² = 1/2, so each reflection retards p by a quarter wave, which
+        // together make a half wave mirroring the polarization across the s direction
+        let (result, rotations) = periscope(PI).trace_polarized(ray, 10, angle);
+        assert_eq!(result.len(), 2);
+        assert_eq!(rotations.len(), 2);
+        assert!((rotations[0] + angle).abs() < 1e-5);
+        assert!((rotations.iter().sum::<f32>() + 2.0 * angle).abs() < 1e-5);
+
+        // without retardance, equal reflectivities leave the polarization alone
+        let (_, rotations) = periscope(0.0).trace_polarized(ray, 10, angle);
+        assert!(rotations.iter().all(|rotation| rotation.abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_trace_adaptive() {
+        // a ray bouncing up and down between two parallel mirrors
+        let cavity = |reflectivity: f32| {
+            Simulation::new(Scene::new(vec![
+                Box::new(
+                    PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()])
+                        .with_reflectivity(reflectivity),
+                ),
+                Box::new(
+                    PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])
+                        .with_reflectivity(reflectivity),
+                ),
+            ]))
+        };
+        let ray = ray_from([0.0, 0.5], [0.01, 1.0]);
+
+        // 0.99^229 > 0.1 > 0.99^230
+        let bright = cavity(0.99).trace_adaptive(ray, 0.1, 1000);
+        assert_eq!(bright.termination, Termination::Dimmed);
+        assert_eq!(bright.len(), 230);
+
+        // 0.5^3 > 0.1 > 0.5^4
+        let dim = cavity(0.5).trace_adaptive(ray, 0.1, 1000);
+        assert_eq!(dim.termination, Termination::Dimmed);
+        assert_eq!(dim.len(), 4);
+
+        let lossless = cavity(1.0).trace_adaptive(ray, 0.1, 1000);
+        assert_eq!(lossless.termination, Termination::MaxBounces);
+        assert_eq!(lossless.len(), 1000);
+    }
+}
@@ -0,0 +1,195 @@
+use nalgebra::{Point, SVector, Unit};
+
+use crate::{
+    error::MirrorError,
+    intersection::Intersection,
+    material::Polarization,
+    mirror::{degenerate, sort_by_distance, Mirror, PlaneMirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// A transparent plate with two parallel faces, like the substrate of a mirror, in a medium
+/// of index 1.
+///
+/// Tracing only follows reflections: both faces reflect the part of the light given by
+/// fresnel's equations. The light going through the plate is followed by
+/// [`SlabMirror::transmit`].
+#[derive(Clone, Copy)]
+pub struct SlabMirror {
+    front: PlaneMirror,
+    back: PlaneMirror,
+    thickness: f32,
+    refractive_index: f32,
+}
+
+impl Mirror for SlabMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        sort_by_distance(
+            self.front
+                .reflect(ray)
+                .into_iter()
+                .chain(self.back.reflect(ray))
+                .collect(),
+        )
+    }
+    fn get_type(&self) -> String {
+        "slab".to_string()
+    }
+    fn is_analytic(&self) -> bool {
+        true
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.front.contains_point(point, tolerance) || self.back.contains_point(point, tolerance)
+    }
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        let mut samples = self.front.sample_surface(n.div_ceil(2));
+        samples.extend(self.back.sample_surface(n / 2));
+        samples
+    }
+    /// The reflectivity at normal incidence, `((n - 1) / (n + 1))^2`.
+    fn reflectivity(&self) -> f32 {
+        let ratio = (self.refractive_index - 1.0) / (self.refractive_index + 1.0);
+        ratio * ratio
+    }
+    fn polarized_reflectivity(&self, polarization: Polarization, sin2_incidence: f32) -> f32 {
+        let n = self.refractive_index;
+        let cos_incidence = (1.0 - sin2_incidence).sqrt();
+        let cos_refraction = (1.0 - sin2_incidence / (n * n)).sqrt();
+
+        let s =
+            ((cos_incidence - n * cos_refraction) / (cos_incidence + n * cos_refraction)).powi(2);
+        let p =
+            ((n * cos_incidence - cos_refraction) / (n * cos_incidence + cos_refraction)).powi(2);
+
+        match polarization {
+            Polarization::S => s,
+            Polarization::P => p,
+            Polarization::Unpolarized => (s + p) / 2.0,
+        }
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if !(self.thickness.is_finite() && self.thickness > 0.0) {
+            degenerate(self, "its thickness isn't strictly positive")
+        } else if !(self.refractive_index.is_finite() && self.refractive_index >= 1.0) {
+            degenerate(self, "its refractive index is less than 1")
+        } else {
+            self.front.validate()
+        }
+    }
+}
+
+impl SlabMirror {
+    /// A plate whose front face is `front`, extending by `thickness` behind it (against the
+    /// normal of `front`). The reflectivity of `front` is ignored.
+    pub fn new(front: PlaneMirror, thickness: f32, refractive_index: f32) -> Self {
+        let offset = thickness * front.normal().into_inner();
+
+        Self {
+            front,
+            back: PlaneMirror::new(front.points().map(|point| point - offset)),
+            thickness,
+            refractive_index,
+        }
+    }
+
+    pub fn thickness(&self) -> f32 {
+        self.thickness
+    }
+
+    pub fn refractive_index(&self) -> f32 {
+        self.refractive_index
+    }
+
+    /// The ray leaving the other side of the plate after `ray` went through it, refracted by
+    /// snell's law on both faces. Since the faces are parallel, it leaves with the direction
+    /// it came in, shifted sideways. `None` if `ray` doesn't hit the plate.
+    pub fn transmit(&self, ray: &Ray) -> Option<Ray> {
+        let (entry_face, exit_face) = match (
+            self.front.reflect(*ray).first(),
+            self.back.reflect(*ray).first(),
+        ) {
+            (Some((front, _)), Some((back, _))) if back < front => (&self.back, &self.front),
+            (Some(_), _) => (&self.front, &self.back),
+            (None, Some(_)) => (&self.back, &self.front),
+            (None, None) => return None,
+        };
+
+        let inside = refract(entry_face, ray, 1.0 / self.refractive_index)?;
+        refract(exit_face, &inside, self.refractive_index)
+    }
+}
+
+/// The ray refracted where `ray` hits `face`, going from a medium to one whose index is
+/// `1 / ratio` times larger, or `None` if it misses or is totally reflected.
+fn refract(face: &PlaneMirror, ray: &Ray, ratio: f32) -> Option<Ray> {
+    let &(distance, matrix) = face.reflect(*ray).first()?;
+    let intersection = Intersection::new(ray, distance, matrix);
+    let normal = intersection.facing_normal(&ray.direction).into_inner();
+    let direction = ray.direction.into_inner();
+
+    let cos_incidence = -normal.dot(&direction);
+    let cos2_refraction = 1.0 - ratio * ratio * (1.0 - cos_incidence * cos_incidence);
+    if cos2_refraction < 0.0 {
+        return None;
+    }
+
+    let refracted: SVector<f32, DIM> =
+        ratio * direction + (ratio * cos_incidence - cos2_refraction.sqrt()) * normal;
+
+    Some(Ray {
+        origin: intersection.point,
+        direction: Unit::new_normalize(refracted),
+        time: ray.time + intersection.distance,
+        ..*ray
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::ray_from;
+
+    fn glass() -> SlabMirror {
+        // from y = 0 down to y = -1
+        let front = PlaneMirror::new([[0.0, 0.0].into(), [1.0, 0.0].into()]);
+        SlabMirror::new(front, 1.0, 1.5)
+    }
+
+    #[test]
+    fn test_transmit_shifts_sideways() {
+        let slab = glass();
+        let ray = ray_from([0.0, 1.0], [1.0, -1.0]);
+
+        let outgoing = slab.transmit(&ray).unwrap();
+
+        // sin 45° / 1.5 inside, so the ray crosses the glass with a slope of tan(asin(0.471))
+        let sin_refraction = std::f32::consts::FRAC_1_SQRT_2 / 1.5;
+        let run = sin_refraction / (1.0 - sin_refraction * sin_refraction).sqrt();
+        assert!((outgoing.origin - Point::from([1.0 + run, -1.0])).norm() < 1e-5);
+        assert!((outgoing.direction.into_inner() - ray.direction.into_inner()).norm() < 1e-5);
+
+        // the same from below, and rays missing the plate
+        let upwards = slab.transmit(&ray_from([0.0, -2.0], [1.0, 1.0])).unwrap();
+        assert!((upwards.origin - Point::from([1.0 + run, 0.0])).norm() < 1e-5);
+        assert_eq!(slab.transmit(&ray_from([0.0, 1.0], [1.0, 1.0])), None);
+    }
+
+    #[test]
+    fn test_fresnel_reflectivity() {
+        let slab = glass();
+
+        assert!((slab.reflectivity() - 0.04).abs() < 1e-6);
+        assert!((slab.polarized_reflectivity(Polarization::Unpolarized, 0.0) - 0.04).abs() < 1e-6);
+        // no p reflection at brewster's angle, tan θ = 1.5
+        let sin2_brewster = 1.5 * 1.5 / (1.0 + 1.5 * 1.5);
+        assert!(slab.polarized_reflectivity(Polarization::P, sin2_brewster) < 1e-6);
+        assert!(slab.polarized_reflectivity(Polarization::S, sin2_brewster) > 0.1);
+
+        // both faces reflect, the front first
+        let reflections = slab.reflect(ray_from([0.0, 1.0], [0.0, -1.0]));
+        assert_eq!(reflections.len(), 2);
+        assert!((reflections[0].0 - 1.0).abs() < 1e-6);
+        assert!((reflections[1].0 - 2.0).abs() < 1e-6);
+    }
+}
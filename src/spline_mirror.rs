@@ -0,0 +1,297 @@
+use nalgebra::{Point, SVector};
+
+use crate::{
+    bezier_mirror::BezierMirror,
+    error::MirrorError,
+    json, material,
+    math::solve_natural_tridiagonal,
+    mirror::{degenerate, enclosing_sphere, sort_resolving_seams, Mirror, Reflections},
+    ray::Ray,
+    DIM,
+};
+
+/// A natural cubic spline going through waypoints, for large mirrors which have to stay
+/// smooth. Both the slope and the curvature are continuous at the waypoints, and the
+/// curvature vanishes at both ends.
+///
+/// The spline has one cubic piece per pair of consecutive waypoints, each piece taking a
+/// unit interval of the parameter, and is reflected on as a chain of cubic bezier curves.
+pub struct SplineMirror {
+    waypoints: Vec<Point<f32, DIM>>,
+    segments: Vec<BezierMirror>,
+    reflectivity: f32,
+}
+
+impl Mirror for SplineMirror {
+    fn reflect(&self, ray: Ray) -> Reflections {
+        sort_resolving_seams(
+            self.segments
+                .iter()
+                .flat_map(|segment| segment.reflect(ray))
+                .collect(),
+            &ray,
+        )
+    }
+    fn get_type(&self) -> String {
+        "spline".to_string()
+    }
+    /// Intersections are found on each piece with a numerical root-finder.
+    fn is_analytic(&self) -> bool {
+        false
+    }
+    fn contains_point(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| segment.contains_point(point, tolerance))
+    }
+    /// Samples the parameter at regular intervals, so every piece gets as many points.
+    fn sample_surface(&self, n: usize) -> Vec<Point<f32, DIM>> {
+        let (start, end) = self.param_domain().unwrap_or_default();
+
+        (0..n)
+            .map(|i| {
+                let u = start + (end - start) * i as f32 / n.saturating_sub(1).max(1) as f32;
+                self.calculate_point(u)
+            })
+            .collect()
+    }
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+    /// The parameter goes from 0 at the first waypoint to 1 at the second one, and so on.
+    fn param_domain(&self) -> Option<(f32, f32)> {
+        (!self.segments.is_empty()).then_some((0.0, self.segments.len() as f32))
+    }
+    fn is_on_boundary(&self, point: &Point<f32, DIM>, tolerance: f32) -> bool {
+        [self.waypoints.first(), self.waypoints.last()]
+            .into_iter()
+            .flatten()
+            .any(|endpoint| (point - endpoint).norm() <= tolerance)
+    }
+    /// Each piece stays within the convex hull of its control points.
+    fn bounding_sphere(&self) -> Option<(Point<f32, DIM>, f32)> {
+        let points = self
+            .segments
+            .iter()
+            .flat_map(|segment| segment.control_points())
+            .map(|point| (*point, 0.0));
+        enclosing_sphere(&points.collect::<Vec<_>>())
+    }
+    fn validate(&self) -> Result<(), MirrorError> {
+        if self.waypoints.len() < 2 {
+            degenerate(self, "it has less than 2 waypoints")
+        } else if !self
+            .waypoints
+            .iter()
+            .all(|point| point.iter().all(|c| c.is_finite()))
+        {
+            degenerate(self, "one of its waypoints isn't finite")
+        } else if self.waypoints.windows(2).any(|pair| pair[0] == pair[1]) {
+            degenerate(self, "two consecutive waypoints are the same")
+        } else {
+            Ok(())
+        }
+    }
+    fn to_json(&self) -> Result<serde_json::Value, MirrorError> {
+        Ok(serde_json::json!({
+            "type": self.get_type(),
+            "waypoints": json::from_points(&self.waypoints),
+            "reflectivity": self.reflectivity,
+        }))
+    }
+}
+
+impl SplineMirror {
+    /// Fits the spline going through `waypoints`, in order.
+    pub fn new(waypoints: Vec<Point<f32, DIM>>) -> Self {
+        let second_derivatives = second_derivatives(&waypoints);
+        let segments = waypoints
+            .windows(2)
+            .zip(second_derivatives.windows(2))
+            .map(|(points, second)| {
+                // the first derivatives of the piece at both of its ends
+                let chord = points[1] - points[0];
+                let start = chord - (2.0 * second[0] + second[1]) / 6.0;
+                let end = chord + (second[0] + 2.0 * second[1]) / 6.0;
+
+                BezierMirror::new(vec![
+                    points[0],
+                    points[0] + start / 3.0,
+                    points[1] - end / 3.0,
+                    points[1],
+                ])
+            })
+            .collect();
+
+        Self {
+            waypoints,
+            segments,
+            reflectivity: 1.0,
+        }
+    }
+
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, MirrorError> {
+        /* example json
+        {
+            "waypoints": [
+                [1.0, 2.0],
+                [3.0, 4.0],
+                [5.0, 6.0],
+                ...
+            ],
+            "material": "silver" // or "reflectivity": 0.97, optional
+        }
+         */
+        let waypoints = json::points(json, "waypoints")?;
+
+        Ok(Self::new(waypoints).with_reflectivity(material::reflectivity_from_json(json)?))
+    }
+
+    pub fn waypoints(&self) -> &[Point<f32, DIM>] {
+        &self.waypoints
+    }
+
+    /// The cubic pieces of the spline, from each waypoint to the next.
+    pub fn segments(&self) -> &[BezierMirror] {
+        &self.segments
+    }
+
+    /// The point of the spline at `u`, within [`Mirror::param_domain`].
+    pub fn calculate_point(&self, u: f32) -> Point<f32, DIM> {
+        let Some(last) = self.segments.len().checked_sub(1) else {
+            return self.waypoints.first().copied().unwrap_or_default();
+        };
+        let index = (u.max(0.0) as usize).min(last);
+
+        self.segments[index].calculate_point((u - index as f32).clamp(0.0, 1.0))
+    }
+}
+
+/// The second derivatives of the natural cubic spline through `points` at each of them, with
+/// a unit parameter interval between consecutive points.
+///
+/// Continuity of the curvature at every interior point gives the tridiagonal system
+/// `m[i - 1] + 4 m[i] + m[i + 1] = 6 (p[i - 1] - 2 p[i] + p[i + 1])`, with `m` vanishing at
+/// both ends.
+fn second_derivatives(points: &[Point<f32, DIM>]) -> Vec<SVector<f32, DIM>> {
+    let n = points.len();
+    let rhs = (0..n)
+        .map(|i| {
+            if i == 0 || i == n - 1 {
+                SVector::zeros()
+            } else {
+                6.0 * (points[i - 1].coords - 2.0 * points[i].coords + points[i + 1].coords)
+            }
+        })
+        .collect();
+
+    solve_natural_tridiagonal(vec![4.0; n], &vec![1.0; n.saturating_sub(1)], rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mirror::{test_helpers::assert_reflects_like, PlaneMirror},
+        ray::ray_from,
+    };
+
+    fn wave() -> SplineMirror {
+        SplineMirror::new(
+            [[0.0, 0.0], [1.0, 1.0], [2.0, 0.5], [3.0, 2.0], [4.0, 0.0]]
+                .map(Point::from)
+                .to_vec(),
+        )
+    }
+
+    // the second derivatives of a cubic bezier curve at its start and end
+    fn end_second_derivatives(segment: &BezierMirror) -> (SVector<f32, DIM>, SVector<f32, DIM>) {
+        let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|i| segment.control_points()[i].coords);
+        (6.0 * (p0 - 2.0 * p1 + p2), 6.0 * (p1 - 2.0 * p2 + p3))
+    }
+
+    #[test]
+    fn test_second_derivatives_match() {
+        let spline = wave();
+        let segments = spline.segments();
+        assert_eq!(segments.len(), 4);
+
+        for pair in segments.windows(2) {
+            let (_, before) = end_second_derivatives(&pair[0]);
+            let (after, _) = end_second_derivatives(&pair[1]);
+            assert!((before - after).norm() < 1e-4);
+            assert!(before.norm() > 1e-2);
+
+            // and the slopes, like any C1 curve
+            assert!(
+                (pair[0].calculate_tangent(1.0) - pair[1].calculate_tangent(0.0)).norm() < 1e-4
+            );
+        }
+
+        // a natural spline is straight at both ends
+        assert!(end_second_derivatives(&segments[0]).0.norm() < 1e-4);
+        assert!(end_second_derivatives(&segments[3]).1.norm() < 1e-4);
+    }
+
+    #[test]
+    fn test_goes_through_waypoints() {
+        let spline = wave();
+
+        for (i, waypoint) in spline.waypoints().iter().enumerate() {
+            assert!((spline.calculate_point(i as f32) - waypoint).norm() < 1e-6);
+            assert!(spline.contains_point(waypoint, 1e-4));
+        }
+        assert!(spline.validate().is_ok());
+    }
+
+    #[test]
+    fn test_reflect_on_waypoint() {
+        let spline = wave();
+
+        // the pieces on both sides of a waypoint both reach it
+        let reflections = spline.reflect(ray_from([2.0, 3.0], [0.0, -1.0]));
+        assert_eq!(reflections.len(), 1);
+        assert!((reflections[0].0 - 2.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_straight_waypoints_reflect_like_a_plane() {
+        let spline = SplineMirror::new(
+            [[0.0, 0.0], [1.0, 0.5], [3.0, 1.5], [4.0, 2.0]]
+                .map(Point::from)
+                .to_vec(),
+        );
+        let plane = PlaneMirror::new([[0.0, 0.0].into(), [4.0, 2.0].into()]);
+
+        assert_reflects_like(&spline, &plane, &[ray_from([2.0, 4.0], [0.1, -1.0])]);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = serde_json::json!({
+            "waypoints": [[0.0, 0.0], [1.0, 1.0], [2.0, 0.5], [3.0, 2.0], [4.0, 0.0]],
+            "reflectivity": 0.9,
+        });
+
+        let spline = SplineMirror::from_json(&json).unwrap();
+        assert_eq!(spline.waypoints(), wave().waypoints());
+        assert_eq!(spline.reflectivity(), 0.9);
+        assert_eq!(
+            SplineMirror::from_json(&spline.to_json().unwrap())
+                .unwrap()
+                .waypoints(),
+            spline.waypoints()
+        );
+
+        assert!(SplineMirror::new(vec![[0.0, 0.0].into()])
+            .validate()
+            .is_err());
+    }
+}
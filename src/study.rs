@@ -0,0 +1,177 @@
+use nalgebra::{Point, Rotation2};
+
+use crate::{
+    analysis,
+    math::{from_local, to_local, Frame},
+    mirror::{sample_parameters, Mirror},
+    ray::Ray,
+    scene::Scene,
+    simulation::Simulation,
+    DIM,
+};
+
+/// Builds a scene for each parameter value, traces `rays` through it and returns the RMS size
+/// of the spot formed by the points where the rays end up, in the same order as `values`.
+///
+/// The rays are traced for at most `max_bounces` reflections, so the spot is usually measured
+/// on a detector-like mirror hit on the last bounce.
+pub fn sweep(
+    make_scene: impl Fn(f32) -> Scene,
+    values: &[f32],
+    rays: &[Ray],
+    max_bounces: usize,
+) -> Vec<f32> {
+    values
+        .iter()
+        .map(|value| {
+            let simulation = Simulation::new(make_scene(*value));
+            let spot = rays
+                .iter()
+                .map(|ray| *simulation.trace(*ray, max_bounces).path.last().unwrap())
+                .collect::<Vec<_>>();
+
+            analysis::rms_spot_size(&spot)
+        })
+        .collect()
+}
+
+/// Where `rays` end up while the mirrors move during an exposure from `start` to `end`,
+/// e.g. a scanning mirror. The scene built by `make_scene` at `samples` instants of the
+/// exposure is traced like in [`sweep`], and all the end points are returned together,
+/// forming the blurred spot a detector would record.
+pub fn exposure(
+    make_scene: impl Fn(f32) -> Scene,
+    (start, end): (f32, f32),
+    samples: usize,
+    rays: &[Ray],
+    max_bounces: usize,
+) -> Vec<Point<f32, DIM>> {
+    sample_parameters(samples)
+        .into_iter()
+        .flat_map(|t| {
+            let simulation = Simulation::new(make_scene(start + t * (end - start)));
+            rays.iter()
+                .map(|ray| *simulation.trace(*ray, max_bounces).path.last().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// How `base_mirror` reflects `ray` while it is rotated about `axis`, e.g. a scan mirror:
+/// for each of the `angles` (in radians, counterclockwise), the ray reflected by the rotated
+/// mirror, or `None` if it misses it. Only meaningful in 2D.
+pub fn angular_sweep(
+    base_mirror: &dyn Mirror,
+    axis: Point<f32, DIM>,
+    angles: &[f32],
+    ray: Ray,
+) -> Vec<Option<Ray>> {
+    angles
+        .iter()
+        .map(|angle| {
+            // the rotated mirror is the base one in a rotated frame
+            let frame = Frame::rotation_wrt_point(Rotation2::new(*angle), axis);
+            base_mirror
+                .reflect_ray(&to_local(&ray, &frame))
+                .map(|reflected| from_local(&reflected, &frame))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bezier_mirror::BezierMirror, mirror::PlaneMirror, ray::ray_from};
+
+    #[test]
+    fn test_sweep_curvature() {
+        // the parabola y = h * x^2 has its focus at 1 / 4h, and the detector is at y = 1
+        let make_scene = |h: f32| {
+            Scene::new(vec![
+                Box::new(BezierMirror::new(vec![
+                    [-1.0, h].into(),
+                    [0.0, -h].into(),
+                    [1.0, h].into(),
+                ])),
+                Box::new(PlaneMirror::new([[0.0, 1.0].into(), [1.0, 1.0].into()])),
+            ])
+        };
+        let rays = (-4..=4)
+            .filter(|i| *i != 0)
+            .map(|i| ray_from([i as f32 / 10.0, 0.5], [0.0, -1.0]))
+            .collect::<Vec<_>>();
+        let values = (1..=9).map(|i| i as f32 * 0.05).collect::<Vec<_>>();
+
+        let spot_sizes = sweep(make_scene, &values, &rays, 2);
+        assert_eq!(spot_sizes.len(), values.len());
+
+        let (best, _) = spot_sizes
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+
+        assert!((values[best] - 0.25).abs() < 1e-6);
+        assert!(spot_sizes[best] < 1e-3);
+        assert!(spot_sizes[0] > spot_sizes[best] && spot_sizes[8] > spot_sizes[best]);
+    }
+
+    #[test]
+    fn test_angular_sweep() {
+        // a fold mirror pivoting about the point it sends the ray up from
+        let mirror = PlaneMirror::new([[1.0, 1.0].into(), [2.0, 2.0].into()]);
+        let angles = [-0.2, -0.1, 0.0, 0.05, 0.3];
+        let ray = ray_from([-2.0, 1.0], [1.0, 0.0]);
+
+        let reflected = angular_sweep(&mirror, [1.0, 1.0].into(), &angles, ray);
+
+        assert_eq!(reflected.len(), angles.len());
+        for (angle, reflected) in angles.iter().zip(reflected) {
+            let reflected = reflected.unwrap();
+            let direction = reflected.direction;
+            let output_angle = direction[1].atan2(direction[0]);
+
+            assert!((reflected.origin - Point::from([1.0, 1.0])).norm() < 1e-5);
+            // the reflected ray turns twice as fast as the mirror
+            assert!((output_angle - (std::f32::consts::FRAC_PI_2 + 2.0 * angle)).abs() < 1e-5);
+        }
+
+        // turned edge on, the mirror is missed
+        let edge_on = angular_sweep(
+            &mirror,
+            [1.0, 1.0].into(),
+            &[-std::f32::consts::FRAC_PI_4],
+            ray,
+        );
+        assert!(edge_on[0].is_none());
+    }
+
+    #[test]
+    fn test_exposure_blur() {
+        // a fold mirror at the origin turning at `speed` radians per unit of time, sending
+        // the ray up to a detector at y = 2
+        let scanner = |speed: f32| {
+            move |time: f32| {
+                let angle = std::f32::consts::FRAC_PI_4 + speed * time;
+                Scene::new(vec![
+                    Box::new(PlaneMirror::new([
+                        [0.0, 0.0].into(),
+                        [angle.cos(), angle.sin()].into(),
+                    ])),
+                    Box::new(PlaneMirror::new([[0.0, 2.0].into(), [1.0, 2.0].into()])),
+                ])
+            }
+        };
+        let rays = [ray_from([-2.0, 0.0], [1.0, 0.0])];
+
+        let still = exposure(scanner(0.0), (0.0, 1.0), 16, &rays, 2);
+        let moving = exposure(scanner(0.05), (0.0, 1.0), 16, &rays, 2);
+
+        assert_eq!(still.len(), 16);
+        assert!((still[0] - Point::from([0.0, 2.0])).norm() < 1e-5);
+        assert!(analysis::rms_spot_size(&still) < 1e-5);
+        // the reflected beam turns twice as fast as the mirror
+        assert!(analysis::rms_spot_size(&moving) > 0.05);
+        assert!((moving[15][0] + 2.0 * (0.1f32).tan()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,57 @@
+//! Reading toml scenes by converting them to the json read by the `from_json` functions, so
+//! both formats share one schema.
+
+use serde_json::{Map, Number, Value};
+use toml_edit::{Document, Item};
+
+use crate::error::MirrorError;
+
+pub(crate) fn to_json(toml: &str) -> Result<Value, MirrorError> {
+    let document = toml
+        .parse::<Document>()
+        .map_err(|error| MirrorError::InvalidToml(error.to_string()))?;
+
+    Ok(table_to_json(document.as_table().iter()))
+}
+
+fn table_to_json<'a>(entries: impl Iterator<Item = (&'a str, &'a Item)>) -> Value {
+    Value::Object(
+        entries
+            .map(|(key, item)| (key.to_string(), item_to_json(item)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+fn item_to_json(item: &Item) -> Value {
+    match item {
+        Item::None => Value::Null,
+        Item::Value(value) => value_to_json(value),
+        Item::Table(table) => table_to_json(table.iter()),
+        Item::ArrayOfTables(tables) => Value::Array(
+            tables
+                .iter()
+                .map(|table| table_to_json(table.iter()))
+                .collect(),
+        ),
+    }
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(string) => Value::String(string.value().clone()),
+        toml_edit::Value::Integer(integer) => Value::from(*integer.value()),
+        // json has no infinities nor NaN
+        toml_edit::Value::Float(float) => {
+            Number::from_f64(*float.value()).map_or(Value::Null, Value::Number)
+        }
+        toml_edit::Value::Boolean(boolean) => Value::Bool(*boolean.value()),
+        toml_edit::Value::Datetime(datetime) => Value::String(datetime.value().to_string()),
+        toml_edit::Value::Array(array) => Value::Array(array.iter().map(value_to_json).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
@@ -0,0 +1,96 @@
+//! Cross-checks the numeric intersections of bezier curves against the closed-form ones of
+//! the mirror they approximate.
+
+use std::f32::consts::PI;
+
+use mirror_verse::{
+    bezier_mirror::BezierMirror,
+    circle_mirror::CircleMirror,
+    mirror::Mirror,
+    ray::{ray_from, Ray},
+    scene::Scene,
+    DIM,
+};
+use nalgebra::{Point, SVector};
+
+const RADIUS: f32 = 2.0;
+const START_ANGLE: f32 = 200.0 * PI / 180.0;
+const END_ANGLE: f32 = 340.0 * PI / 180.0;
+
+fn on_circle(angle: f32) -> Point<f32, DIM> {
+    Point::from([RADIUS * angle.cos(), RADIUS * angle.sin()])
+}
+
+fn tangent(angle: f32) -> SVector<f32, DIM> {
+    SVector::from([-angle.sin(), angle.cos()])
+}
+
+/// The arc as cubic pieces, each with its inner control points `4/3 tan(θ/4)` along the
+/// tangents at its ends, which is within 1e-6 of the circle for pieces of θ = 17.5°.
+fn bezier_arc(pieces: usize) -> Scene {
+    let span = (END_ANGLE - START_ANGLE) / pieces as f32;
+    let handle = RADIUS * 4.0 / 3.0 * (span / 4.0).tan();
+
+    Scene::new(
+        (0..pieces)
+            .map(|i| {
+                let (start, end) = (
+                    START_ANGLE + i as f32 * span,
+                    START_ANGLE + (i + 1) as f32 * span,
+                );
+                Box::new(BezierMirror::new(vec![
+                    on_circle(start),
+                    on_circle(start) + handle * tangent(start),
+                    on_circle(end) - handle * tangent(end),
+                    on_circle(end),
+                ])) as Box<dyn Mirror>
+            })
+            .collect(),
+    )
+}
+
+fn rays() -> Vec<Ray> {
+    (-10..=10)
+        .flat_map(|i| {
+            let x = i as f32 * 0.15;
+            [
+                ray_from([x, 0.5], [0.0, -1.0]),
+                ray_from([x, 0.5], [0.3, -1.0]),
+                ray_from([x, 0.5], [-0.7, -1.0]),
+            ]
+        })
+        .collect()
+}
+
+#[test]
+fn test_bezier_arc_reflects_like_circle() {
+    let circle = CircleMirror::arc(Point::origin(), RADIUS, START_ANGLE, END_ANGLE);
+    let bezier = bezier_arc(8);
+
+    for ray in rays() {
+        let expected = circle.reflect_ray(&ray);
+        let reflected = bezier
+            .nearest_hit(&ray)
+            .map(|(_, intersection)| intersection.reflected_ray(&ray));
+
+        match (expected, reflected) {
+            (Some(expected), Some(reflected)) => {
+                assert!(
+                    (expected.origin - reflected.origin).norm() < 1e-4,
+                    "{ray:?} hits {} instead of {}",
+                    reflected.origin,
+                    expected.origin,
+                );
+                assert!(
+                    (expected.direction.into_inner() - reflected.direction.into_inner()).norm()
+                        < 1e-3,
+                    "{ray:?} leaves along {} instead of {}",
+                    reflected.direction.into_inner(),
+                    expected.direction.into_inner(),
+                );
+            }
+            (None, None) => {}
+            _ => panic!("{ray:?} hits only one of the mirrors"),
+        }
+    }
+}